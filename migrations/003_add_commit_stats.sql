@@ -0,0 +1,4 @@
+-- 提交的文件变更统计（indexer.compute_stats 开启时填充，合并提交为 NULL）
+ALTER TABLE commits ADD COLUMN files_changed INTEGER;
+ALTER TABLE commits ADD COLUMN insertions INTEGER;
+ALTER TABLE commits ADD COLUMN deletions INTEGER;
@@ -0,0 +1,3 @@
+-- mailmap 映射前的原始作者信息（未启用 mailmap 或未被映射时为 NULL）
+ALTER TABLE commits ADD COLUMN raw_author_name TEXT;
+ALTER TABLE commits ADD COLUMN raw_author_email TEXT;
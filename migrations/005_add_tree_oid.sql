@@ -0,0 +1,2 @@
+-- 提交的根目录树 OID（此前已索引的提交为 NULL）
+ALTER TABLE commits ADD COLUMN tree_oid TEXT;
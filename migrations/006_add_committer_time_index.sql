@@ -0,0 +1,3 @@
+-- 不指定分支时按 committer_time 范围过滤与排序
+CREATE INDEX IF NOT EXISTS idx_commits_repository_committer_time
+ON commits(repository_id, committer_time DESC);
@@ -0,0 +1,2 @@
+-- 记录每个分支最后一次成功索引时的分支头，作为增量索引的起点
+ALTER TABLE branches ADD COLUMN indexed_tip_oid TEXT;
@@ -0,0 +1,15 @@
+-- 每个仓库当前或最近一次索引的进度，进程中断后据此从上次完成的分支之后继续
+CREATE TABLE IF NOT EXISTS index_progress (
+    repository_id INTEGER PRIMARY KEY,
+    state TEXT NOT NULL,
+    branches_total INTEGER NOT NULL DEFAULT 0,
+    branches_done INTEGER NOT NULL DEFAULT 0,
+    last_branch TEXT,
+    last_tip_oid TEXT,
+    commits_indexed INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    started_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    finished_at INTEGER,
+    FOREIGN KEY (repository_id) REFERENCES repositories(id) ON DELETE CASCADE
+);
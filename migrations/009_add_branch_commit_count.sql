@@ -0,0 +1,7 @@
+-- 每个分支已索引的提交数，索引完成后更新；已有数据在迁移时回填
+ALTER TABLE branches ADD COLUMN commit_count INTEGER NOT NULL DEFAULT 0;
+
+UPDATE branches SET commit_count = (
+    SELECT COUNT(*) FROM commits
+    WHERE commits.repository_id = branches.repository_id AND commits.branch = branches.name
+);
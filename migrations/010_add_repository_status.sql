@@ -0,0 +1,7 @@
+-- 仓库状态：磁盘上的路径不存在时标记为 missing，超过宽限期后删除
+ALTER TABLE repositories ADD COLUMN status TEXT NOT NULL DEFAULT 'active';
+ALTER TABLE repositories ADD COLUMN missing_since INTEGER;
+-- 根提交 OID 作为仓库的稳定标识：目录移动后据此关联原记录，经不同路径（如 bind mount）发现时据此去重
+ALTER TABLE repositories ADD COLUMN fingerprint TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_repositories_fingerprint ON repositories(fingerprint);
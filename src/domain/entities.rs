@@ -1,5 +1,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use crate::shared::result::Result;
+
+/// 未检测到默认分支时使用的名称
+pub const FALLBACK_DEFAULT_BRANCH: &str = "main";
 
 /// 仓库实体
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +16,11 @@ pub struct Repository {
     pub last_synced_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub status: RepositoryStatus,
+    /// 首次发现路径不存在的时间
+    pub missing_since: Option<DateTime<Utc>>,
+    /// 仓库指纹：HEAD 沿第一父提交回溯到的根提交 OID，不随路径变化，用于识别移动或重复挂载的仓库
+    pub fingerprint: Option<String>,
 }
 
 impl Repository {
@@ -22,10 +31,13 @@ impl Repository {
             name,
             path,
             description: None,
-            default_branch: "main".to_string(),
+            default_branch: FALLBACK_DEFAULT_BRANCH.to_string(),
             last_synced_at: None,
             created_at: now,
             updated_at: now,
+            status: RepositoryStatus::Active,
+            missing_since: None,
+            fingerprint: None,
         }
     }
 
@@ -38,6 +50,36 @@ impl Repository {
         self.last_synced_at = Some(Utc::now());
         self.updated_at = Utc::now();
     }
+
+    pub fn is_missing(&self) -> bool {
+        self.status == RepositoryStatus::Missing
+    }
+}
+
+/// 仓库状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepositoryStatus {
+    Active,
+    /// 磁盘上的路径已不存在（可能被移动或删除）
+    Missing,
+}
+
+impl RepositoryStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RepositoryStatus::Active => "active",
+            RepositoryStatus::Missing => "missing",
+        }
+    }
+
+    /// 解析数据库中的状态值，未知值视为 active
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "missing" => RepositoryStatus::Missing,
+            _ => RepositoryStatus::Active,
+        }
+    }
 }
 
 /// 提交实体
@@ -54,12 +96,24 @@ pub struct Commit {
     pub committer_email: String,
     pub committer_time: DateTime<Utc>,
     pub summary: String,
+    /// 提交信息正文（不含首行 summary）
     pub message: Option<String>,
-    pub parent_oids: Option<String>, // JSON array
+    /// 父提交 OID 的 JSON 数组（较早索引的提交为逗号分隔）
+    pub parent_oids: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// 文件变更统计（未开启 indexer.compute_stats 或合并提交时为 None）
+    pub files_changed: Option<i64>,
+    pub insertions: Option<i64>,
+    pub deletions: Option<i64>,
+    /// mailmap 映射前的原始作者（未被映射时为 None，author_* 即原始值）
+    pub raw_author_name: Option<String>,
+    pub raw_author_email: Option<String>,
+    /// 根目录树 OID（迁移前已索引的提交为 None）
+    pub tree_oid: Option<String>,
 }
 
 impl Commit {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         repository_id: i64,
         oid: String,
@@ -87,33 +141,63 @@ impl Commit {
             message: None,
             parent_oids: None,
             created_at: Utc::now(),
+            files_changed: None,
+            insertions: None,
+            deletions: None,
+            raw_author_name: None,
+            raw_author_email: None,
+            tree_oid: None,
         }
     }
 
+    /// 替换为 mailmap 规范化后的作者，原始值保存在 raw_author_*
+    pub fn with_canonical_author(mut self, name: String, email: String) -> Self {
+        if name != self.author_name || email != self.author_email {
+            self.raw_author_name = Some(std::mem::replace(&mut self.author_name, name));
+            self.raw_author_email = Some(std::mem::replace(&mut self.author_email, email));
+        }
+        self
+    }
+
+    pub fn with_stats(mut self, files_changed: i64, insertions: i64, deletions: i64) -> Self {
+        self.files_changed = Some(files_changed);
+        self.insertions = Some(insertions);
+        self.deletions = Some(deletions);
+        self
+    }
+
     pub fn with_message(mut self, message: String) -> Self {
         self.message = Some(message);
         self
     }
 
+    /// 完整提交信息：summary 加上正文（如有）
+    pub fn full_message(&self) -> String {
+        match self.message.as_deref().map(str::trim_end) {
+            Some(body) if !body.is_empty() => format!("{}\n\n{}", self.summary, body),
+            _ => self.summary.clone(),
+        }
+    }
+
+    pub fn with_tree(mut self, tree_oid: String) -> Self {
+        self.tree_oid = Some(tree_oid);
+        self
+    }
+
     pub fn with_parents(mut self, parents: Vec<String>) -> Self {
-        // TODO: 需要添加 serde_json 依赖
-        self.parent_oids = Some(parents.join(","));
+        self.parent_oids = Some(serde_json::Value::from(parents).to_string());
         self
     }
-}
 
-/// 标签实体
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Tag {
-    pub id: i64,
-    pub repository_id: i64,
-    pub name: String,
-    pub target_oid: String,
-    pub tagger_name: Option<String>,
-    pub tagger_email: Option<String>,
-    pub tagger_time: Option<DateTime<Utc>>,
-    pub message: Option<String>,
-    pub created_at: DateTime<Utc>,
+    /// 父提交 OID 列表
+    pub fn parents(&self) -> Result<Vec<String>> {
+        match self.parent_oids.as_deref().map(str::trim) {
+            Some(p) if p.starts_with('[') => Ok(serde_json::from_str(p)?),
+            // 兼容早期的逗号分隔格式
+            Some(p) => Ok(p.split(',').filter(|oid| !oid.is_empty()).map(String::from).collect()),
+            None => Ok(Vec::new()),
+        }
+    }
 }
 
 /// 分支实体
@@ -125,4 +209,123 @@ pub struct Branch {
     pub target_oid: String,
     pub is_default: bool,
     pub updated_at: DateTime<Utc>,
+    /// 已索引的提交数（分支索引完成后更新，可能滞后一个周期）
+    pub commit_count: i64,
+}
+
+/// 仓库索引进度（当前或最近一次索引）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexProgress {
+    pub repository_id: i64,
+    pub state: IndexState,
+    /// 本次需要索引的分支数
+    pub branches_total: i64,
+    /// 已完成的分支数
+    pub branches_done: i64,
+    /// 最后完成的分支及其索引到的分支头
+    pub last_branch: Option<String>,
+    pub last_tip_oid: Option<String>,
+    pub commits_indexed: i64,
+    /// 失败原因（`state` 为 failed 时）
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl IndexProgress {
+    /// 开始一次新的索引
+    pub fn start(repository_id: i64, branches_total: i64) -> Self {
+        let now = Utc::now();
+        Self {
+            repository_id,
+            state: IndexState::Running,
+            branches_total,
+            branches_done: 0,
+            last_branch: None,
+            last_tip_oid: None,
+            commits_indexed: 0,
+            error: None,
+            started_at: now,
+            updated_at: now,
+            finished_at: None,
+        }
+    }
+
+    /// 记录一个分支索引完成
+    pub fn branch_done(&mut self, branch: &str, tip_oid: &str, commits: usize) {
+        self.branches_done += 1;
+        self.last_branch = Some(branch.to_string());
+        self.last_tip_oid = Some(tip_oid.to_string());
+        self.commits_indexed += commits as i64;
+        self.updated_at = Utc::now();
+    }
+
+    /// 结束索引，`error` 为 None 表示成功
+    pub fn finish(&mut self, error: Option<String>) {
+        self.state = match error {
+            Some(_) => IndexState::Failed,
+            None => IndexState::Completed,
+        };
+        self.error = error;
+        self.updated_at = Utc::now();
+        self.finished_at = Some(self.updated_at);
+    }
+}
+
+/// 索引状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexState {
+    Running,
+    Completed,
+    Failed,
+    /// 进程在索引过程中退出，下次索引从上次完成的分支之后继续
+    Interrupted,
+}
+
+impl IndexState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IndexState::Running => "running",
+            IndexState::Completed => "completed",
+            IndexState::Failed => "failed",
+            IndexState::Interrupted => "interrupted",
+        }
+    }
+
+    /// 解析数据库中的状态值，未知值视为中断
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "running" => IndexState::Running,
+            "completed" => IndexState::Completed,
+            "failed" => IndexState::Failed,
+            _ => IndexState::Interrupted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parent_oids_formats() {
+        let now = Utc::now();
+        let commit = Commit::new(
+            1, "c".into(), "main".into(), "a".into(), "a@x".into(), now, "a".into(), "a@x".into(), now, "s".into(),
+        );
+        assert!(commit.parents().unwrap().is_empty());
+
+        let merge = commit.clone().with_parents(vec!["p1".into(), "p2".into()]);
+        assert_eq!(merge.parent_oids.as_deref(), Some(r#"["p1","p2"]"#));
+        assert_eq!(merge.parents().unwrap(), ["p1", "p2"]);
+
+        // 早期索引的逗号分隔格式
+        let legacy = Commit { parent_oids: Some("p1,p2".into()), ..commit.clone() };
+        assert_eq!(legacy.parents().unwrap(), ["p1", "p2"]);
+
+        let broken = Commit { parent_oids: Some("[\"p1\"".into()), ..commit };
+        assert!(matches!(broken.parents(), Err(crate::shared::error::GitxError::Json(_))));
+    }
 }
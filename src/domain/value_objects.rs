@@ -1,26 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
-/// 仓库 ID 值对象
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct RepositoryId(pub i64);
-
-impl RepositoryId {
-    pub fn new(id: i64) -> Self {
-        Self(id)
-    }
-
-    pub fn as_i64(&self) -> i64 {
-        self.0
-    }
-}
-
-impl fmt::Display for RepositoryId {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
-    }
-}
-
 /// 提交 SHA 值对象
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct CommitSha(String);
@@ -38,52 +18,33 @@ impl CommitSha {
         Ok(Self(sha))
     }
 
-    pub fn as_str(&self) -> &str {
-        &self.0
-    }
-}
+    /// 缩写的 SHA 前缀（至少 `MIN_ABBREV_LEN` 位），需经 `GitPort::resolve_oid` 解析为完整 OID
+    pub fn new_abbrev(sha: String) -> Result<Self, String> {
+        if sha.len() < Self::MIN_ABBREV_LEN || sha.len() > 40 {
+            return Err(format!("Invalid abbreviated commit SHA length: {}", sha.len()));
+        }
 
-impl fmt::Display for CommitSha {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        if !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("Invalid commit SHA: contains non-hex characters".to_string());
+        }
+
+        Ok(Self(sha.to_ascii_lowercase()))
     }
-}
 
-/// 分支名称值对象
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct BranchName(String);
+    /// 缩写 SHA 的最小长度
+    pub const MIN_ABBREV_LEN: usize = 4;
 
-impl BranchName {
-    pub fn new(name: String) -> Self {
-        Self(name)
+    /// 是否为完整的 40 位 SHA
+    pub fn is_full(&self) -> bool {
+        self.0.len() == 40
     }
 
     pub fn as_str(&self) -> &str {
         &self.0
     }
-
-    /// 移除 refs/remotes/origin/ 前缀
-    pub fn short_name(&self) -> &str {
-        self.0
-            .strip_prefix("refs/remotes/origin/")
-            .or_else(|| self.0.strip_prefix("refs/heads/"))
-            .unwrap_or(&self.0)
-    }
-}
-
-impl From<String> for BranchName {
-    fn from(s: String) -> Self {
-        Self::new(s)
-    }
 }
 
-impl From<&str> for BranchName {
-    fn from(s: &str) -> Self {
-        Self::new(s.to_string())
-    }
-}
-
-impl fmt::Display for BranchName {
+impl fmt::Display for CommitSha {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
     }
@@ -0,0 +1,225 @@
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+use crate::ports::cache::CachePort;
+use crate::shared::error::GitxError;
+use crate::shared::result::Result;
+
+/// 淘汰时清理到上限的该比例，避免每次写入都触发扫描
+const PRUNE_TARGET_RATIO: f64 = 0.9;
+
+/// 磁盘缓存：每个键一个文件（`{root}/{key}.bin`），适合不可变内容（如提交 diff）
+///
+/// 条目不过期，总大小超过上限时按最近访问时间（文件 mtime）淘汰。
+pub struct DiskCache {
+    root: PathBuf,
+    max_bytes: u64,
+    used_bytes: AtomicU64,
+    prune_lock: tokio::sync::Mutex<()>,
+}
+
+impl DiskCache {
+    /// 创建缓存目录并统计已有条目大小
+    pub fn new(root: PathBuf, max_bytes: u64) -> Result<Self> {
+        std::fs::create_dir_all(&root)?;
+        let used: u64 = list_entries(&root)?.iter().map(|e| e.size).sum();
+        tracing::info!("Disk cache at {} ({} bytes used, limit {})", root.display(), used, max_bytes);
+
+        Ok(Self {
+            root,
+            max_bytes,
+            used_bytes: AtomicU64::new(used),
+            prune_lock: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// 键按 `/` 分段映射为子目录，每段只允许字母、数字、`.`、`_`、`-`
+    fn entry_path(&self, key: &str) -> Result<PathBuf> {
+        let valid = key.split('/').all(|seg| {
+            !seg.is_empty()
+                && seg != "."
+                && seg != ".."
+                && seg.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        });
+        if !valid {
+            return Err(GitxError::InvalidRequest(format!("invalid cache key: {}", key)));
+        }
+        Ok(self.root.join(format!("{}.bin", key)))
+    }
+
+    pub(crate) async fn get_raw(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.entry_path(key)?;
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => {
+                // 更新 mtime 作为最近访问时间，失败不影响读取
+                let _ = tokio::task::spawn_blocking(move || {
+                    std::fs::File::options()
+                        .write(true)
+                        .open(&path)
+                        .and_then(|f| f.set_modified(SystemTime::now()))
+                })
+                .await;
+                Ok(Some(bytes))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub(crate) async fn set_raw(&self, key: &str, bytes: &[u8]) -> Result<()> {
+        let path = self.entry_path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // 先写临时文件再重命名，读取方不会看到写了一半的条目
+        let old_size = tokio::fs::metadata(&path).await.map(|m| m.len()).unwrap_or(0);
+        let tmp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+        tokio::fs::write(&tmp, bytes).await?;
+        if let Err(e) = tokio::fs::rename(&tmp, &path).await {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            return Err(e.into());
+        }
+
+        self.used_bytes.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+        self.used_bytes.fetch_sub(old_size.min(self.used_bytes.load(Ordering::Relaxed)), Ordering::Relaxed);
+
+        if self.used_bytes.load(Ordering::Relaxed) > self.max_bytes {
+            self.prune().await?;
+        }
+        Ok(())
+    }
+
+    /// 删除最久未访问的条目，直到总大小降到上限的 90%
+    async fn prune(&self) -> Result<()> {
+        let _guard = self.prune_lock.lock().await;
+        if self.used_bytes.load(Ordering::Relaxed) <= self.max_bytes {
+            return Ok(());
+        }
+
+        let root = self.root.clone();
+        let target = (self.max_bytes as f64 * PRUNE_TARGET_RATIO) as u64;
+        let (used, removed) = tokio::task::spawn_blocking(move || -> Result<(u64, usize)> {
+            let mut entries = list_entries(&root)?;
+            entries.sort_by_key(|e| e.modified);
+
+            let mut used: u64 = entries.iter().map(|e| e.size).sum();
+            let mut removed = 0;
+            for entry in entries {
+                if used <= target {
+                    break;
+                }
+                match std::fs::remove_file(&entry.path) {
+                    Ok(()) => {
+                        used -= entry.size;
+                        removed += 1;
+                    }
+                    Err(e) => tracing::warn!("Failed to remove cache entry {}: {}", entry.path.display(), e),
+                }
+            }
+            Ok((used, removed))
+        })
+        .await
+        .map_err(|e| GitxError::Internal(format!("Task join error: {}", e)))??;
+
+        self.used_bytes.store(used, Ordering::Relaxed);
+        tracing::debug!("Pruned {} disk cache entries, {} bytes used", removed, used);
+        Ok(())
+    }
+}
+
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// 递归列出缓存目录下的所有文件
+fn list_entries(root: &Path) -> Result<Vec<CacheEntry>> {
+    let mut entries = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let meta = entry.metadata()?;
+            if meta.is_dir() {
+                dirs.push(entry.path());
+            } else {
+                entries.push(CacheEntry {
+                    path: entry.path(),
+                    size: meta.len(),
+                    modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                });
+            }
+        }
+    }
+    Ok(entries)
+}
+
+#[async_trait]
+impl CachePort for DiskCache {
+    async fn get<T: DeserializeOwned + Send>(&self, key: &str) -> Result<Option<T>> {
+        match self.get_raw(key).await? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 磁盘条目不过期，忽略 TTL
+    async fn set<T: Serialize + Send + Sync>(&self, key: &str, value: &T, _ttl: Duration) -> Result<()> {
+        let bytes = bincode::serialize(value)?;
+        self.set_raw(key, &bytes).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let path = self.entry_path(key)?;
+        match tokio::fs::metadata(&path).await {
+            Ok(meta) => {
+                tokio::fs::remove_file(&path).await?;
+                self.used_bytes.fetch_sub(meta.len().min(self.used_bytes.load(Ordering::Relaxed)), Ordering::Relaxed);
+                Ok(())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_disk_cache_roundtrip_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DiskCache::new(dir.path().join("cache"), 250).unwrap();
+        let ttl = Duration::from_secs(60);
+
+        cache.set("commit/1/aaa", &"x".repeat(100), ttl).await.unwrap();
+        assert_eq!(cache.get::<String>("commit/1/aaa").await.unwrap(), Some("x".repeat(100)));
+        assert_eq!(cache.get::<String>("commit/1/missing").await.unwrap(), None);
+
+        // 键不能逃出缓存目录
+        assert!(cache.get::<String>("../etc/passwd").await.is_err());
+        assert!(cache.set("a//b", &1u8, ttl).await.is_err());
+
+        // 超过上限时淘汰最久未访问的条目
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        cache.set("commit/1/bbb", &"y".repeat(100), ttl).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        cache.get::<String>("commit/1/aaa").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        cache.set("commit/1/ccc", &"z".repeat(100), ttl).await.unwrap();
+
+        let exists = |key: &str| cache.entry_path(key).unwrap().exists();
+        assert!(exists("commit/1/aaa"));
+        assert!(!exists("commit/1/bbb"));
+        assert!(exists("commit/1/ccc"));
+
+        // 重新打开时统计已有条目
+        let reopened = DiskCache::new(dir.path().join("cache"), 250).unwrap();
+        assert_eq!(reopened.used_bytes.load(Ordering::Relaxed), cache.used_bytes.load(Ordering::Relaxed));
+    }
+}
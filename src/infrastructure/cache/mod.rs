@@ -1,3 +1,7 @@
 pub mod moka_cache;
+pub mod disk_cache;
+pub mod tiered_cache;
 
 pub use moka_cache::MokaCache;
+pub use disk_cache::DiskCache;
+pub use tiered_cache::TieredCache;
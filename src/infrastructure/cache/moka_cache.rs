@@ -20,6 +20,14 @@ impl MokaCache {
 
         Self { cache }
     }
+
+    pub(crate) async fn get_raw(&self, key: &str) -> Option<Vec<u8>> {
+        self.cache.get(key).await
+    }
+
+    pub(crate) async fn set_raw(&self, key: &str, bytes: Vec<u8>) {
+        self.cache.insert(key.to_string(), bytes).await;
+    }
 }
 
 #[async_trait]
@@ -48,13 +56,4 @@ impl CachePort for MokaCache {
         self.cache.invalidate(&key.to_string()).await;
         Ok(())
     }
-
-    async fn exists(&self, key: &str) -> Result<bool> {
-        Ok(self.cache.contains_key(&key.to_string()))
-    }
-
-    async fn clear(&self) -> Result<()> {
-        self.cache.invalidate_all();
-        Ok(())
-    }
 }
@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use std::time::Duration;
+use crate::infrastructure::cache::{DiskCache, MokaCache};
+use crate::ports::cache::CachePort;
+use crate::shared::result::Result;
+
+/// 两级缓存：先查内存，未命中再查磁盘（命中后回填内存）
+///
+/// 磁盘层是尽力而为的，读写失败只记录日志，不影响请求。
+pub struct TieredCache {
+    memory: MokaCache,
+    disk: Option<DiskCache>,
+}
+
+impl TieredCache {
+    pub fn new(memory: MokaCache, disk: Option<DiskCache>) -> Self {
+        Self { memory, disk }
+    }
+}
+
+#[async_trait]
+impl CachePort for TieredCache {
+    async fn get<T: DeserializeOwned + Send>(&self, key: &str) -> Result<Option<T>> {
+        if let Some(bytes) = self.memory.get_raw(key).await {
+            return Ok(Some(bincode::deserialize(&bytes)?));
+        }
+
+        let Some(disk) = &self.disk else {
+            return Ok(None);
+        };
+        match disk.get_raw(key).await {
+            Ok(Some(bytes)) => match bincode::deserialize(&bytes) {
+                Ok(value) => {
+                    self.memory.set_raw(key, bytes).await;
+                    Ok(Some(value))
+                }
+                Err(e) => {
+                    // 格式不兼容的旧条目视为未命中
+                    tracing::warn!("Discarding unreadable disk cache entry {}: {}", key, e);
+                    let _ = disk.delete(key).await;
+                    Ok(None)
+                }
+            },
+            Ok(None) => Ok(None),
+            Err(e) => {
+                tracing::warn!("Disk cache read failed for {}: {}", key, e);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn set<T: Serialize + Send + Sync>(&self, key: &str, value: &T, _ttl: Duration) -> Result<()> {
+        let bytes = bincode::serialize(value)?;
+        if let Some(disk) = &self.disk {
+            if let Err(e) = disk.set_raw(key, &bytes).await {
+                tracing::warn!("Disk cache write failed for {}: {}", key, e);
+            }
+        }
+        self.memory.set_raw(key, bytes).await;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.memory.delete(key).await?;
+        if let Some(disk) = &self.disk {
+            disk.delete(key).await?;
+        }
+        Ok(())
+    }
+}
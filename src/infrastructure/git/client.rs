@@ -1,67 +1,361 @@
 use async_trait::async_trait;
-use git2::{Oid, Repository, Sort, DiffOptions, DiffFormat};
-use std::path::Path;
+use git2::{Oid, Repository, Sort, Diff, DiffOptions, DiffFindOptions, DiffFormat, Patch};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
 use crate::ports::git::{
     GitPort, FetchResult, GitCommit, GitBranch, GitTag, 
-    GitCommitDetail, GitDiff, GitDiffPatch
+    GitCommitDetail, GitDiff, GitDiffPatch, GitFileDiff, DeltaStatus, BlameLine, CommitStats, FileStat, GitBlob, GitTreeEntry, TreeEntryKind, DiffHunk, DiffHunkLine,
+    DEFAULT_CONTEXT_LINES, MAX_CONTEXT_LINES,
 };
+use crate::infrastructure::git::gitmodules::parse_gitmodules;
+use crate::infrastructure::git::intraline::{escape_html, IntralineBuffer};
+use crate::infrastructure::git::repo_pool::RepoPool;
+use crate::infrastructure::git::signature::parse_signature;
+#[cfg(feature = "highlight")]
+use crate::infrastructure::git::highlight::DiffHighlighter;
+use crate::shared::config::{GitConfig, Secret, WalkMode};
 use crate::shared::result::Result;
 use crate::shared::error::GitxError;
 
 /// Git 客户端实现（基于 git2-rs）
 pub struct Git2Client {
-    // 可以添加配置，如 SSH 密钥路径等
+    config: GitConfig,
+    /// 限制同时执行的阻塞 git 操作数，超出的请求排队等待
+    blocking_permits: Arc<Semaphore>,
+    /// 复用已打开的仓库句柄
+    repos: Arc<RepoPool>,
 }
 
+/// 未指定时阻塞 git 操作的并发上限
+const DEFAULT_BLOCKING_LIMIT: usize = 8;
+
 impl Git2Client {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(config: GitConfig) -> Self {
+        Self::with_blocking_limit(config, DEFAULT_BLOCKING_LIMIT)
+    }
+
+    /// 指定阻塞 git 操作的并发上限
+    ///
+    /// 上限越小内存占用越可控，但索引大仓库时页面请求（diff、blame）需要排队等待。
+    pub fn with_blocking_limit(config: GitConfig, limit: usize) -> Self {
+        Self {
+            repos: Arc::new(RepoPool::new(config.repo_cache_size)),
+            config,
+            blocking_permits: Arc::new(Semaphore::new(limit.max(1))),
+        }
+    }
+
+    /// 从 git2 提交提取元数据
+    fn to_git_commit(commit: &git2::Commit<'_>) -> GitCommit {
+        let author = commit.author();
+        let committer = commit.committer();
+
+        GitCommit {
+            oid: commit.id().to_string(),
+            author_name: String::from_utf8_lossy(author.name_bytes()).to_string(),
+            author_email: String::from_utf8_lossy(author.email_bytes()).to_string(),
+            author_time: author.when().seconds(),
+            committer_name: String::from_utf8_lossy(committer.name_bytes()).to_string(),
+            committer_email: String::from_utf8_lossy(committer.email_bytes()).to_string(),
+            committer_time: committer.when().seconds(),
+            summary: commit.summary().unwrap_or("").to_string(),
+            message: commit.body().map(String::from),
+            parent_oids: commit.parent_ids().map(|id| id.to_string()).collect(),
+            tree_oid: commit.tree_id().to_string(),
+        }
+    }
+
+    /// 标签引用转为 GitTag，标注标签剥离到其指向的提交并读取 tagger 信息
+    fn to_git_tag(name: &str, reference: &git2::Reference<'_>) -> Result<GitTag> {
+        let target_oid = match reference.peel_to_commit() {
+            Ok(commit) => commit.id(),
+            Err(_) => reference.target().ok_or(GitxError::InvalidRef)?,
+        };
+
+        let (tagger_name, tagger_email, tagger_time, message) = if let Ok(tag) = reference.peel_to_tag() {
+            let tagger = tag.tagger();
+            (
+                tagger.as_ref().map(|t| String::from_utf8_lossy(t.name_bytes()).to_string()),
+                tagger.as_ref().map(|t| String::from_utf8_lossy(t.email_bytes()).to_string()),
+                tagger.as_ref().map(|t| t.when().seconds()),
+                tag.message().map(String::from),
+            )
+        } else {
+            (None, None, None, None)
+        };
+
+        Ok(GitTag {
+            name: name.to_string(),
+            target_oid: target_oid.to_string(),
+            tagger_name,
+            tagger_email,
+            tagger_time,
+            message,
+        })
+    }
+
+    /// diff 选项：上下文行数默认 3 行，超过上限时截断
+    fn diff_options(context_lines: Option<u32>) -> DiffOptions {
+        let mut opts = DiffOptions::new();
+        opts.context_lines(context_lines.unwrap_or(DEFAULT_CONTEXT_LINES).min(MAX_CONTEXT_LINES));
+        opts
+    }
+
+    /// 启用重命名/复制检测
+    fn find_similar(diff: &mut Diff<'_>, enabled: bool) -> Result<()> {
+        if enabled {
+            let mut find_opts = DiffFindOptions::new();
+            find_opts.renames(true).copies(true);
+            diff.find_similar(Some(&mut find_opts))?;
+        }
+        Ok(())
+    }
+
+    /// git2 的 Delta 映射为稳定的变更类型
+    fn delta_status(delta: git2::Delta) -> DeltaStatus {
+        match delta {
+            git2::Delta::Added => DeltaStatus::Added,
+            git2::Delta::Deleted => DeltaStatus::Deleted,
+            git2::Delta::Modified => DeltaStatus::Modified,
+            git2::Delta::Renamed => DeltaStatus::Renamed,
+            git2::Delta::Copied => DeltaStatus::Copied,
+            git2::Delta::Typechange => DeltaStatus::TypeChanged,
+            _ => DeltaStatus::Other,
+        }
+    }
+
+    /// 读取 blob 是否为二进制及其大小，零 OID（新增/删除的一侧）返回 (false, 0)
+    fn blob_info(repo: &Repository, id: Oid) -> (bool, u64) {
+        if id.is_zero() {
+            return (false, 0);
+        }
+        repo.find_blob(id)
+            .map(|blob| (blob.is_binary(), blob.size() as u64))
+            .unwrap_or((false, 0))
+    }
+
+    /// 追加一行统一 diff 原文：内容行需要补上 +/-/空格 前缀，文件头、hunk 头等已包含完整文本
+    fn push_plain_line(plain: &mut Vec<u8>, line: &git2::DiffLine) {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            plain.push(line.origin() as u8);
+        }
+        plain.extend_from_slice(line.content());
+    }
+
+    /// 记录结构化 diff：hunk 头开始新的 hunk，内容行附带新旧行号
+    fn push_hunk_line(
+        hunks: &mut Vec<DiffHunk>,
+        delta: &git2::DiffDelta<'_>,
+        hunk: Option<&git2::DiffHunk<'_>>,
+        line: &git2::DiffLine<'_>,
+        content: &str,
+    ) {
+        match line.origin() {
+            'H' => {
+                let Some(hunk) = hunk else { return };
+                let path = |file: git2::DiffFile<'_>| file.path().map(|p| p.to_string_lossy().into_owned());
+                hunks.push(DiffHunk {
+                    old_path: path(delta.old_file()),
+                    new_path: path(delta.new_file()),
+                    header: String::from_utf8_lossy(hunk.header()).trim_end().to_string(),
+                    old_start: hunk.old_start(),
+                    old_lines: hunk.old_lines(),
+                    new_start: hunk.new_start(),
+                    new_lines: hunk.new_lines(),
+                    lines: Vec::new(),
+                });
+            }
+            origin @ ('+' | '-' | ' ' | '=' | '>' | '<') => {
+                if let Some(current) = hunks.last_mut() {
+                    let content = content.strip_suffix('\n').unwrap_or(content);
+                    current.lines.push(DiffHunkLine {
+                        origin,
+                        old_lineno: line.old_lineno(),
+                        new_lineno: line.new_lineno(),
+                        content: content.to_string(),
+                        html: escape_html(content),
+                    });
+                }
+            }
+            _ => {}
+        }
     }
 
-    /// 在线程池中执行阻塞的 Git 操作
-    async fn run_blocking<F, T>(f: F) -> Result<T>
+    /// 累计每个文件的增删行数：遇到新文件时追加一项
+    fn count_file_line(stats: &mut Vec<FileStat>, delta: &git2::DiffDelta<'_>, line: &git2::DiffLine<'_>) {
+        let file = delta.new_file().path().or_else(|| delta.old_file().path());
+        let path = file.map(|p| p.to_string_lossy().into_owned()).unwrap_or_default();
+        if stats.last().is_none_or(|s| s.path != path) {
+            stats.push(FileStat { path, insertions: 0, deletions: 0 });
+        }
+        let Some(current) = stats.last_mut() else { return };
+        match line.origin() {
+            '+' => current.insertions += 1,
+            '-' => current.deletions += 1,
+            _ => {}
+        }
+    }
+
+    /// 按行类型包装一行 diff HTML，内容行同时按输出顺序记入 `rendered`
+    fn push_diff_line(html: &mut String, rendered: &mut Vec<String>, origin: char, body: &str) {
+        if matches!(origin, '+' | '-' | ' ') {
+            rendered.push(body.replace('\n', ""));
+        }
+        match origin {
+            '+' => html.push_str(&format!("<span class=\"diff-add-line\">{}</span>", body)),
+            '-' => html.push_str(&format!("<span class=\"diff-remove-line\">{}</span>", body)),
+            ' ' => html.push_str(&format!("<span class=\"diff-context\">{}</span>", body)),
+            _ => html.push_str(body),
+        }
+    }
+
+    /// 二进制文件的 diff 条目，代替 git 的 "Binary files differ" 文本
+    fn binary_change_html(repo: &Repository, delta: &git2::DiffDelta<'_>) -> String {
+        let (_, old_size) = Self::blob_info(repo, delta.old_file().id());
+        let (_, new_size) = Self::blob_info(repo, delta.new_file().id());
+        format!(
+            "<span class=\"diff-binary\">Binary file changed ({} → {} bytes)</span>\n",
+            old_size, new_size
+        )
+    }
+
+    /// 在 Tokio 阻塞线程池中执行 git 操作，并发数受 `blocking_permits` 限制
+    async fn run_blocking<F, T>(&self, f: F) -> Result<T>
     where
         F: FnOnce() -> Result<T> + Send + 'static,
         T: Send + 'static,
     {
-        tokio::task::spawn_blocking(f)
+        // 许可随闭包移入阻塞线程，请求被取消时也要等操作真正结束才释放
+        let permit = self.blocking_permits
+            .clone()
+            .acquire_owned()
             .await
-            .map_err(|e| GitxError::Internal(format!("Task join error: {}", e)))?
+            .map_err(|e| GitxError::Internal(e.to_string()))?;
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            f()
+        })
+        .await
+        .map_err(|e| GitxError::Internal(format!("Task join error: {}", e)))?
+    }
+
+    /// 解析仓库使用的凭证
+    ///
+    /// token 优先级：按仓库名配置 > GITX_HTTPS_TOKEN_<REPO> > 全局配置 > GITX_HTTPS_TOKEN
+    fn credentials_for(&self, repo_name: &str) -> GitCredentials {
+        let repo_env = format!(
+            "GITX_HTTPS_TOKEN_{}",
+            repo_name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+                .collect::<String>()
+        );
+
+        let https_token = self.config.https_tokens.get(repo_name).cloned()
+            .or_else(|| std::env::var(&repo_env).ok().map(Secret::new))
+            .or_else(|| self.config.https_token.clone())
+            .or_else(|| std::env::var("GITX_HTTPS_TOKEN").ok().map(Secret::new));
+
+        GitCredentials {
+            ssh_key_path: self.config.ssh_key_path.clone(),
+            https_username: self.config.https_username.clone(),
+            https_token,
+        }
     }
 
-    /// Git 凭证回调（SSH 密钥认证）
+    /// Git 凭证回调（HTTPS token / SSH 密钥认证）
     fn git_credentials(
-        _url: &str,
+        creds: &GitCredentials,
+        attempts: &mut usize,
         username: Option<&str>,
-        _allowed: git2::CredentialType,
+        allowed: git2::CredentialType,
     ) -> std::result::Result<git2::Cred, git2::Error> {
-        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+        // libgit2 在认证失败时会反复调用回调，避免死循环
+        *attempts += 1;
+        if *attempts > 5 {
+            return Err(git2::Error::from_str("authentication failed after repeated attempts"));
+        }
+
+        if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
+            if let Some(token) = &creds.https_token {
+                let user = creds.https_username.as_deref()
+                    .or(username)
+                    .unwrap_or("x-access-token");
+                return git2::Cred::userpass_plaintext(user, token.expose());
+            }
+        }
+
+        // SSH URL 未包含用户名时，libgit2 会先单独请求用户名
+        if allowed == git2::CredentialType::USERNAME {
+            return git2::Cred::username(username.unwrap_or("git"));
+        }
+
+        // 默认使用 SSH 密钥
+        let private_key = creds.ssh_key_path.clone().unwrap_or_else(|| {
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+            PathBuf::from(format!("{}/.ssh/id_rsa", home))
+        });
+        let public_key = PathBuf::from(format!("{}.pub", private_key.display()));
         git2::Cred::ssh_key(
             username.unwrap_or("git"),
-            Some(Path::new(&format!("{}/.ssh/id_rsa.pub", home))),
-            Path::new(&format!("{}/.ssh/id_rsa", home)),
+            public_key.exists().then_some(public_key.as_path()),
+            &private_key,
             None,
         )
     }
 }
 
+/// 单次 fetch 使用的凭证
+#[derive(Clone)]
+struct GitCredentials {
+    ssh_key_path: Option<PathBuf>,
+    https_username: Option<String>,
+    https_token: Option<Secret>,
+}
+
 impl Default for Git2Client {
     fn default() -> Self {
-        Self::new()
+        Self::new(GitConfig::default())
     }
 }
 
 #[async_trait]
 impl GitPort for Git2Client {
-    async fn fetch_repository(&self, path: &Path) -> Result<FetchResult> {
+    async fn fetch_repository(&self, path: &Path, remote: &str) -> Result<FetchResult> {
         let path = path.to_path_buf();
+        let remote_name = remote.to_string();
+        // 仓库名与发现服务一致：取路径最后一段
+        let repo_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let creds = self.credentials_for(&repo_name);
+        let repos = self.repos.clone();
         
-        Self::run_blocking(move || {
-            let repo = Repository::open(&path)?;
-            let mut remote = repo.find_remote("origin")?;
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            let mut remote = match repo.find_remote(&remote_name) {
+                Ok(remote) => remote,
+                Err(e) if e.code() == git2::ErrorCode::NotFound => {
+                    let available = repo.remotes()?;
+                    let available: Vec<&str> = available.iter().flatten().collect();
+                    return Err(GitxError::Config(format!(
+                        "remote '{}' not found in {} (available: {})",
+                        remote_name,
+                        path.display(),
+                        if available.is_empty() { "none".to_string() } else { available.join(", ") }
+                    )));
+                }
+                Err(e) => return Err(e.into()),
+            };
             
             let mut callbacks = git2::RemoteCallbacks::new();
-            callbacks.credentials(Self::git_credentials);
+            let mut attempts = 0;
+            callbacks.credentials(move |_url, username, allowed| {
+                Self::git_credentials(&creds, &mut attempts, username, allowed)
+            });
             
             // 进度回调
             callbacks.transfer_progress(|stats| {
@@ -83,19 +377,24 @@ impl GitPort for Git2Client {
 
             let mut fetch_options = git2::FetchOptions::new();
             fetch_options.remote_callbacks(callbacks);
+            // 清理上游已删除分支的远程跟踪引用，索引时据此删除过期分支
+            fetch_options.prune(git2::FetchPrune::On);
             
             // Fetch all refs
             remote.fetch(&[] as &[&str], Some(&mut fetch_options), None)?;
+            // 其它空闲句柄可能缓存了旧的 packed-refs，丢弃后按需重新打开
+            repos.invalidate(&path);
             
-            // 获取更新的分支列表
+            // 获取该远程下更新的分支列表
+            let prefix = format!("{}/", remote_name);
             let branches_updated: Vec<String> = repo
                 .branches(Some(git2::BranchType::Remote))?
                 .filter_map(|b| b.ok())
                 .filter_map(|(branch, _)| branch.name().ok().flatten().map(String::from))
+                .filter(|name| name.starts_with(&prefix))
                 .collect();
 
             Ok(FetchResult {
-                commits_fetched: 0, // TODO: 计算新增提交数
                 branches_updated,
             })
         })
@@ -108,26 +407,45 @@ impl GitPort for Git2Client {
         branch: &str,
         limit: usize,
         since_oid: Option<&str>,
+        include_merges: bool,
+        walk: WalkMode,
     ) -> Result<Vec<GitCommit>> {
         let path = path.to_path_buf();
         let branch = branch.to_string();
         let since_oid = since_oid.map(String::from);
+        let repos = self.repos.clone();
         
-        Self::run_blocking(move || {
-            let repo = Repository::open(&path)?;
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
             // 检查分支是否存在
             let _reference = repo.find_reference(&branch)?;
             
             let mut revwalk = repo.revwalk()?;
-            revwalk.set_sorting(Sort::TIME)?;
             revwalk.push_ref(&branch)?;
             
             let mut commits = Vec::new();
-            let since_oid_parsed = if let Some(ref oid_str) = since_oid {
+            let mut since_oid_parsed = if let Some(ref oid_str) = since_oid {
                 Some(Oid::from_str(oid_str)?)
             } else {
                 None
             };
+
+            let include_merges = match walk {
+                WalkMode::Linear => {
+                    revwalk.set_sorting(Sort::TIME)?;
+                    include_merges
+                }
+                WalkMode::Topological => {
+                    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+                    // 隐藏上次索引的提交及其祖先，合并引入的旁支提交不会因提前停止而遗漏
+                    if let Some(since) = since_oid_parsed.take() {
+                        if let Err(e) = revwalk.hide(since) {
+                            tracing::warn!("Last indexed commit {} not found, walking full history: {}", since, e);
+                        }
+                    }
+                    true
+                }
+            };
             
             for (idx, oid) in revwalk.enumerate() {
                 if idx >= limit {
@@ -146,25 +464,11 @@ impl GitPort for Git2Client {
                 let commit = repo.find_commit(oid)?;
                 
                 // 跳过合并提交
-                if commit.parent_count() > 1 {
+                if !include_merges && commit.parent_count() > 1 {
                     continue;
                 }
                 
-                let author = commit.author();
-                let committer = commit.committer();
-                
-                commits.push(GitCommit {
-                    oid: commit.id().to_string(),
-                    author_name: String::from_utf8_lossy(author.name_bytes()).to_string(),
-                    author_email: String::from_utf8_lossy(author.email_bytes()).to_string(),
-                    author_time: author.when().seconds(),
-                    committer_name: String::from_utf8_lossy(committer.name_bytes()).to_string(),
-                    committer_email: String::from_utf8_lossy(committer.email_bytes()).to_string(),
-                    committer_time: committer.when().seconds(),
-                    summary: commit.summary().unwrap_or("").to_string(),
-                    message: commit.body().map(String::from),
-                    parent_oids: commit.parent_ids().map(|id| id.to_string()).collect(),
-                });
+                commits.push(Self::to_git_commit(&commit));
             }
             
             Ok(commits)
@@ -172,17 +476,335 @@ impl GitPort for Git2Client {
         .await
     }
 
+    async fn commit_stats(&self, path: &Path, oids: &[String]) -> Result<Vec<CommitStats>> {
+        let path = path.to_path_buf();
+        let oids = oids.to_vec();
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            let mut results = Vec::with_capacity(oids.len());
+
+            for oid in &oids {
+                let commit = repo.find_commit(Oid::from_str(oid)?)?;
+                let parent_tree = if commit.parent_count() > 0 {
+                    Some(commit.parent(0)?.tree()?)
+                } else {
+                    None
+                };
+                // 只需要统计，不做重命名检测
+                let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+                let stats = diff.stats()?;
+                results.push(CommitStats {
+                    files_changed: stats.files_changed(),
+                    insertions: stats.insertions(),
+                    deletions: stats.deletions(),
+                });
+            }
+
+            Ok(results)
+        })
+        .await
+    }
+
+    async fn is_bare(&self, path: &Path) -> Result<bool> {
+        let path = path.to_path_buf();
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || Ok(repos.open(&path)?.is_bare())).await
+    }
+
+    async fn resolve_oid(&self, path: &Path, prefix: &str) -> Result<String> {
+        let path = path.to_path_buf();
+        let prefix = prefix.to_string();
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            let found = repo.find_commit_by_prefix(&prefix).map(|commit| commit.id());
+            match found {
+                Ok(oid) => Ok(oid.to_string()),
+                Err(e) if e.code() == git2::ErrorCode::NotFound => Err(GitxError::CommitNotFound(prefix)),
+                Err(e) if e.code() == git2::ErrorCode::Ambiguous => Err(GitxError::AmbiguousOid(prefix)),
+                Err(e) => Err(e.into()),
+            }
+        })
+        .await
+    }
+
+    async fn is_descendant_of(&self, path: &Path, oid: &str, ancestor: &str) -> Result<bool> {
+        let path = path.to_path_buf();
+        let oid = Oid::from_str(oid)?;
+        let ancestor = Oid::from_str(ancestor)?;
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            if oid == ancestor {
+                return Ok(true);
+            }
+            // 强制推送后旧提交可能已被回收
+            if repo.find_commit(ancestor).is_err() {
+                return Ok(false);
+            }
+            Ok(repo.graph_descendant_of(oid, ancestor)?)
+        })
+        .await
+    }
+
+    async fn merge_base(&self, path: &Path, a_oid: &str, b_oid: &str) -> Result<Option<String>> {
+        let path = path.to_path_buf();
+        let a = Oid::from_str(a_oid)?;
+        let b = Oid::from_str(b_oid)?;
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            match repo.merge_base(a, b) {
+                Ok(oid) => Ok(Some(oid.to_string())),
+                Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        })
+        .await
+    }
+
+    async fn ahead_behind(&self, path: &Path, oid: &str, upstream: &str) -> Result<(usize, usize)> {
+        let path = path.to_path_buf();
+        let oid = Oid::from_str(oid)?;
+        let upstream = Oid::from_str(upstream)?;
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            Ok(repo.graph_ahead_behind(oid, upstream)?)
+        })
+        .await
+    }
+
+    async fn object_storage_size(&self, path: &Path) -> Result<u64> {
+        let path = path.to_path_buf();
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            // 只统计文件大小，不遍历对象，超大仓库也能很快返回
+            let mut total = 0;
+            let mut pending = vec![repo.path().join("objects")];
+            while let Some(dir) = pending.pop() {
+                for entry in std::fs::read_dir(&dir)? {
+                    let entry = entry?;
+                    let file_type = entry.file_type()?;
+                    if file_type.is_dir() {
+                        pending.push(entry.path());
+                    } else if file_type.is_file() {
+                        total += entry.metadata()?.len();
+                    }
+                }
+            }
+            Ok(total)
+        })
+        .await
+    }
+
+    async fn root_commit_oid(&self, path: &Path) -> Result<Option<String>> {
+        let path = path.to_path_buf();
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            let mut revwalk = repo.revwalk()?;
+            // 工作区仓库可能只有远程分支，HEAD 无法解析时改从所有分支回溯
+            if revwalk.push_head().is_err() {
+                revwalk.push_glob("refs/heads/*")?;
+                revwalk.push_glob("refs/remotes/*")?;
+            }
+            revwalk.simplify_first_parent()?;
+            revwalk.set_sorting(git2::Sort::TOPOLOGICAL)?;
+
+            let mut root = None;
+            for oid in revwalk {
+                root = Some(oid?);
+            }
+            Ok(root.map(|oid| oid.to_string()))
+        })
+        .await
+    }
+
+    async fn default_branch(&self, path: &Path, remote: &str) -> Result<Option<String>> {
+        let path = path.to_path_buf();
+        let remote_head = format!("refs/remotes/{}/HEAD", remote);
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            // origin/HEAD -> refs/remotes/origin/<branch>，裸仓库的 HEAD -> refs/heads/<branch>
+            let head_ref = if repo.is_bare() { "HEAD" } else { remote_head.as_str() };
+            let target = match repo.find_reference(head_ref) {
+                Ok(reference) => reference.symbolic_target().map(String::from),
+                Err(e) if e.code() == git2::ErrorCode::NotFound => None,
+                Err(e) => return Err(e.into()),
+            };
+
+            Ok(target.and_then(|t| {
+                t.strip_prefix("refs/remotes/")
+                    .or_else(|| t.strip_prefix("refs/heads/"))
+                    .map(String::from)
+            }))
+        })
+        .await
+    }
+
+    async fn read_file(&self, path: &Path, rev: &str, file_path: &str) -> Result<Option<Vec<u8>>> {
+        let path = path.to_path_buf();
+        let rev = rev.to_string();
+        let file_path = file_path.to_string();
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            let tree = repo.revparse_single(&rev)?.peel_to_tree()?;
+            let entry = match tree.get_path(Path::new(&file_path)) {
+                Ok(entry) => entry,
+                Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+                Err(e) => return Err(e.into()),
+            };
+            let content = entry.to_object(&repo)?.into_blob().ok().map(|blob| blob.content().to_vec());
+            Ok(content)
+        })
+        .await
+    }
+
+    async fn read_blob(&self, path: &Path, rev: &str, file_path: &str) -> Result<GitBlob> {
+        let path = path.to_path_buf();
+        let rev = rev.to_string();
+        let file_path = file_path.to_string();
+        let max_bytes = self.config.max_raw_bytes;
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            let not_found = || GitxError::FileNotFound(format!("{}:{}", rev, file_path));
+            let tree = repo.revparse_single(&rev).map_err(|_| not_found())?.peel_to_tree()?;
+            let entry = match tree.get_path(Path::new(&file_path)) {
+                Ok(entry) => entry,
+                Err(e) if e.code() == git2::ErrorCode::NotFound => return Err(not_found()),
+                Err(e) => return Err(e.into()),
+            };
+            if entry.kind() != Some(git2::ObjectType::Blob) {
+                return Err(not_found());
+            }
+
+            // 先读对象头判断大小，避免把超大文件整个载入内存
+            let (size, _) = repo.odb()?.read_header(entry.id())?;
+            if size > max_bytes {
+                return Err(GitxError::InvalidRequest(format!(
+                    "{} is too large ({} bytes, limit {})",
+                    file_path, size, max_bytes
+                )));
+            }
+
+            let blob = repo.find_blob(entry.id())?;
+            Ok(GitBlob {
+                content: blob.content().to_vec(),
+                is_binary: blob.is_binary(),
+            })
+        })
+        .await
+    }
+
+    async fn list_tree(&self, path: &Path, rev: &str, dir: &str) -> Result<Vec<GitTreeEntry>> {
+        let path = path.to_path_buf();
+        let rev = rev.to_string();
+        let dir = dir.trim_matches('/').to_string();
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            let not_found = || GitxError::FileNotFound(format!("{}:{}", rev, dir));
+            let root = repo.revparse_single(&rev).map_err(|_| not_found())?.peel_to_tree()?;
+            let tree = if dir.is_empty() {
+                root.clone()
+            } else {
+                let entry = match root.get_path(Path::new(&dir)) {
+                    Ok(entry) => entry,
+                    Err(e) if e.code() == git2::ErrorCode::NotFound => return Err(not_found()),
+                    Err(e) => return Err(e.into()),
+                };
+                // 子模块的提交不在本仓库中，不能当作目录打开
+                if entry.kind() != Some(git2::ObjectType::Tree) {
+                    return Err(not_found());
+                }
+                repo.find_tree(entry.id())?
+            };
+
+            // 子模块 URL 取自同一版本根目录的 .gitmodules
+            let mut submodule_urls = None;
+            let odb = repo.odb()?;
+            let mut entries = Vec::with_capacity(tree.len());
+            for entry in tree.iter() {
+                let name = String::from_utf8_lossy(entry.name_bytes()).into_owned();
+                let full_path = if dir.is_empty() { name.clone() } else { format!("{}/{}", dir, name) };
+                let kind = match entry.filemode() {
+                    m if m == i32::from(git2::FileMode::Tree) => TreeEntryKind::Directory,
+                    m if m == i32::from(git2::FileMode::Commit) => TreeEntryKind::Submodule,
+                    m if m == i32::from(git2::FileMode::Link) => TreeEntryKind::Symlink,
+                    _ => TreeEntryKind::File,
+                };
+                let (size, submodule_url) = match kind {
+                    TreeEntryKind::Directory => (None, None),
+                    TreeEntryKind::Submodule => {
+                        let urls = submodule_urls.get_or_insert_with(|| {
+                            root.get_path(Path::new(".gitmodules"))
+                                .ok()
+                                .and_then(|e| repo.find_blob(e.id()).ok())
+                                .map(|blob| parse_gitmodules(&String::from_utf8_lossy(blob.content())))
+                                .unwrap_or_default()
+                        });
+                        (None, urls.get(&full_path).cloned())
+                    }
+                    _ => (odb.read_header(entry.id()).ok().map(|(size, _)| size as u64), None),
+                };
+                entries.push(GitTreeEntry {
+                    name,
+                    path: full_path,
+                    kind,
+                    oid: entry.id().to_string(),
+                    mode: entry.filemode(),
+                    size,
+                    submodule_url,
+                });
+            }
+
+            entries.sort_by(|a, b| {
+                (b.kind == TreeEntryKind::Directory)
+                    .cmp(&(a.kind == TreeEntryKind::Directory))
+                    .then_with(|| a.name.cmp(&b.name))
+            });
+            Ok(entries)
+        })
+        .await
+    }
+
     async fn list_branches(&self, path: &Path) -> Result<Vec<GitBranch>> {
         let path = path.to_path_buf();
+        let repos = self.repos.clone();
         
-        Self::run_blocking(move || {
-            let repo = Repository::open(&path)?;
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
             let head = repo.head().ok();
             let head_name = head.as_ref().and_then(|h| h.name()).map(String::from);
             
+            // 服务器上的裸仓库没有远程跟踪分支，直接使用本地分支
+            let branch_type = if repo.is_bare() {
+                git2::BranchType::Local
+            } else {
+                git2::BranchType::Remote
+            };
+            
             let mut branches = Vec::new();
             
-            for branch in repo.branches(Some(git2::BranchType::Remote))? {
+            for branch in repo.branches(Some(branch_type))? {
                 let (branch, _) = match branch {
                     Ok(b) => b,
                     Err(e) => {
@@ -209,10 +831,13 @@ impl GitPort for Git2Client {
                     }
                 };
                 
+                let ref_name = branch.get().name().unwrap_or_default().to_string();
+                
                 branches.push(GitBranch {
-                    name: name.clone(),
+                    is_head: head_name.as_ref().is_some_and(|h| h == &ref_name),
+                    name,
+                    ref_name,
                     target_oid: target.to_string(),
-                    is_head: head_name.as_ref().map_or(false, |h| h == &name),
                 });
             }
             
@@ -223,67 +848,88 @@ impl GitPort for Git2Client {
 
     async fn list_tags(&self, path: &Path) -> Result<Vec<GitTag>> {
         let path = path.to_path_buf();
+        let repos = self.repos.clone();
         
-        Self::run_blocking(move || {
-            let repo = Repository::open(&path)?;
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
             let mut tags = Vec::new();
             
             for tag_name in repo.tag_names(None)?.iter().flatten() {
                 let reference = repo.find_reference(&format!("refs/tags/{}", tag_name))?;
-                let target_oid = reference.target().ok_or(GitxError::InvalidRef)?;
-                
-                // 尝试获取标注标签信息
-                let (tagger_name, tagger_email, tagger_time, message) = if let Ok(tag) = reference.peel_to_tag() {
-                    let tagger = tag.tagger();
-                    (
-                        tagger.as_ref().map(|t| String::from_utf8_lossy(t.name_bytes()).to_string()),
-                        tagger.as_ref().map(|t| String::from_utf8_lossy(t.email_bytes()).to_string()),
-                        tagger.as_ref().map(|t| t.when().seconds()),
-                        tag.message().map(String::from),
-                    )
-                } else {
-                    (None, None, None, None)
-                };
-                
-                tags.push(GitTag {
-                    name: tag_name.to_string(),
-                    target_oid: target_oid.to_string(),
-                    tagger_name,
-                    tagger_email,
-                    tagger_time,
-                    message,
-                });
+                tags.push(Self::to_git_tag(tag_name, &reference)?);
             }
             
+            // 标注标签按创建时间倒序，轻量标签（无 tagger）排在其后按名称倒序
+            tags.sort_by(|a, b| b.tagger_time.cmp(&a.tagger_time).then_with(|| b.name.cmp(&a.name)));
             Ok(tags)
         })
         .await
     }
 
-    async fn get_commit_detail(&self, path: &Path, oid: &str) -> Result<GitCommitDetail> {
+    async fn find_tag(&self, path: &Path, name: &str) -> Result<Option<GitTag>> {
+        let path = path.to_path_buf();
+        let name = name.to_string();
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            // 显式查找 refs/tags/，同名分支不会被误认为标签
+            let reference = match repo.find_reference(&format!("refs/tags/{}", name)) {
+                Ok(reference) => reference,
+                Err(e) if matches!(e.code(), git2::ErrorCode::NotFound | git2::ErrorCode::InvalidSpec) => return Ok(None),
+                Err(e) => return Err(e.into()),
+            };
+            Self::to_git_tag(&name, &reference).map(Some)
+        })
+        .await
+    }
+
+    async fn get_commit(&self, path: &Path, oid: &str) -> Result<GitCommit> {
+        let path = path.to_path_buf();
+        let oid_str = oid.to_string();
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            let oid = Oid::from_str(&oid_str)?;
+            let commit = match repo.find_commit(oid) {
+                Ok(commit) => commit,
+                Err(e) if e.code() == git2::ErrorCode::NotFound => return Err(GitxError::CommitNotFound(oid_str)),
+                Err(e) => return Err(e.into()),
+            };
+            Ok(Self::to_git_commit(&commit))
+        })
+        .await
+    }
+
+    async fn get_commit_detail(&self, path: &Path, oid: &str, context_lines: Option<u32>) -> Result<GitCommitDetail> {
         let path = path.to_path_buf();
         let oid_str = oid.to_string();
+        let rename_detection = self.config.rename_detection;
+        let intraline_diff = self.config.intraline_diff;
+        let max_diff_lines = self.config.max_diff_lines;
+        let repos = self.repos.clone();
         
-        Self::run_blocking(move || {
-            let repo = Repository::open(&path)?;
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
             let oid = Oid::from_str(&oid_str)?;
-            let commit = repo.find_commit(oid)?;
+            let commit = match repo.find_commit(oid) {
+                Ok(commit) => commit,
+                Err(e) if e.code() == git2::ErrorCode::NotFound => return Err(GitxError::CommitNotFound(oid_str)),
+                Err(e) => return Err(e.into()),
+            };
             
             // 获取提交基本信息
-            let author = commit.author();
-            let committer = commit.committer();
+            let git_commit = Self::to_git_commit(&commit);
             
-            let git_commit = GitCommit {
-                oid: commit.id().to_string(),
-                author_name: String::from_utf8_lossy(author.name_bytes()).to_string(),
-                author_email: String::from_utf8_lossy(author.email_bytes()).to_string(),
-                author_time: author.when().seconds(),
-                committer_name: String::from_utf8_lossy(committer.name_bytes()).to_string(),
-                committer_email: String::from_utf8_lossy(committer.email_bytes()).to_string(),
-                committer_time: committer.when().seconds(),
-                summary: commit.summary().unwrap_or("").to_string(),
-                message: commit.body().map(String::from),
-                parent_oids: commit.parent_ids().map(|id| id.to_string()).collect(),
+            // 读取签名（未签名的提交返回 NotFound）
+            let signature = match repo.extract_signature(&oid, None) {
+                Ok((sig, _signed_data)) => Some(parse_signature(&sig)),
+                Err(e) if e.code() == git2::ErrorCode::NotFound => None,
+                Err(e) => {
+                    tracing::warn!("Failed to extract signature for {}: {}", oid, e);
+                    None
+                }
             };
             
             // 计算 diff
@@ -294,11 +940,12 @@ impl GitPort for Git2Client {
                 None
             };
             
-            let diff = repo.diff_tree_to_tree(
+            let mut diff = repo.diff_tree_to_tree(
                 parent_tree.as_ref(),
                 Some(&tree),
-                Some(&mut DiffOptions::new()),
+                Some(&mut Self::diff_options(context_lines)),
             )?;
+            Self::find_similar(&mut diff, rename_detection)?;
             
             // 获取 diff 统计信息
             let stats = diff.stats()?;
@@ -308,52 +955,258 @@ impl GitPort for Git2Client {
                 stats.insertions(),
                 stats.deletions()
             );
+
+            // 超大 diff 不渲染 HTML，只保留原文供下载
+            let diff_too_large = max_diff_lines > 0 && stats.insertions() + stats.deletions() > max_diff_lines;
+            if diff_too_large {
+                tracing::debug!("Skipping diff rendering for {}: {}", oid, diff_stats);
+                let mut diff_plain = Vec::new();
+                let mut file_stats = Vec::new();
+                diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+                    Self::push_plain_line(&mut diff_plain, &line);
+                    Self::count_file_line(&mut file_stats, &delta, &line);
+                    true
+                })?;
+                return Ok(GitCommitDetail {
+                    commit: git_commit,
+                    diff_stats,
+                    diff_html: String::new(),
+                    diff_too_large,
+                    diff_plain,
+                    file_stats,
+                    hunks: Vec::new(),
+                    signature,
+                });
+            }
             
             // 生成 diff HTML（保持git格式）
             let mut diff_html = String::new();
             let mut diff_plain = Vec::new();
-            
-            diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+            let mut file_stats = Vec::new();
+            let mut hunks = Vec::new();
+            let mut rendered = Vec::new();
+            #[cfg(feature = "highlight")]
+            let mut highlighter = DiffHighlighter::new();
+            let mut intraline = intraline_diff.then(IntralineBuffer::new);
+
+            diff.print(DiffFormat::Patch, |delta, hunk, line| {
                 let content = String::from_utf8_lossy(line.content());
-                diff_plain.extend_from_slice(line.content());
-                
+                Self::push_plain_line(&mut diff_plain, &line);
+                Self::count_file_line(&mut file_stats, &delta, &line);
+                Self::push_hunk_line(&mut hunks, &delta, hunk.as_ref(), &line, &content);
+
                 // HTML转义
                 let escaped = content
                     .replace('&', "&amp;")
                     .replace('<', "&lt;")
                     .replace('>', "&gt;");
-                
-                match line.origin() {
-                    '+' => diff_html.push_str(&format!("<span class=\"diff-add-line\">{}</span>", escaped)),
-                    '-' => diff_html.push_str(&format!("<span class=\"diff-remove-line\">{}</span>", escaped)),
-                    ' ' => diff_html.push_str(&format!("<span class=\"diff-context\"> {}</span>", escaped)),
-                    _ => diff_html.push_str(&escaped),
+
+                // 语法高亮新文件一侧，未知文件类型回退到纯文本
+                #[cfg(feature = "highlight")]
+                let body = match line.origin() {
+                    '+' | ' ' => highlighter
+                        .highlight_new_line(delta.new_file().path(), &content)
+                        .unwrap_or(escaped),
+                    'H' => {
+                        highlighter.reset();
+                        escaped
+                    }
+                    _ => escaped,
+                };
+                #[cfg(not(feature = "highlight"))]
+                let body = escaped;
+
+                // 二进制文件不渲染内容，只显示大小变化
+                if line.origin() == 'B' || (delta.flags().is_binary() && line.origin() != 'F') {
+                    diff_html.push_str(&Self::binary_change_html(&repo, &delta));
+                    return true;
+                }
+
+                // 相邻的删除/新增行先缓存，成对后再输出单词级 diff
+                if let Some(buffer) = intraline.as_mut() {
+                    match line.origin() {
+                        '-' => {
+                            for (origin, body) in buffer.push_removed(&content) {
+                                Self::push_diff_line(&mut diff_html, &mut rendered, origin, &body);
+                            }
+                            return true;
+                        }
+                        '+' => {
+                            buffer.push_added(&content, body);
+                            return true;
+                        }
+                        _ => {
+                            for (origin, body) in buffer.flush() {
+                                Self::push_diff_line(&mut diff_html, &mut rendered, origin, &body);
+                            }
+                        }
+                    }
                 }
+
+                Self::push_diff_line(&mut diff_html, &mut rendered, line.origin(), &body);
                 true
             })?;
+            if let Some(buffer) = intraline.as_mut() {
+                for (origin, body) in buffer.flush() {
+                    Self::push_diff_line(&mut diff_html, &mut rendered, origin, &body);
+                }
+            }
+
+            // 删除行总在同组新增行之前，渲染顺序与 hunk 中的内容行一致
+            let content_lines = hunks
+                .iter_mut()
+                .flat_map(|h| h.lines.iter_mut())
+                .filter(|l| matches!(l.origin, '+' | '-' | ' '));
+            for (line, html) in content_lines.zip(rendered) {
+                line.html = html;
+            }
             
             Ok(GitCommitDetail {
                 commit: git_commit,
                 diff_stats,
                 diff_html,
+                diff_too_large,
                 diff_plain,
+                file_stats,
+                hunks,
+                signature,
             })
         })
         .await
     }
 
+    async fn file_diff(
+        &self,
+        path: &Path,
+        oid: &str,
+        file_path: &str,
+        context_lines: Option<u32>,
+    ) -> Result<Option<GitFileDiff>> {
+        let path = path.to_path_buf();
+        let oid_str = oid.to_string();
+        let file_path = file_path.to_string();
+        let rename_detection = self.config.rename_detection;
+        let max_diff_lines = self.config.max_diff_lines;
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            let commit = match repo.find_commit(Oid::from_str(&oid_str)?) {
+                Ok(commit) => commit,
+                Err(e) if e.code() == git2::ErrorCode::NotFound => return Err(GitxError::CommitNotFound(oid_str)),
+                Err(e) => return Err(e.into()),
+            };
+            let tree = commit.tree()?;
+            let parent_tree = if commit.parent_count() > 0 {
+                Some(commit.parent(0)?.tree()?)
+            } else {
+                None
+            };
+
+            // 按字面路径过滤，不做 glob 匹配
+            let mut opts = Self::diff_options(context_lines);
+            opts.pathspec(&file_path).disable_pathspec_match(true);
+            let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))?;
+
+            // 过滤后看不到重命名的另一侧，新增/删除时在完整 diff 中重新检测
+            let added_or_deleted = diff
+                .deltas()
+                .any(|d| matches!(d.status(), git2::Delta::Added | git2::Delta::Deleted));
+            if rename_detection && added_or_deleted {
+                diff = repo.diff_tree_to_tree(
+                    parent_tree.as_ref(),
+                    Some(&tree),
+                    Some(&mut Self::diff_options(context_lines)),
+                )?;
+                Self::find_similar(&mut diff, true)?;
+            }
+
+            // pathspec 为目录时会匹配其下所有文件，只取路径完全相同的一项
+            let target = Path::new(&file_path);
+            let Some(index) = diff
+                .deltas()
+                .position(|d| d.new_file().path() == Some(target) || d.old_file().path() == Some(target))
+            else {
+                return Ok(None);
+            };
+            let Some(mut patch) = Patch::from_diff(&diff, index)? else {
+                return Ok(None);
+            };
+
+            let delta = patch.delta();
+            let (old_binary, _) = Self::blob_info(&repo, delta.old_file().id());
+            let (new_binary, _) = Self::blob_info(&repo, delta.new_file().id());
+            let binary = delta.flags().is_binary() || old_binary || new_binary;
+            let path_of = |file: git2::DiffFile<'_>| file.path().map(|p| p.to_string_lossy().into_owned());
+            let old_path = path_of(delta.old_file());
+            let new_path = path_of(delta.new_file());
+            let status = Self::delta_status(delta.status());
+
+            let (_, insertions, deletions) = patch.line_stats()?;
+            let too_large = max_diff_lines > 0 && insertions + deletions > max_diff_lines;
+            let mut hunks = Vec::new();
+            if !binary && !too_large {
+                patch.print(&mut |delta, hunk, line| {
+                    let content = String::from_utf8_lossy(line.content());
+                    Self::push_hunk_line(&mut hunks, &delta, hunk.as_ref(), &line, &content);
+                    true
+                })?;
+            }
+
+            Ok(Some(GitFileDiff {
+                old_path,
+                new_path,
+                status,
+                binary,
+                insertions,
+                deletions,
+                too_large,
+                hunks,
+            }))
+        })
+        .await
+    }
+
+    async fn format_patch(&self, path: &Path, oid: &str) -> Result<Vec<u8>> {
+        let path = path.to_path_buf();
+        let oid_str = oid.to_string();
+        let rename_detection = self.config.rename_detection;
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            let commit = repo.find_commit(Oid::from_str(&oid_str)?)?;
+            if commit.parent_count() > 1 {
+                return Err(GitxError::InvalidRequest(format!(
+                    "cannot format a patch for merge commit {}",
+                    oid_str
+                )));
+            }
+
+            // 默认选项与 git format-patch 一致：包含二进制补丁，检测重命名
+            let mut opts = git2::EmailCreateOptions::new();
+            opts.ignore_renames(!rename_detection);
+            let email = git2::Email::from_commit(&commit, &mut opts)?;
+            Ok(email.as_slice().to_vec())
+        })
+        .await
+    }
+
     async fn compare_commits(
         &self,
         path: &Path,
         from_oid: &str,
         to_oid: &str,
+        context_lines: Option<u32>,
     ) -> Result<GitDiff> {
         let path = path.to_path_buf();
         let from_oid_str = from_oid.to_string();
         let to_oid_str = to_oid.to_string();
+        let rename_detection = self.config.rename_detection;
+        let repos = self.repos.clone();
         
-        Self::run_blocking(move || {
-            let repo = Repository::open(&path)?;
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
             let from_oid = Oid::from_str(&from_oid_str)?;
             let to_oid = Oid::from_str(&to_oid_str)?;
             
@@ -363,112 +1216,787 @@ impl GitPort for Git2Client {
             let from_tree = from_commit.tree()?;
             let to_tree = to_commit.tree()?;
             
-            let diff = repo.diff_tree_to_tree(
+            let mut diff = repo.diff_tree_to_tree(
                 Some(&from_tree),
                 Some(&to_tree),
-                Some(&mut DiffOptions::new()),
+                Some(&mut Self::diff_options(context_lines)),
             )?;
+            Self::find_similar(&mut diff, rename_detection)?;
             
             let stats = diff.stats()?;
-            let stats_str = format!(
-                "{} files changed, {} insertions(+), {} deletions(-)",
-                stats.files_changed(),
-                stats.insertions(),
-                stats.deletions()
-            );
-            
-            let mut patches = Vec::new();
-            
-            diff.print(DiffFormat::Patch, |delta, _hunk, _line| {
-                let old_path = delta.old_file().path().map(|p| p.display().to_string());
-                let new_path = delta.new_file().path().map(|p| p.display().to_string());
-                let status = format!("{:?}", delta.status());
-                
-                patches.push(GitDiffPatch {
-                    old_path,
-                    new_path,
-                    status,
-                    hunks: vec![], // TODO: 收集 hunks
-                });
-                
-                true
-            })?;
+
+            // 每个文件一个 patch（重命名时 old_path/new_path 均有值）
+            let patches = diff
+                .deltas()
+                .map(|delta| {
+                    let (old_binary, old_size) = Self::blob_info(&repo, delta.old_file().id());
+                    let (new_binary, new_size) = Self::blob_info(&repo, delta.new_file().id());
+                    GitDiffPatch {
+                        old_path: delta.old_file().path().map(|p| p.display().to_string()),
+                        new_path: delta.new_file().path().map(|p| p.display().to_string()),
+                        status: Self::delta_status(delta.status()),
+                        binary: delta.flags().is_binary() || old_binary || new_binary,
+                        old_size,
+                        new_size,
+                    }
+                })
+                .collect();
             
             Ok(GitDiff {
-                stats: stats_str,
+                totals: CommitStats {
+                    files_changed: stats.files_changed(),
+                    insertions: stats.insertions(),
+                    deletions: stats.deletions(),
+                },
                 patches,
             })
         })
         .await
     }
     
-    async fn get_branch_diff_commits(
+    async fn file_history(
         &self,
         path: &Path,
-        old_branch: &str,
-        new_branch: &str,
+        rev: &str,
+        file_path: &str,
         limit: usize,
     ) -> Result<Vec<GitCommit>> {
         let path = path.to_path_buf();
-        let old_branch = old_branch.to_string();
-        let new_branch = new_branch.to_string();
-        
-        Self::run_blocking(move || {
-            use std::process::Command;
-            
-            // 直接使用git命令行，确保行为一致
-            // git log old_branch..new_branch --oneline --no-merges --format=%H
-            let output = Command::new("git")
-                .current_dir(&path)
-                .args(&[
-                    "log",
-                    &format!("{}..{}", old_branch, new_branch),
-                    "--no-merges",
-                    &format!("-{}", limit),
-                    "--format=%H",
-                ])
-                .output()
-                .map_err(|e| GitxError::Internal(format!("Failed to run git command: {}", e)))?;
-            
-            if !output.status.success() {
-                return Err(GitxError::Internal(format!(
-                    "Git command failed: {}",
-                    String::from_utf8_lossy(&output.stderr)
-                )));
-            }
-            
-            let oids_str = String::from_utf8_lossy(&output.stdout);
-            let repo = Repository::open(&path)?;
+        let rev = rev.to_string();
+        let file_path = file_path.to_string();
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            let start = repo.revparse_single(&rev)?.peel_to_commit()?;
+
+            let mut revwalk = repo.revwalk()?;
+            revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+            revwalk.push(start.id())?;
+
             let mut commits = Vec::new();
-            
-            for line in oids_str.lines() {
-                let oid_str = line.trim();
-                if oid_str.is_empty() {
-                    continue;
+
+            for oid in revwalk {
+                // limit 针对匹配的提交数，而不是遍历的提交数
+                if commits.len() >= limit {
+                    break;
+                }
+
+                let commit = repo.find_commit(oid?)?;
+                let tree = commit.tree()?;
+                // 根提交与空树比较
+                let parent_tree = if commit.parent_count() > 0 {
+                    Some(commit.parent(0)?.tree()?)
+                } else {
+                    None
+                };
+
+                // 按字面路径过滤，不做 glob 匹配
+                let mut opts = DiffOptions::new();
+                opts.pathspec(&file_path).disable_pathspec_match(true);
+                let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))?;
+
+                if diff.deltas().len() > 0 {
+                    commits.push(Self::to_git_commit(&commit));
                 }
-                
-                let oid = Oid::from_str(oid_str)?;
-                let commit = repo.find_commit(oid)?;
-                
-                let author = commit.author();
-                let committer = commit.committer();
-                
-                commits.push(GitCommit {
-                    oid: commit.id().to_string(),
-                    author_name: String::from_utf8_lossy(author.name_bytes()).to_string(),
-                    author_email: String::from_utf8_lossy(author.email_bytes()).to_string(),
-                    author_time: author.when().seconds(),
-                    committer_name: String::from_utf8_lossy(committer.name_bytes()).to_string(),
-                    committer_email: String::from_utf8_lossy(committer.email_bytes()).to_string(),
-                    committer_time: committer.when().seconds(),
-                    summary: commit.summary().unwrap_or("").to_string(),
-                    message: commit.body().map(String::from),
-                    parent_oids: commit.parent_ids().map(|id| id.to_string()).collect(),
-                });
             }
-            
+
             Ok(commits)
         })
         .await
     }
+
+    async fn blame(&self, path: &Path, rev: &str, file_path: &str) -> Result<Vec<BlameLine>> {
+        let path = path.to_path_buf();
+        let rev = rev.to_string();
+        let file_path = file_path.to_string();
+        let max_bytes = self.config.max_blame_bytes;
+        let repos = self.repos.clone();
+
+        self.run_blocking(move || {
+            let repo = repos.open(&path)?;
+            let commit = repo.revparse_single(&rev)?.peel_to_commit()?;
+
+            let entry = commit.tree()?.get_path(Path::new(&file_path)).map_err(|e| {
+                if e.code() == git2::ErrorCode::NotFound {
+                    GitxError::FileNotFound(format!("{}:{}", rev, file_path))
+                } else {
+                    GitxError::Git(e)
+                }
+            })?;
+            if entry.kind() != Some(git2::ObjectType::Blob) {
+                return Err(GitxError::InvalidPath(format!("{} is not a file", file_path)));
+            }
+
+            // 先读对象头判断大小，超大文件不载入内存也不做 blame
+            let (size, _) = repo.odb()?.read_header(entry.id())?;
+            if size > max_bytes {
+                return Err(GitxError::InvalidRequest(format!(
+                    "{} is too large to blame ({} bytes, limit {})",
+                    file_path, size, max_bytes
+                )));
+            }
+
+            let blob = repo.find_blob(entry.id())?;
+            if blob.is_binary() {
+                return Err(GitxError::InvalidRequest(format!("{} is a binary file", file_path)));
+            }
+
+            let mut opts = git2::BlameOptions::new();
+            opts.newest_commit(commit.id());
+            let blame = repo.blame_file(Path::new(&file_path), Some(&mut opts))?;
+
+            let content = String::from_utf8_lossy(blob.content());
+            let lines: Vec<&str> = content.lines().collect();
+            let mut result = Vec::with_capacity(lines.len());
+
+            for hunk in blame.iter() {
+                let oid = hunk.final_commit_id().to_string();
+                let author_name = String::from_utf8_lossy(hunk.final_signature().name_bytes()).to_string();
+                let start = hunk.final_start_line();
+
+                for line_no in start..start + hunk.lines_in_hunk() {
+                    result.push(BlameLine {
+                        line_no,
+                        oid: oid.clone(),
+                        author_name: author_name.clone(),
+                        content: lines.get(line_no - 1).map(|l| l.to_string()).unwrap_or_default(),
+                    });
+                }
+            }
+
+            Ok(result)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::git::test_repo::{commit_changes, TestRepo};
+    use git2::Signature;
+
+    #[tokio::test]
+    async fn test_compare_commits_detects_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let content = "fn main() {\n    println!(\"hello\");\n}\n".repeat(10);
+        let first = commit_changes(&repo, &[("old.rs", &content)], &[], "add file");
+        let second = commit_changes(&repo, &[("new.rs", &content)], &["old.rs"], "rename file");
+
+        let client = Git2Client::default();
+        let diff = client
+            .compare_commits(dir.path(), &first.to_string(), &second.to_string(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(diff.patches.len(), 1);
+        assert_eq!(diff.totals, CommitStats { files_changed: 1, insertions: 0, deletions: 0 });
+        assert_eq!(diff.patches[0].status, DeltaStatus::Renamed);
+        assert_eq!(serde_json::to_value(diff.patches[0].status).unwrap(), "renamed");
+        assert_eq!(diff.patches[0].status.as_str(), "renamed");
+        assert_eq!(diff.patches[0].old_path.as_deref(), Some("old.rs"));
+        assert_eq!(diff.patches[0].new_path.as_deref(), Some("new.rs"));
+
+        // 关闭重命名检测后退化为删除 + 新增
+        let client = Git2Client::new(GitConfig {
+            rename_detection: false,
+            ..GitConfig::default()
+        });
+        let diff = client
+            .compare_commits(dir.path(), &first.to_string(), &second.to_string(), None)
+            .await
+            .unwrap();
+        let statuses: Vec<DeltaStatus> = diff.patches.iter().map(|p| p.status).collect();
+        assert_eq!(statuses.len(), 2);
+        assert!(statuses.contains(&DeltaStatus::Added) && statuses.contains(&DeltaStatus::Deleted));
+    }
+
+    #[tokio::test]
+    async fn test_binary_file_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let write_png = |len: usize| {
+            let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+            png.resize(len, 0);
+            std::fs::write(dir.path().join("logo.png"), &png).unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("logo.png")).unwrap();
+            index.write().unwrap();
+        };
+        write_png(64);
+        let first = commit_changes(&repo, &[("README.md", "logo\n")], &[], "add logo");
+        write_png(128);
+        let second = commit_changes(&repo, &[("README.md", "new logo\n")], &[], "update logo");
+
+        let client = Git2Client::default();
+        let detail = client.get_commit_detail(dir.path(), &second.to_string(), None).await.unwrap();
+        assert!(detail.diff_html.contains("<span class=\"diff-binary\">Binary file changed (64 → 128 bytes)</span>"));
+        assert!(!detail.diff_html.contains("Binary files"));
+        // 文本文件照常渲染
+        assert!(detail.diff_html.contains("diff-add-line"));
+
+        let diff = client
+            .compare_commits(dir.path(), &first.to_string(), &second.to_string(), None)
+            .await
+            .unwrap();
+        let png = diff.patches.iter().find(|p| p.new_path.as_deref() == Some("logo.png")).unwrap();
+        assert!(png.binary);
+        assert_eq!((png.old_size, png.new_size), (64, 128));
+        let readme = diff.patches.iter().find(|p| p.new_path.as_deref() == Some("README.md")).unwrap();
+        assert!(!readme.binary);
+
+        // 纯文本 diff 与 git diff 一致，补丁包含可被 git apply 使用的二进制补丁
+        let plain = String::from_utf8_lossy(&detail.diff_plain);
+        assert!(plain.contains("Binary files a/logo.png and b/logo.png differ\n"));
+        assert!(plain.contains("\n-logo\n+new logo\n"));
+        let patch = String::from_utf8(client.format_patch(dir.path(), &second.to_string()).await.unwrap()).unwrap();
+        assert!(patch.starts_with(&format!("From {} Mon Sep 17 00:00:00 2001\n", second)));
+        assert!(patch.contains("Subject: [PATCH] update logo\n"));
+        assert!(patch.contains("GIT binary patch"));
+        assert!(patch.contains("\n-logo\n+new logo\n"));
+    }
+
+    #[tokio::test]
+    async fn test_diff_context_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let lines: Vec<String> = (1..=9).map(|i| format!("line{}\n", i)).collect();
+        commit_changes(&repo, &[("f.txt", &lines.concat())], &[], "add");
+        let mut changed = lines.clone();
+        changed[4] = "changed\n".to_string();
+        let oid = commit_changes(&repo, &[("f.txt", &changed.concat())], &[], "change line5");
+
+        let client = Git2Client::default();
+        let path = dir.path();
+        let plain = |context| {
+            let client = &client;
+            let oid = oid.to_string();
+            async move {
+                let detail = client.get_commit_detail(path, &oid, context).await.unwrap();
+                String::from_utf8_lossy(&detail.diff_plain).into_owned()
+            }
+        };
+
+        // 默认 3 行上下文
+        let diff = plain(None).await;
+        assert!(diff.contains("\n line2\n") && !diff.contains("\n line1\n"));
+        // 0 行上下文只剩变更行
+        let diff = plain(Some(0)).await;
+        assert!(diff.contains("\n-line5\n+changed\n") && !diff.contains("\n line4\n"));
+        // 超出上限时截断，仍输出完整文件
+        let diff = plain(Some(10_000)).await;
+        assert!(diff.contains("\n line1\n") && diff.contains("\n line9\n"));
+    }
+
+    #[tokio::test]
+    async fn test_file_diff() {
+        let repo = TestRepo::new();
+        let content = "fn main() {\n    println!(\"hello\");\n}\n".repeat(10);
+        repo.commit(&[("src/old.rs", &content), ("README.md", "a\n")], &[], "add");
+        let renamed = content.replacen("hello", "world", 1);
+        let oid = repo.commit(&[("src/new.rs", &renamed), ("README.md", "b\n")], &["src/old.rs"], "rename").to_string();
+
+        let client = Git2Client::default();
+        let readme = client.file_diff(repo.path(), &oid, "README.md", None).await.unwrap().unwrap();
+        assert_eq!(readme.status, DeltaStatus::Modified);
+        assert_eq!((readme.insertions, readme.deletions), (1, 1));
+        assert_eq!(readme.hunks.len(), 1);
+        let lines: Vec<(char, &str)> = readme.hunks[0].lines.iter().map(|l| (l.origin, l.content.as_str())).collect();
+        assert_eq!(lines, vec![('-', "a"), ('+', "b")]);
+
+        // 重命名时新旧路径都能查到，且只包含该文件的 hunk
+        for path in ["src/new.rs", "src/old.rs"] {
+            let diff = client.file_diff(repo.path(), &oid, path, None).await.unwrap().unwrap();
+            assert_eq!(diff.status, DeltaStatus::Renamed);
+            assert_eq!(diff.old_path.as_deref(), Some("src/old.rs"));
+            assert_eq!(diff.new_path.as_deref(), Some("src/new.rs"));
+            assert!(diff.hunks.iter().all(|h| h.new_path.as_deref() == Some("src/new.rs")));
+            assert_eq!((diff.insertions, diff.deletions), (1, 1));
+        }
+
+        // 目录与未修改的文件不匹配
+        assert!(client.file_diff(repo.path(), &oid, "src", None).await.unwrap().is_none());
+        assert!(client.file_diff(repo.path(), &oid, "missing.rs", None).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_file_history_literal_path() {
+        let repo = TestRepo::new();
+        let bracketed = repo.commit(&[("a[1].txt", "x\n")], &[], "bracketed");
+        let plain = repo.commit(&[("a1.txt", "y\n")], &[], "plain");
+
+        // `[1]` 按字面匹配，不会当作字符类匹配到 a1.txt
+        let client = Git2Client::default();
+        let history = client.file_history(repo.path(), "HEAD", "a[1].txt", 10).await.unwrap();
+        let oids: Vec<String> = history.iter().map(|c| c.oid.clone()).collect();
+        assert_eq!(oids, vec![bracketed.to_string()]);
+        let history = client.file_history(repo.path(), "HEAD", "a1.txt", 10).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].oid, plain.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_blame_limits() {
+        let repo = TestRepo::new();
+        let first = repo.commit(&[("a.txt", "one\n"), ("dir/b.txt", "b\n")], &[], "first");
+        let second = repo.commit(&[("a.txt", "one\ntwo\n")], &[], "second");
+
+        let client = Git2Client::new(GitConfig { max_blame_bytes: 8, ..GitConfig::default() });
+        let lines = client.blame(repo.path(), "HEAD", "a.txt").await.unwrap();
+        let blamed: Vec<(usize, String, &str)> = lines.iter().map(|l| (l.line_no, l.oid.clone(), l.content.as_str())).collect();
+        assert_eq!(blamed, vec![(1, first.to_string(), "one"), (2, second.to_string(), "two")]);
+
+        // 超过 max_blame_bytes 与目录在读取内容前即被拒绝
+        let client = Git2Client::new(GitConfig { max_blame_bytes: 4, ..GitConfig::default() });
+        let too_large = client.blame(repo.path(), "HEAD", "a.txt").await;
+        assert!(matches!(too_large, Err(GitxError::InvalidRequest(_))));
+        let dir = client.blame(repo.path(), "HEAD", "dir").await;
+        assert!(matches!(dir, Err(GitxError::InvalidPath(_))));
+    }
+
+    #[tokio::test]
+    async fn test_list_tree_submodule() {
+        let repo = TestRepo::new();
+        let gitmodules = "[submodule \"lib\"]\n\tpath = vendor/lib\n\turl = https://example.com/lib.git\n";
+        let base = repo.commit(&[(".gitmodules", gitmodules), ("vendor/README", "x\n"), ("a.txt", "a\n")], &[], "base");
+
+        // 直接写入 gitlink 条目（子模块提交不在本仓库中）
+        let pinned = Oid::from_str("0123456789abcdef0123456789abcdef01234567").unwrap();
+        let base_commit = repo.repo.find_commit(base).unwrap();
+        let root = base_commit.tree().unwrap();
+        let vendor = root.get_path(Path::new("vendor")).unwrap();
+        let mut vendor_tree = repo.repo.treebuilder(Some(&repo.repo.find_tree(vendor.id()).unwrap())).unwrap();
+        vendor_tree.insert("lib", pinned, i32::from(git2::FileMode::Commit)).unwrap();
+        let mut root_tree = repo.repo.treebuilder(Some(&root)).unwrap();
+        root_tree.insert("vendor", vendor_tree.write().unwrap(), i32::from(git2::FileMode::Tree)).unwrap();
+        let tree = repo.repo.find_tree(root_tree.write().unwrap()).unwrap();
+        let sig = Signature::now("Tester", "tester@example.com").unwrap();
+        repo.repo.commit(Some("HEAD"), &sig, &sig, "add submodule", &tree, &[&base_commit]).unwrap();
+
+        let client = Git2Client::default();
+        let root = client.list_tree(repo.path(), "HEAD", "").await.unwrap();
+        let names: Vec<&str> = root.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["vendor", ".gitmodules", "a.txt"]);
+        assert_eq!(root[0].kind, TreeEntryKind::Directory);
+        assert_eq!(root[2].size, Some(2));
+
+        let vendor = client.list_tree(repo.path(), "HEAD", "vendor/").await.unwrap();
+        let lib = vendor.iter().find(|e| e.name == "lib").unwrap();
+        assert_eq!(lib.kind, TreeEntryKind::Submodule);
+        assert_eq!(lib.path, "vendor/lib");
+        assert_eq!(lib.oid, pinned.to_string());
+        assert_eq!(lib.submodule_url.as_deref(), Some("https://example.com/lib.git"));
+        assert_eq!(lib.size, None);
+
+        // 不递归进入子模块，文件路径也不是目录
+        assert!(matches!(client.list_tree(repo.path(), "HEAD", "vendor/lib").await, Err(GitxError::FileNotFound(_))));
+        assert!(matches!(client.list_tree(repo.path(), "HEAD", "a.txt").await, Err(GitxError::FileNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_intraline_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        commit_changes(&repo, &[("notes.txt", "hello wrold\nunchanged\n")], &[], "add notes");
+        let oid = commit_changes(&repo, &[("notes.txt", "hello world\nunchanged\n")], &[], "fix typo");
+
+        let detail = Git2Client::default().get_commit_detail(dir.path(), &oid.to_string(), None).await.unwrap();
+        assert!(!detail.diff_html.contains("diff-word-change"));
+
+        let client = Git2Client::new(GitConfig {
+            intraline_diff: true,
+            ..GitConfig::default()
+        });
+        let detail = client.get_commit_detail(dir.path(), &oid.to_string(), None).await.unwrap();
+        assert!(detail.diff_html.contains("<span class=\"diff-remove-line\">hello <span class=\"diff-word-change\">wrold</span>\n</span>"));
+        assert!(detail.diff_html.contains("<span class=\"diff-add-line\">hello <span class=\"diff-word-change\">world</span>\n</span>"));
+        // 纯文本输出不受影响
+        assert!(String::from_utf8_lossy(&detail.diff_plain).contains("\n-hello wrold\n+hello world\n"));
+        assert!(!String::from_utf8_lossy(&detail.diff_plain).contains("diff-word-change"));
+    }
+
+    #[tokio::test]
+    async fn test_multibyte_diff_rendering() {
+        let t = TestRepo::new();
+        t.commit(&[("i18n.xyz", "héllo wörld\n你好，世界\n")], &[], "add");
+        let oid = t.commit(&[("i18n.xyz", "héllo wörld\n你好，世界 🎉\n")], &[], "celebrate");
+
+        // 未知扩展名不做语法高亮，开启 highlight feature 时输出也相同
+        let detail = Git2Client::default().get_commit_detail(t.path(), &oid.to_string(), None).await.unwrap();
+        // 上下文行与增删行对齐，不额外带前导空格
+        assert!(detail.diff_html.contains("<span class=\"diff-context\">héllo wörld\n</span>"));
+        assert!(detail.diff_html.contains("<span class=\"diff-remove-line\">你好，世界\n</span>"));
+        assert!(detail.diff_html.contains("<span class=\"diff-add-line\">你好，世界 🎉\n</span>"));
+        assert!(!detail.diff_html.contains('\u{FFFD}'));
+        let added = detail.hunks[0].lines.iter().find(|l| l.origin == '+').unwrap();
+        assert_eq!(added.content, "你好，世界 🎉");
+
+        // 非 UTF-8 内容（Latin-1）在没有结尾换行的行上按替换字符输出
+        let repo = Repository::open(t.path()).unwrap();
+        std::fs::write(t.path().join("latin1.xyz"), b"caf\xe9").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("latin1.xyz")).unwrap();
+        index.write().unwrap();
+        let oid = commit_changes(&repo, &[], &[], "latin-1");
+        let detail = Git2Client::default().get_commit_detail(t.path(), &oid.to_string(), None).await.unwrap();
+        assert!(detail.diff_html.contains("<span class=\"diff-add-line\">caf\u{FFFD}</span>"));
+        let added = detail.hunks[0].lines.iter().find(|l| l.origin == '+').unwrap();
+        assert_eq!(added.content, "caf\u{FFFD}");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_oid() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let oid = commit_changes(&repo, &[("a.txt", "a")], &[], "first").to_string();
+
+        let client = Git2Client::default();
+        assert_eq!(client.resolve_oid(dir.path(), &oid[..7]).await.unwrap(), oid);
+        assert_eq!(client.resolve_oid(dir.path(), &oid).await.unwrap(), oid);
+
+        let missing = if oid.starts_with("0000") { "ffff" } else { "0000" };
+        assert!(matches!(
+            client.resolve_oid(dir.path(), missing).await,
+            Err(GitxError::CommitNotFound(_))
+        ));
+
+        // 写入对象直到两个对象的前 4 位相同，该前缀有歧义
+        let mut seen = std::collections::HashSet::new();
+        let prefix: String = (0..)
+            .map(|i| repo.blob(format!("blob {}", i).as_bytes()).unwrap().to_string()[..4].to_string())
+            .find(|prefix| !seen.insert(prefix.clone()))
+            .unwrap();
+        assert!(matches!(
+            client.resolve_oid(dir.path(), &prefix).await,
+            Err(GitxError::AmbiguousOid(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_merge_base_and_ahead_behind() {
+        let t = TestRepo::new();
+        let base = t.commit(&[("a.txt", "a\n")], &[], "base");
+        let main = t.commit(&[("a.txt", "b\n")], &[], "main");
+        t.branch("feature", base);
+        t.repo.set_head("refs/heads/feature").unwrap();
+        t.commit(&[("f.txt", "1\n")], &[], "feature 1");
+        let feature = t.commit(&[("f.txt", "2\n")], &[], "feature 2");
+
+        let client = Git2Client::default();
+        let (main, feature) = (main.to_string(), feature.to_string());
+        assert_eq!(client.merge_base(t.path(), &feature, &main).await.unwrap(), Some(base.to_string()));
+        assert_eq!(client.ahead_behind(t.path(), &feature, &main).await.unwrap(), (2, 1));
+
+        // 没有共同历史
+        let sig = Signature::now("Tester", "tester@example.com").unwrap();
+        let tree = t.repo.find_commit(base).unwrap().tree().unwrap();
+        let orphan = t.repo.commit(None, &sig, &sig, "orphan", &tree, &[]).unwrap().to_string();
+        assert_eq!(client.merge_base(t.path(), &orphan, &main).await.unwrap(), None);
+        assert_eq!(client.ahead_behind(t.path(), &orphan, &main).await.unwrap(), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_root_commit_oid() {
+        let repo = TestRepo::new();
+        let client = Git2Client::default();
+        assert_eq!(client.root_commit_oid(repo.path()).await.unwrap(), None);
+
+        let root = repo.commit(&[("a.txt", "a")], &[], "first");
+        repo.commit(&[("a.txt", "b")], &[], "second");
+        assert_eq!(client.root_commit_oid(repo.path()).await.unwrap(), Some(root.to_string()));
+        // 克隆到别处的仓库标识不变
+        let moved = repo.clone_to("moved.git", true);
+        assert_eq!(client.root_commit_oid(&moved).await.unwrap(), Some(root.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_list_tags_peels_and_sorts() {
+        let t = TestRepo::new();
+        let first = t.commit(&[("a.txt", "a")], &[], "first");
+        let second = t.commit(&[("a.txt", "b")], &[], "second");
+
+        t.tag("v1.0", first, "release", 1_600_000_000);
+        t.tag("v2.0", second, "release", 1_700_000_000);
+        t.repo.tag_lightweight("a-light", &t.repo.find_object(first, None).unwrap(), false).unwrap();
+        t.repo.tag_lightweight("b-light", &t.repo.find_object(second, None).unwrap(), false).unwrap();
+
+        let tags = Git2Client::default().list_tags(t.path()).await.unwrap();
+        let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["v2.0", "v1.0", "b-light", "a-light"]);
+        // 标注标签剥离到提交
+        assert_eq!(tags[0].target_oid, second.to_string());
+        assert_eq!(tags[1].target_oid, first.to_string());
+        assert_eq!(tags[1].message.as_deref(), Some("release"));
+        assert_eq!(tags[1].tagger_name.as_deref(), Some("Tester"));
+        // 轻量标签没有 tagger 信息
+        assert!(tags[2].tagger_name.is_none() && tags[2].message.is_none());
+
+        // 按名称查找只匹配 refs/tags/，同名分支不影响
+        t.branch("v1.0", second);
+        let client = Git2Client::default();
+        let tag = client.find_tag(t.path(), "v1.0").await.unwrap().unwrap();
+        assert_eq!((tag.target_oid, tag.message.as_deref()), (first.to_string(), Some("release")));
+        assert!(client.find_tag(t.path(), "missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_commits_parses_metadata() {
+        let t = TestRepo::new();
+        let first = t.commit(&[("a.txt", "a")], &[], "first");
+
+        // 非 UTF-8 作者名（Latin-1 编码的 "José"）只能直接写入原始提交对象；
+        // 提交时间晚于 first，按时间排序时位于最前
+        let tree = t.repo.find_commit(first).unwrap().tree_id();
+        let mut raw = format!("tree {}\nparent {}\nauthor Jos", tree, first).into_bytes();
+        raw.push(0xE9);
+        raw.extend_from_slice(
+            b" <jose@example.com> 1700000000 +0800\n\
+              committer Maintainer <maint@example.com> 4102444800 +0000\n\
+              \n\
+              Subject line\n\
+              \n\
+              Body text\n",
+        );
+        let second = t.repo.odb().unwrap().write(git2::ObjectType::Commit, &raw).unwrap();
+        t.repo.reference(&t.head_ref(), second, true, "raw commit").unwrap();
+
+        let client = Git2Client::default();
+        let commits = client.get_commits(t.path(), &t.head_ref(), 10, None, false, WalkMode::Linear).await.unwrap();
+        assert_eq!(commits.len(), 2);
+        let c = &commits[0];
+        assert_eq!(c.oid, second.to_string());
+        assert_eq!(c.author_name, "Jos\u{FFFD}");
+        assert_eq!(c.author_email, "jose@example.com");
+        assert_eq!(c.author_time, 1_700_000_000);
+        assert_eq!((c.committer_name.as_str(), c.committer_time), ("Maintainer", 4_102_444_800));
+        assert_eq!(c.summary, "Subject line");
+        assert_eq!(c.message.as_deref().map(str::trim_end), Some("Body text"));
+        assert_eq!(c.parent_oids, vec![first.to_string()]);
+        assert_eq!(commits[1].message, None);
+
+        // limit 与增量起点
+        assert_eq!(client.get_commits(t.path(), &t.head_ref(), 1, None, false, WalkMode::Linear).await.unwrap().len(), 1);
+        let newer = client
+            .get_commits(t.path(), &t.head_ref(), 10, Some(&first.to_string()), false, WalkMode::Linear)
+            .await
+            .unwrap();
+        assert_eq!(newer.iter().map(|c| c.oid.clone()).collect::<Vec<_>>(), vec![second.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_list_branches_remote() {
+        let t = TestRepo::new();
+        let first = t.commit(&[("a.txt", "a")], &[], "first");
+        let second = t.commit(&[("a.txt", "b")], &[], "second");
+        t.branch("feature", first);
+        let default = t.head_ref().trim_start_matches("refs/heads/").to_string();
+        let clone = t.clone_to("clone", false);
+
+        // 工作区返回远程跟踪分支，origin/HEAD 符号引用不计入
+        let mut branches = Git2Client::default().list_branches(&clone).await.unwrap();
+        branches.sort_by(|a, b| a.name.cmp(&b.name));
+        let summary: Vec<(String, String, String)> = branches
+            .iter()
+            .map(|b| (b.name.clone(), b.ref_name.clone(), b.target_oid.clone()))
+            .collect();
+        let mut expected = vec![
+            ("origin/feature".to_string(), "refs/remotes/origin/feature".to_string(), first.to_string()),
+            (format!("origin/{}", default), format!("refs/remotes/origin/{}", default), second.to_string()),
+        ];
+        expected.sort();
+        assert_eq!(summary, expected);
+        assert!(branches.iter().all(|b| !b.is_head));
+    }
+
+    #[tokio::test]
+    async fn test_get_commit_detail_stats() {
+        let t = TestRepo::new();
+        let root = t.commit(&[("a.txt", "1\n2\n"), ("dir/b.txt", "b\n")], &[], "root commit\n\nwith body");
+        let second = t.commit(&[("a.txt", "1\nx\n")], &[], "change a");
+
+        let client = Git2Client::default();
+        // 根提交与空树比较
+        let detail = client.get_commit_detail(t.path(), &root.to_string(), None).await.unwrap();
+        assert_eq!(detail.diff_stats, "2 files changed, 3 insertions(+), 0 deletions(-)");
+        assert_eq!(detail.commit.summary, "root commit");
+        assert!(detail.commit.parent_oids.is_empty());
+        assert!(detail.signature.is_none());
+        assert!(String::from_utf8_lossy(&detail.diff_plain).contains("+++ b/dir/b.txt\n"));
+        let stat = |path: &str, insertions, deletions| FileStat { path: path.to_string(), insertions, deletions };
+        assert_eq!(detail.file_stats, vec![stat("a.txt", 2, 0), stat("dir/b.txt", 1, 0)]);
+
+        let detail = client.get_commit_detail(t.path(), &second.to_string(), None).await.unwrap();
+        assert_eq!(detail.diff_stats, "1 files changed, 1 insertions(+), 1 deletions(-)");
+        assert_eq!(detail.file_stats, vec![stat("a.txt", 1, 1)]);
+        assert_eq!(detail.commit.parent_oids, vec![root.to_string()]);
+        assert!(detail.diff_html.contains("diff-remove-line"));
+        assert!(detail.diff_html.contains("diff-add-line"));
+        assert!(!detail.diff_too_large);
+
+        // 单文件的结构化 hunk 带新旧行号
+        let diff = client.file_diff(t.path(), &second.to_string(), "a.txt", None).await.unwrap().unwrap();
+        assert_eq!(diff.hunks.len(), 1);
+        let hunk = &diff.hunks[0];
+        assert_eq!(hunk.header, "@@ -1,2 +1,2 @@");
+        assert_eq!(hunk.new_path.as_deref(), Some("a.txt"));
+        let lines: Vec<(char, Option<u32>, Option<u32>, &str)> = hunk
+            .lines
+            .iter()
+            .map(|l| (l.origin, l.old_lineno, l.new_lineno, l.content.as_str()))
+            .collect();
+        assert_eq!(lines, vec![(' ', Some(1), Some(1), "1"), ('-', Some(2), None, "2"), ('+', None, Some(2), "x")]);
+
+        // 提交详情中的 hunk 与单文件 diff 一致，每行带有渲染后的 HTML
+        assert_eq!(detail.hunks.len(), 1);
+        let detail_lines: Vec<(char, Option<u32>, Option<u32>, &str)> = detail.hunks[0]
+            .lines
+            .iter()
+            .map(|l| (l.origin, l.old_lineno, l.new_lineno, l.content.as_str()))
+            .collect();
+        assert_eq!(detail_lines, lines);
+        for line in &detail.hunks[0].lines {
+            assert!(line.html.contains(&line.content) && !line.html.contains('\n'));
+        }
+
+        // 只取元数据
+        let commit = client.get_commit(t.path(), &second.to_string()).await.unwrap();
+        assert_eq!((commit.summary.as_str(), commit.parent_oids.clone()), ("change a", vec![root.to_string()]));
+        let missing = client.get_commit(t.path(), &"0".repeat(40)).await;
+        assert!(matches!(missing, Err(GitxError::CommitNotFound(_))));
+
+        // 超过 max_diff_lines 时不渲染 HTML，原文仍可下载
+        let client = Git2Client::new(GitConfig { max_diff_lines: 2, ..GitConfig::default() });
+        let detail = client.get_commit_detail(t.path(), &root.to_string(), None).await.unwrap();
+        assert!(detail.diff_too_large && detail.diff_html.is_empty() && detail.hunks.is_empty());
+        assert_eq!(detail.file_stats.len(), 2);
+        assert!(String::from_utf8_lossy(&detail.diff_plain).contains("+++ b/dir/b.txt\n"));
+        let detail = client.get_commit_detail(t.path(), &second.to_string(), None).await.unwrap();
+        assert!(!detail.diff_too_large);
+    }
+
+    #[tokio::test]
+    async fn test_commit_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let first = commit_changes(&repo, &[("a.txt", "1\n2\n3\n"), ("b.txt", "b\n")], &[], "first");
+        let second = commit_changes(&repo, &[("a.txt", "1\nx\n3\n4\n")], &["b.txt"], "second");
+
+        let stats = Git2Client::default()
+            .commit_stats(dir.path(), &[second.to_string(), first.to_string()])
+            .await
+            .unwrap();
+        assert_eq!(stats[0], CommitStats { files_changed: 2, insertions: 2, deletions: 2 });
+        assert_eq!(stats[1], CommitStats { files_changed: 2, insertions: 4, deletions: 0 });
+    }
+
+    #[tokio::test]
+    async fn test_bare_repository_reads() {
+        let dir = tempfile::tempdir().unwrap();
+        let work = dir.path().join("work");
+        let repo = Repository::init(&work).unwrap();
+        commit_changes(&repo, &[("a.txt", "a")], &[], "first");
+        commit_changes(&repo, &[("a.txt", "b")], &[], "second");
+
+        let bare = dir.path().join("server.git");
+        git2::build::RepoBuilder::new()
+            .bare(true)
+            .clone(work.to_str().unwrap(), &bare)
+            .unwrap();
+
+        let client = Git2Client::default();
+        assert!(client.is_bare(&bare).await.unwrap());
+        assert!(!client.is_bare(&work).await.unwrap());
+
+        // 裸仓库返回本地分支
+        let branches = client.list_branches(&bare).await.unwrap();
+        assert_eq!(branches.len(), 1);
+        assert!(branches[0].ref_name.starts_with("refs/heads/"));
+        assert!(branches[0].is_head);
+
+        assert_eq!(client.default_branch(&bare, "origin").await.unwrap().as_deref(), Some(branches[0].name.as_str()));
+
+        // 克隆的工作区通过 origin/HEAD 得到默认分支
+        let clone = dir.path().join("clone");
+        git2::build::RepoBuilder::new()
+            .clone(bare.to_str().unwrap(), &clone)
+            .unwrap();
+        assert_eq!(
+            client.default_branch(&clone, "origin").await.unwrap(),
+            Some(format!("origin/{}", branches[0].name))
+        );
+        assert_eq!(client.default_branch(&work, "origin").await.unwrap(), None);
+
+        let commits = client.get_commits(&bare, &branches[0].ref_name, 10, None, false, WalkMode::Linear).await.unwrap();
+        assert_eq!(commits.len(), 2);
+        let detail = client.get_commit_detail(&bare, &commits[0].oid, None).await.unwrap();
+        assert!(detail.diff_stats.contains("1 files changed"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_configured_remote() {
+        let dir = tempfile::tempdir().unwrap();
+        let upstream = dir.path().join("upstream");
+        let repo = Repository::init(&upstream).unwrap();
+        commit_changes(&repo, &[("a.txt", "a")], &[], "first");
+
+        let work = dir.path().join("work");
+        let work_repo = Repository::init(&work).unwrap();
+        work_repo.remote("upstream", upstream.to_str().unwrap()).unwrap();
+
+        let client = Git2Client::default();
+        let result = client.fetch_repository(&work, "upstream").await.unwrap();
+        assert!(!result.branches_updated.is_empty());
+        assert!(result.branches_updated.iter().all(|b| b.starts_with("upstream/")));
+
+        // 配置的远程不存在时明确报错
+        let err = client.fetch_repository(&work, "origin").await.unwrap_err();
+        assert!(matches!(err, GitxError::Config(ref msg) if msg.contains("'origin'") && msg.contains("upstream")));
+    }
+
+    #[tokio::test]
+    async fn test_get_commits_merges() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let base = commit_changes(&repo, &[("a.txt", "a")], &[], "base");
+        let main = commit_changes(&repo, &[("b.txt", "b")], &[], "main work");
+
+        // 从 base 分出的旁支提交，再合并回 HEAD
+        let sig = Signature::now("Tester", "tester@example.com").unwrap();
+        let base_commit = repo.find_commit(base).unwrap();
+        let side = repo
+            .commit(None, &sig, &sig, "side work", &base_commit.tree().unwrap(), &[&base_commit])
+            .unwrap();
+        let main_commit = repo.find_commit(main).unwrap();
+        let side_commit = repo.find_commit(side).unwrap();
+        let merge = repo
+            .commit(Some("HEAD"), &sig, &sig, "merge side", &main_commit.tree().unwrap(), &[&main_commit, &side_commit])
+            .unwrap();
+        let head = repo.head().unwrap().name().unwrap().to_string();
+
+        let client = Git2Client::default();
+        let commits = client.get_commits(dir.path(), &head, 100, None, false, WalkMode::Linear).await.unwrap();
+        assert_eq!(commits.len(), 3);
+        assert!(commits.iter().all(|c| c.oid != merge.to_string()));
+
+        let commits = client.get_commits(dir.path(), &head, 100, None, true, WalkMode::Linear).await.unwrap();
+        assert_eq!(commits.len(), 4);
+        let merge_commit = commits.iter().find(|c| c.oid == merge.to_string()).unwrap();
+        assert_eq!(merge_commit.parent_oids, vec![main.to_string(), side.to_string()]);
+
+        // 拓扑遍历：子提交先于父提交，始终包含合并提交
+        let oids = |commits: Vec<GitCommit>| commits.into_iter().map(|c| c.oid).collect::<Vec<_>>();
+        let commits = client.get_commits(dir.path(), &head, 100, None, false, WalkMode::Topological).await.unwrap();
+        assert_eq!(commits.len(), 4);
+        assert_eq!(commits[0].oid, merge.to_string());
+        assert_eq!(commits[3].oid, base.to_string());
+
+        // 增量：上次索引到 main 时，经合并引入的旁支提交也会被索引
+        let commits = client
+            .get_commits(dir.path(), &head, 100, Some(&main.to_string()), false, WalkMode::Topological)
+            .await
+            .unwrap();
+        assert_eq!(oids(commits), vec![merge.to_string(), side.to_string()]);
+    }
 }
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+/// 解析 `.gitmodules`，返回子模块路径到 URL 的映射
+///
+/// 只识别 `[submodule "name"]` 小节中的 `path` 与 `url`，缺少任一项的子模块忽略
+pub fn parse_gitmodules(text: &str) -> HashMap<String, String> {
+    let mut urls = HashMap::new();
+    let mut in_submodule = false;
+    let mut path: Option<String> = None;
+    let mut url: Option<String> = None;
+
+    let mut flush = |path: &mut Option<String>, url: &mut Option<String>| {
+        if let (Some(p), Some(u)) = (path.take(), url.take()) {
+            urls.insert(p, u);
+        }
+    };
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if line.starts_with('[') {
+            flush(&mut path, &mut url);
+            path = None;
+            url = None;
+            in_submodule = line
+                .trim_start_matches('[')
+                .trim_start()
+                .starts_with("submodule");
+            continue;
+        }
+        if !in_submodule {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let value = value.trim().trim_matches('"').to_string();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "path" => path = Some(value.trim_end_matches('/').to_string()),
+            "url" => url = Some(value),
+            _ => {}
+        }
+    }
+    flush(&mut path, &mut url);
+
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gitmodules() {
+        let text = r#"
+[submodule "vendor/lib"]
+	path = vendor/lib
+	url = https://example.com/lib.git
+[core]
+	path = ignored
+; 注释
+[submodule "docs"]
+	url = "git@example.com:docs.git"
+	path = docs/
+[submodule "no-url"]
+	path = no-url
+"#;
+        let urls = parse_gitmodules(text);
+        assert_eq!(urls.len(), 2);
+        assert_eq!(urls["vendor/lib"], "https://example.com/lib.git");
+        assert_eq!(urls["docs"], "git@example.com:docs.git");
+    }
+}
@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use syntect::html::{line_tokens_to_classed_spans, ClassStyle};
+use syntect::parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet};
+
+/// 语法定义加载较慢，进程内只加载一次
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// Diff 语法高亮器：按文件扩展名高亮新文件一侧（新增行与上下文行）
+///
+/// 输出的 class 与 statics/highlight.css（syntect 生成）一致
+pub struct DiffHighlighter {
+    current_path: Option<PathBuf>,
+    syntax: Option<&'static SyntaxReference>,
+    parse_state: Option<ParseState>,
+    scope_stack: ScopeStack,
+}
+
+impl DiffHighlighter {
+    pub fn new() -> Self {
+        Self {
+            current_path: None,
+            syntax: None,
+            parse_state: None,
+            scope_stack: ScopeStack::new(),
+        }
+    }
+
+    /// hunk 之间不连续，重置解析状态
+    pub fn reset(&mut self) {
+        self.parse_state = self.syntax.map(ParseState::new);
+        self.scope_stack = ScopeStack::new();
+    }
+
+    /// 高亮一行新文件内容，未知文件类型返回 None
+    pub fn highlight_new_line(&mut self, path: Option<&Path>, line: &str) -> Option<String> {
+        if self.current_path.as_deref() != path {
+            self.current_path = path.map(Path::to_path_buf);
+            self.syntax = path
+                .and_then(|p| p.extension())
+                .and_then(|ext| ext.to_str())
+                .and_then(|ext| syntax_set().find_syntax_by_extension(ext));
+            self.reset();
+        }
+
+        let parse_state = self.parse_state.as_mut()?;
+        let ops = parse_state.parse_line(line, syntax_set()).ok()?;
+
+        // 每行自行闭合 span：先重新打开上一行遗留的作用域，结束时全部关闭
+        let mut html = String::new();
+        for scope in self.scope_stack.as_slice() {
+            html.push_str(&format!("<span class=\"{}\">", scope.build_string().replace('.', " ")));
+        }
+        let (spans, _) = line_tokens_to_classed_spans(line, &ops, ClassStyle::Spaced, &mut self.scope_stack).ok()?;
+        html.push_str(&spans);
+        html.push_str(&"</span>".repeat(self.scope_stack.len()));
+
+        Some(html)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_by_extension() {
+        let mut highlighter = DiffHighlighter::new();
+
+        let html = highlighter
+            .highlight_new_line(Some(Path::new("src/main.rs")), "fn main() {}\n")
+            .expect("rust should be highlighted");
+        assert!(html.contains("<span class=\""));
+        assert_eq!(html.matches("<span").count(), html.matches("</span>").count());
+
+        // 多行注释跨行时每行仍然闭合
+        highlighter.reset();
+        let first = highlighter.highlight_new_line(Some(Path::new("src/main.rs")), "/* a\n").unwrap();
+        let second = highlighter.highlight_new_line(Some(Path::new("src/main.rs")), "b */\n").unwrap();
+        for html in [&first, &second] {
+            assert_eq!(html.matches("<span").count(), html.matches("</span>").count());
+            assert!(html.contains("comment"));
+        }
+
+        assert!(highlighter.highlight_new_line(Some(Path::new("data.unknownext")), "x\n").is_none());
+        assert!(highlighter.highlight_new_line(None, "x\n").is_none());
+    }
+}
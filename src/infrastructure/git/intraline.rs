@@ -0,0 +1,126 @@
+use similar::{ChangeTag, TextDiff};
+
+/// 行内（单词级）diff：缓存 hunk 中相邻的删除/新增行，成对比较后标出变化的单词
+///
+/// 删除行与新增行按顺序一一配对，多出的行按原样输出
+#[derive(Default)]
+pub struct IntralineBuffer {
+    removed: Vec<String>,
+    /// (原始内容, 已渲染的内容)
+    added: Vec<(String, String)>,
+}
+
+impl IntralineBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 删除行总是排在同一组的新增行之前，已有新增行时先结束上一组
+    pub fn push_removed(&mut self, content: &str) -> Vec<(char, String)> {
+        let flushed = if self.added.is_empty() { Vec::new() } else { self.flush() };
+        self.removed.push(content.to_string());
+        flushed
+    }
+
+    /// `body` 是不配对时使用的渲染结果（可能带有语法高亮）
+    pub fn push_added(&mut self, content: &str, body: String) {
+        self.added.push((content.to_string(), body));
+    }
+
+    /// 输出缓存的行，返回 (origin, 渲染后的 HTML)
+    pub fn flush(&mut self) -> Vec<(char, String)> {
+        let removed = std::mem::take(&mut self.removed);
+        let added = std::mem::take(&mut self.added);
+
+        let mut removed_lines = Vec::with_capacity(removed.len());
+        let mut added_lines = Vec::with_capacity(added.len());
+        for (i, old) in removed.iter().enumerate() {
+            match added.get(i) {
+                Some((new, _)) => {
+                    let (old_html, new_html) = word_diff(old, new);
+                    removed_lines.push(('-', old_html));
+                    added_lines.push(('+', new_html));
+                }
+                None => removed_lines.push(('-', escape_html(old))),
+            }
+        }
+        added_lines.extend(added.into_iter().skip(removed.len()).map(|(_, body)| ('+', body)));
+
+        removed_lines.extend(added_lines);
+        removed_lines
+    }
+}
+
+/// 单词级比较两行，返回 (旧行 HTML, 新行 HTML)，变化部分包在 `diff-word-change` 中
+pub fn word_diff(old: &str, new: &str) -> (String, String) {
+    let diff = TextDiff::from_words(old, new);
+    let mut old_html = WordSpans::default();
+    let mut new_html = WordSpans::default();
+
+    for change in diff.iter_all_changes() {
+        let value = change.value();
+        match change.tag() {
+            ChangeTag::Equal => {
+                old_html.push(value, false);
+                new_html.push(value, false);
+            }
+            ChangeTag::Delete => old_html.push(value, true),
+            ChangeTag::Insert => new_html.push(value, true),
+        }
+    }
+
+    (old_html.finish(), new_html.finish())
+}
+
+/// 拼接单词，连续变化的单词合并到同一个 span
+#[derive(Default)]
+struct WordSpans {
+    html: String,
+    in_change: bool,
+}
+
+impl WordSpans {
+    fn push(&mut self, word: &str, changed: bool) {
+        if changed != self.in_change {
+            self.html.push_str(if changed { "<span class=\"diff-word-change\">" } else { "</span>" });
+            self.in_change = changed;
+        }
+        self.html.push_str(&escape_html(word));
+    }
+
+    fn finish(mut self) -> String {
+        if self.in_change {
+            self.html.push_str("</span>");
+        }
+        self.html
+    }
+}
+
+/// 转义 HTML 特殊字符
+pub fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_diff_pairs_lines() {
+        let (old, new) = word_diff("let totl = a < b;\n", "let total = a < b;\n");
+        assert_eq!(old, "let <span class=\"diff-word-change\">totl</span> = a &lt; b;\n");
+        assert_eq!(new, "let <span class=\"diff-word-change\">total</span> = a &lt; b;\n");
+
+        let mut buffer = IntralineBuffer::new();
+        assert!(buffer.push_removed("foo bar\n").is_empty());
+        assert!(buffer.push_removed("gone\n").is_empty());
+        buffer.push_added("foo baz\n", "foo baz\n".to_string());
+        let lines = buffer.flush();
+        assert_eq!(lines, vec![
+            ('-', "foo <span class=\"diff-word-change\">bar</span>\n".to_string()),
+            ('-', "gone\n".to_string()),
+            ('+', "foo <span class=\"diff-word-change\">baz</span>\n".to_string()),
+        ]);
+        assert!(buffer.flush().is_empty());
+    }
+}
@@ -1,3 +1,11 @@
 pub mod client;
+pub mod gitmodules;
+pub mod intraline;
+pub mod repo_pool;
+pub mod signature;
+#[cfg(feature = "highlight")]
+pub mod highlight;
+#[cfg(test)]
+pub mod test_repo;
 
 pub use client::Git2Client;
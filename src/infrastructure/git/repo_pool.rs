@@ -0,0 +1,137 @@
+use git2::Repository;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use crate::shared::result::Result;
+
+/// 已打开的 git2 仓库句柄池，避免每次操作重新读取 refs 与配置
+///
+/// `Repository` 不是 `Sync`，同一时刻只借给一个阻塞任务使用；用完归还，
+/// 空闲句柄按归还顺序淘汰（最久未用的先丢弃）。
+pub struct RepoPool {
+    capacity: usize,
+    idle: Mutex<Vec<(PathBuf, Repository)>>,
+    /// 每次失效时递增，借出期间发生过失效的句柄不再归还
+    generation: AtomicU64,
+}
+
+impl RepoPool {
+    /// `capacity` 为最多保留的空闲句柄数，0 表示不缓存
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            idle: Mutex::new(Vec::new()),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// 借出仓库句柄，池中没有时新打开
+    pub fn open(self: &Arc<Self>, path: &Path) -> Result<PooledRepo> {
+        let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let generation = self.generation.load(Ordering::Acquire);
+
+        let cached = {
+            let mut idle = self.idle.lock().unwrap();
+            idle.iter()
+                .rposition(|(p, _)| *p == key)
+                .map(|i| idle.remove(i).1)
+        };
+        let repo = match cached {
+            Some(repo) => repo,
+            None => Repository::open(&key)?,
+        };
+
+        Ok(PooledRepo {
+            repo: Some(repo),
+            key,
+            generation,
+            pool: Arc::clone(self),
+        })
+    }
+
+    /// 丢弃该仓库的空闲句柄（如 fetch 更新了 refs 后）
+    pub fn invalidate(&self, path: &Path) {
+        let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let mut idle = self.idle.lock().unwrap();
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        idle.retain(|(p, _)| *p != key);
+    }
+
+    fn release(&self, key: PathBuf, repo: Repository, generation: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut idle = self.idle.lock().unwrap();
+        if self.generation.load(Ordering::Acquire) != generation {
+            return;
+        }
+        idle.push((key, repo));
+        if idle.len() > self.capacity {
+            idle.remove(0);
+        }
+    }
+
+    #[cfg(test)]
+    fn idle_len(&self) -> usize {
+        self.idle.lock().unwrap().len()
+    }
+}
+
+/// 借出的仓库句柄，drop 时归还到池中
+pub struct PooledRepo {
+    repo: Option<Repository>,
+    key: PathBuf,
+    generation: u64,
+    pool: Arc<RepoPool>,
+}
+
+impl Deref for PooledRepo {
+    type Target = Repository;
+
+    fn deref(&self) -> &Repository {
+        self.repo.as_ref().expect("repository taken")
+    }
+}
+
+impl Drop for PooledRepo {
+    fn drop(&mut self) {
+        if let Some(repo) = self.repo.take() {
+            self.pool.release(std::mem::take(&mut self.key), repo, self.generation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::git::test_repo::TestRepo;
+
+    #[test]
+    fn test_reuse_evict_and_invalidate() {
+        let a = TestRepo::new();
+        let b = TestRepo::new();
+        let pool = Arc::new(RepoPool::new(1));
+
+        let first = pool.open(a.path()).unwrap();
+        let second = pool.open(a.path()).unwrap();
+        drop(first);
+        drop(second);
+        // 超出容量时丢弃最早归还的句柄
+        assert_eq!(pool.idle_len(), 1);
+        let reused = pool.open(a.path()).unwrap();
+        assert_eq!(pool.idle_len(), 0);
+        drop(reused);
+
+        // 借出期间失效的句柄不再归还
+        let held = pool.open(b.path()).unwrap();
+        pool.invalidate(a.path());
+        assert_eq!(pool.idle_len(), 0);
+        drop(held);
+        assert_eq!(pool.idle_len(), 0);
+
+        let disabled = Arc::new(RepoPool::new(0));
+        drop(disabled.open(a.path()).unwrap());
+        assert_eq!(disabled.idle_len(), 0);
+    }
+}
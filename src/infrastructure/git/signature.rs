@@ -0,0 +1,223 @@
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use crate::ports::git::{SignatureInfo, SignatureKind};
+
+/// 解析提交签名（gpgsig 头内容），识别签名类型与签名者
+///
+/// 这里只做识别，不针对 keyring 做验证
+pub fn parse_signature(raw: &[u8]) -> SignatureInfo {
+    let text = String::from_utf8_lossy(raw);
+
+    if text.contains("-----BEGIN SSH SIGNATURE-----") {
+        SignatureInfo {
+            kind: SignatureKind::Ssh,
+            signer: armored_body(&text, false).and_then(|blob| ssh_signer(&blob)),
+        }
+    } else if text.contains("-----BEGIN PGP SIGNATURE-----") {
+        SignatureInfo {
+            kind: SignatureKind::Gpg,
+            signer: armored_body(&text, true).and_then(|blob| pgp_signer(&blob)),
+        }
+    } else if text.contains("-----BEGIN SIGNED MESSAGE-----") {
+        SignatureInfo {
+            kind: SignatureKind::X509,
+            signer: None,
+        }
+    } else {
+        SignatureInfo {
+            kind: SignatureKind::Unknown,
+            signer: None,
+        }
+    }
+}
+
+/// 提取 ASCII armor 中的 base64 内容
+fn armored_body(text: &str, has_checksum: bool) -> Option<Vec<u8>> {
+    let mut in_body = false;
+    let mut encoded = String::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with("-----BEGIN") {
+            in_body = true;
+            continue;
+        }
+        if line.starts_with("-----END") {
+            break;
+        }
+        // 跳过 armor 头（Key: Value）、空行和 PGP 校验行（=XXXX）
+        if !in_body || line.is_empty() || line.contains(':') || (has_checksum && line.starts_with('=')) {
+            continue;
+        }
+        encoded.push_str(line);
+    }
+
+    STANDARD.decode(encoded).ok()
+}
+
+/// 读取 SSH 线格式的 string（u32 长度 + 内容）
+fn read_ssh_string(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let len = u32::from_be_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    let value = data.get(4..4 + len)?;
+    Some((value, &data[4 + len..]))
+}
+
+/// SSHSIG: "SSHSIG" + u32 版本 + string 公钥 + ...，签名者为公钥类型及 SHA256 指纹
+fn ssh_signer(blob: &[u8]) -> Option<String> {
+    let rest = blob.strip_prefix(b"SSHSIG")?;
+    let (public_key, _) = read_ssh_string(rest.get(4..)?)?;
+    let (key_type, _) = read_ssh_string(public_key)?;
+
+    let fingerprint = STANDARD_NO_PAD.encode(Sha256::digest(public_key));
+    Some(format!("{} SHA256:{}", String::from_utf8_lossy(key_type), fingerprint))
+}
+
+/// OpenPGP 签名包：优先使用 issuer fingerprint 子包，其次 issuer key id
+fn pgp_signer(blob: &[u8]) -> Option<String> {
+    let body = pgp_packet_body(blob)?;
+
+    match *body.first()? {
+        // v3: version, 5, sig type, time(4), key id(8)
+        3 => body.get(7..15).map(hex::encode_upper),
+        4 | 5 => {
+            let hashed_len = u16::from_be_bytes([*body.get(4)?, *body.get(5)?]) as usize;
+            let hashed = body.get(6..6 + hashed_len)?;
+            let unhashed_start = 6 + hashed_len;
+            let unhashed_len = u16::from_be_bytes([
+                *body.get(unhashed_start)?,
+                *body.get(unhashed_start + 1)?,
+            ]) as usize;
+            let unhashed = body.get(unhashed_start + 2..unhashed_start + 2 + unhashed_len)?;
+
+            let mut key_id = None;
+            for area in [hashed, unhashed] {
+                for (kind, data) in pgp_subpackets(area) {
+                    match kind {
+                        // issuer fingerprint: version + fingerprint
+                        33 if data.len() > 1 => return Some(hex::encode_upper(&data[1..])),
+                        // issuer key id
+                        16 if data.len() == 8 => key_id = Some(hex::encode_upper(data)),
+                        _ => {}
+                    }
+                }
+            }
+            key_id
+        }
+        _ => None,
+    }
+}
+
+/// 解析第一个 OpenPGP 包头，返回包体
+fn pgp_packet_body(blob: &[u8]) -> Option<&[u8]> {
+    let tag = *blob.first()?;
+    if tag & 0x80 == 0 {
+        return None;
+    }
+
+    let (len, offset) = if tag & 0x40 != 0 {
+        // 新格式
+        match *blob.get(1)? {
+            l @ 0..=191 => (l as usize, 2),
+            l @ 192..=223 => ((((l as usize) - 192) << 8) + *blob.get(2)? as usize + 192, 3),
+            255 => (u32::from_be_bytes(blob.get(2..6)?.try_into().ok()?) as usize, 6),
+            _ => return None,
+        }
+    } else {
+        // 旧格式
+        match tag & 0x03 {
+            0 => (*blob.get(1)? as usize, 2),
+            1 => (u16::from_be_bytes(blob.get(1..3)?.try_into().ok()?) as usize, 3),
+            2 => (u32::from_be_bytes(blob.get(1..5)?.try_into().ok()?) as usize, 5),
+            _ => (blob.len() - 1, 1),
+        }
+    };
+
+    blob.get(offset..offset + len)
+}
+
+/// 遍历签名子包，返回 (类型, 数据)
+fn pgp_subpackets(mut area: &[u8]) -> Vec<(u8, &[u8])> {
+    let mut packets = Vec::new();
+
+    while let Some(&first) = area.first() {
+        let (len, header) = match first {
+            0..=191 => (first as usize, 1),
+            192..=254 => match area.get(1) {
+                Some(&second) => ((((first as usize) - 192) << 8) + second as usize + 192, 2),
+                None => break,
+            },
+            255 => match area.get(1..5) {
+                Some(bytes) => (u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize, 5),
+                None => break,
+            },
+        };
+
+        let Some(packet) = area.get(header..header + len) else {
+            break;
+        };
+        if let Some((&kind, data)) = packet.split_first() {
+            packets.push((kind & 0x7f, data));
+        }
+        area = &area[header + len..];
+    }
+
+    packets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // gpg --quick-gen-key 'Test <test@example.com>' ed25519 生成的密钥签名
+    const PGP_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----
+
+iHUEABYIAB0WIQRHKC3O2xxFhWYrpjL3bysFcR8KtAUCatCnZQAKCRD3bysFcR8K
+tNexAPwNU4equeMA45j5Lp+PSNJJp9Q1AyJtpbMtwPs1/aV0DwD8CaUuJp6gUK5J
+TjtIjT+4g4BXrNEXPNiNejGSupYAmw0=
+=cLDj
+-----END PGP SIGNATURE-----
+";
+
+    // ssh-keygen -Y sign -n git 生成的签名
+    const SSH_SIGNATURE: &str = "-----BEGIN SSH SIGNATURE-----
+U1NIU0lHAAAAAQAAADMAAAALc3NoLWVkMjU1MTkAAAAgsSUIb+bwxFWGmW16g9B+L5rFSI
+AzfaXg6KQdh+dunE0AAAADZ2l0AAAAAAAAAAZzaGE1MTIAAABTAAAAC3NzaC1lZDI1NTE5
+AAAAQJVFXHLXR53RsV1RyRjqRPRI0dL03VV7f2lZQM7J5oKUpBneqNMmfv5wDxnhWja7YS
+ybS8YsGA6KhLmR1SrOUAs=
+-----END SSH SIGNATURE-----
+";
+
+    #[test]
+    fn test_parse_signature() {
+        let pgp = parse_signature(PGP_SIGNATURE.as_bytes());
+        assert_eq!(pgp.kind, SignatureKind::Gpg);
+        // 与 gpg --list-keys 输出的指纹一致
+        assert_eq!(pgp.signer.as_deref(), Some("47282DCEDB1C4585662BA632F76F2B05711F0AB4"));
+
+        let ssh = parse_signature(SSH_SIGNATURE.as_bytes());
+        assert_eq!(ssh.kind, SignatureKind::Ssh);
+        // 与 ssh-keygen -lf 输出的指纹一致
+        assert_eq!(
+            ssh.signer.as_deref(),
+            Some("ssh-ed25519 SHA256:sR2x3xkAGayh7coKbd3TT16unE1U9D6USWrUNbw3Rdo")
+        );
+
+        // 内容损坏时仍识别类型，但不给出签名者
+        let malformed = parse_signature(SSH_SIGNATURE.replace("U1NIU0lH", "AAAAAAAA").as_bytes());
+        assert_eq!(malformed.kind, SignatureKind::Ssh);
+        assert_eq!(malformed.signer, None);
+
+        let garbage = "-----BEGIN PGP SIGNATURE-----\n\n!!not base64!!\n-----END PGP SIGNATURE-----\n";
+        let malformed = parse_signature(garbage.as_bytes());
+        assert_eq!(malformed.kind, SignatureKind::Gpg);
+        assert_eq!(malformed.signer, None);
+
+        // 包头声明的长度超出实际数据
+        let truncated = PGP_SIGNATURE.replace("TjtIjT+4g4BXrNEXPNiNejGSupYAmw0=\n", "");
+        assert_eq!(parse_signature(truncated.as_bytes()).signer, None);
+
+        assert_eq!(parse_signature(b"-----BEGIN SIGNED MESSAGE-----").kind, SignatureKind::X509);
+        assert_eq!(parse_signature(b"garbage").kind, SignatureKind::Unknown);
+    }
+}
@@ -0,0 +1,80 @@
+//! 测试用临时 git 仓库：通过 git2 创建提交、分支、标签，目录随 `TestRepo` 一起删除
+
+use git2::{Oid, Repository, Signature, Time};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// 临时目录中的非裸仓库
+pub struct TestRepo {
+    dir: TempDir,
+    pub repo: Repository,
+}
+
+impl TestRepo {
+    pub fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        Self { dir, repo }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// 写入/删除文件并提交到当前分支
+    pub fn commit(&self, writes: &[(&str, &str)], removes: &[&str], message: &str) -> Oid {
+        commit_changes(&self.repo, writes, removes, message)
+    }
+
+    /// 当前分支的完整 ref 名称（如 refs/heads/master）
+    pub fn head_ref(&self) -> String {
+        self.repo.head().unwrap().name().unwrap().to_string()
+    }
+
+    /// 在指定提交上创建本地分支
+    pub fn branch(&self, name: &str, target: Oid) {
+        self.repo.branch(name, &self.repo.find_commit(target).unwrap(), false).unwrap();
+    }
+
+    /// 创建标注标签，tagger 时间为 `time`（Unix 秒）
+    pub fn tag(&self, name: &str, target: Oid, message: &str, time: i64) {
+        let tagger = Signature::new("Tester", "tester@example.com", &Time::new(time, 0)).unwrap();
+        let object = self.repo.find_object(target, None).unwrap();
+        self.repo.tag(name, &object, &tagger, message, false).unwrap();
+    }
+
+    /// 克隆到同一临时目录下，返回克隆路径（`bare` 为 true 时克隆为裸仓库）
+    pub fn clone_to(&self, name: &str, bare: bool) -> PathBuf {
+        let target = self.dir.path().join(name);
+        git2::build::RepoBuilder::new()
+            .bare(bare)
+            .clone(self.path().to_str().unwrap(), &target)
+            .unwrap();
+        target
+    }
+}
+
+/// 写入/删除文件并提交，返回新提交的 OID
+pub fn commit_changes(repo: &Repository, writes: &[(&str, &str)], removes: &[&str], message: &str) -> Oid {
+    let workdir = repo.workdir().unwrap().to_path_buf();
+    let mut index = repo.index().unwrap();
+    for (name, content) in writes {
+        let file = workdir.join(name);
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(file, content).unwrap();
+        index.add_path(Path::new(name)).unwrap();
+    }
+    for name in removes {
+        std::fs::remove_file(workdir.join(name)).unwrap();
+        index.remove_path(Path::new(name)).unwrap();
+    }
+    index.write().unwrap();
+
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Tester", "tester@example.com").unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents).unwrap()
+}
@@ -1,9 +1,9 @@
 use async_trait::async_trait;
-use sqlx::{SqlitePool, Row};
-use chrono::DateTime;
+use sqlx::{Connection, SqlitePool, Row};
 use crate::domain::entities::Branch;
 use crate::ports::branch::BranchPort;
 use crate::shared::result::Result;
+use super::ts_to_datetime;
 
 /// SQLite 分支仓储实现
 pub struct SqliteBranchRepository {
@@ -18,29 +18,6 @@ impl SqliteBranchRepository {
 
 #[async_trait]
 impl BranchPort for SqliteBranchRepository {
-    async fn save(&self, branch: &Branch) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO branches (repository_id, name, target_oid, is_default, updated_at)
-            VALUES (?, ?, ?, ?, ?)
-            ON CONFLICT(repository_id, name) 
-            DO UPDATE SET
-                target_oid = excluded.target_oid,
-                is_default = excluded.is_default,
-                updated_at = excluded.updated_at
-            "#,
-        )
-        .bind(branch.repository_id)
-        .bind(&branch.name)
-        .bind(&branch.target_oid)
-        .bind(branch.is_default)
-        .bind(branch.updated_at.timestamp())
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
-    }
-
     async fn save_many(&self, branches: &[Branch]) -> Result<()> {
         if branches.is_empty() {
             return Ok(());
@@ -76,7 +53,7 @@ impl BranchPort for SqliteBranchRepository {
     async fn find_by_repository(&self, repository_id: i64) -> Result<Vec<Branch>> {
         let rows = sqlx::query(
             r#"
-            SELECT id, repository_id, name, target_oid, is_default, updated_at
+            SELECT id, repository_id, name, target_oid, is_default, updated_at, commit_count
             FROM branches
             WHERE repository_id = ?
             ORDER BY is_default DESC, name ASC
@@ -94,17 +71,134 @@ impl BranchPort for SqliteBranchRepository {
                 name: r.get("name"),
                 target_oid: r.get("target_oid"),
                 is_default: r.get("is_default"),
-                updated_at: DateTime::from_timestamp(r.get("updated_at"), 0).unwrap(),
+                updated_at: ts_to_datetime(r.get("updated_at")),
+                commit_count: r.get("commit_count"),
             })
             .collect())
     }
 
-    async fn delete_by_repository(&self, repository_id: i64) -> Result<()> {
-        sqlx::query("DELETE FROM branches WHERE repository_id = ?")
+    async fn delete_missing(&self, repository_id: i64, keep_names: &[String]) -> Result<Vec<String>> {
+        // 删除大分支的提交耗时较长，不受语句超时限制
+        let mut conn = super::acquire_unlimited(&self.pool).await?;
+        let mut tx = conn.begin().await?;
+
+        let existing: Vec<String> = sqlx::query_scalar("SELECT name FROM branches WHERE repository_id = ?")
+            .bind(repository_id)
+            .fetch_all(&mut *tx)
+            .await?;
+        let missing: Vec<String> = existing
+            .into_iter()
+            .filter(|name| !keep_names.contains(name))
+            .collect();
+
+        // commits 按 (oid, branch) 存储，删除分支的记录不影响其它分支上的同一提交
+        for name in &missing {
+            sqlx::query("DELETE FROM commits WHERE repository_id = ? AND branch = ?")
+                .bind(repository_id)
+                .bind(name)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("DELETE FROM branches WHERE repository_id = ? AND name = ?")
+                .bind(repository_id)
+                .bind(name)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(missing)
+    }
+
+    async fn indexed_tip(&self, repository_id: i64, name: &str) -> Result<Option<String>> {
+        let tip: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT indexed_tip_oid FROM branches WHERE repository_id = ? AND name = ?",
+        )
+        .bind(repository_id)
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(tip.flatten())
+    }
+
+    async fn set_indexed_tip(&self, repository_id: i64, name: &str, oid: &str) -> Result<()> {
+        sqlx::query("UPDATE branches SET indexed_tip_oid = ? WHERE repository_id = ? AND name = ?")
+            .bind(oid)
             .bind(repository_id)
+            .bind(name)
             .execute(&self.pool)
             .await?;
 
         Ok(())
     }
+
+    async fn set_commit_count(&self, repository_id: i64, name: &str, count: i64) -> Result<()> {
+        sqlx::query("UPDATE branches SET commit_count = ? WHERE repository_id = ? AND name = ?")
+            .bind(count)
+            .bind(repository_id)
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::sqlite::run_migrations;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn test_delete_missing() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        sqlx::query("INSERT INTO repositories (id, name, path, created_at, updated_at) VALUES (1, 'r', '/tmp/r', 0, 0)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        // 同一提交同时出现在两个分支上
+        for branch in ["origin/main", "origin/gone"] {
+            sqlx::query(
+                "INSERT INTO commits (repository_id, oid, branch, author_name, author_email, author_time, \
+                 committer_name, committer_email, committer_time, summary, created_at) \
+                 VALUES (1, 'abc', ?, 'a', 'a@x', 0, 'a', 'a@x', 0, 's', 0)",
+            )
+            .bind(branch)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let store = SqliteBranchRepository::new(pool.clone());
+        let branches: Vec<Branch> = ["origin/main", "origin/gone"]
+            .iter()
+            .map(|name| Branch {
+                id: 0,
+                repository_id: 1,
+                name: name.to_string(),
+                target_oid: "abc".to_string(),
+                is_default: false,
+                updated_at: chrono::Utc::now(),
+                commit_count: 0,
+            })
+            .collect();
+        store.save_many(&branches).await.unwrap();
+
+        let removed = store.delete_missing(1, &["origin/main".to_string()]).await.unwrap();
+        assert_eq!(removed, vec!["origin/gone".to_string()]);
+
+        let names: Vec<String> = store.find_by_repository(1).await.unwrap().into_iter().map(|b| b.name).collect();
+        assert_eq!(names, vec!["origin/main".to_string()]);
+        let commit_branches: Vec<String> = sqlx::query_scalar("SELECT branch FROM commits")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(commit_branches, vec!["origin/main".to_string()]);
+    }
 }
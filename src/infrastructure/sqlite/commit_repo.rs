@@ -1,9 +1,55 @@
 use async_trait::async_trait;
-use sqlx::{SqlitePool, Row};
-use chrono::DateTime;
+use futures::stream::{BoxStream, StreamExt};
+use std::collections::HashSet;
+use sqlx::{Connection, SqlitePool, Row};
+use sqlx::sqlite::SqliteRow;
 use crate::domain::entities::Commit;
-use crate::ports::commit::CommitPort;
+use crate::ports::commit::{CommitPort, CommitFilter, CommitOrder, CommitTotals, AuthorStat, DailyActivity, BranchCommitCount};
+use crate::domain::value_objects::CommitSha;
+use crate::shared::error::GitxError;
 use crate::shared::result::Result;
+use super::ts_to_datetime;
+
+/// `stream_by_repository` 每批读取的行数（按 id keyset 分页）
+const STREAM_BATCH_SIZE: i64 = 1000;
+
+type SqliteQuery<'q> = sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>;
+
+/// 按过滤条件追加 WHERE 子句，参数顺序与 `bind_filter` 一致
+fn push_filter(sql: &mut String, filter: &CommitFilter<'_>) {
+    if filter.branch.is_some() {
+        sql.push_str(" AND branch = ?");
+    }
+    if filter.author.is_some() {
+        sql.push_str(" AND (author_name LIKE ? ESCAPE '\\' OR author_email LIKE ? ESCAPE '\\')");
+    }
+    if filter.since.is_some() {
+        sql.push_str(" AND committer_time >= ?");
+    }
+    if filter.until.is_some() {
+        sql.push_str(" AND committer_time < ?");
+    }
+}
+
+/// 绑定 `push_filter` 追加的参数
+fn bind_filter<'q>(mut query: SqliteQuery<'q>, filter: &CommitFilter<'q>) -> SqliteQuery<'q> {
+    if let Some(branch) = filter.branch {
+        query = query.bind(branch);
+    }
+    if let Some(author) = filter.author {
+        // LIKE 对 ASCII 不区分大小写；转义通配符，按字面子串匹配
+        let escaped = author.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        let pattern = format!("%{}%", escaped);
+        query = query.bind(pattern.clone()).bind(pattern);
+    }
+    if let Some(since) = filter.since {
+        query = query.bind(since);
+    }
+    if let Some(until) = filter.until {
+        query = query.bind(until);
+    }
+    query
+}
 
 /// SQLite 提交仓储实现
 pub struct SqliteCommitRepository {
@@ -16,15 +62,79 @@ impl SqliteCommitRepository {
     }
 }
 
+/// 将查询 `commits` 全部列的结果行转换为实体
+fn commit_from_row(r: &SqliteRow) -> Commit {
+    Commit {
+        id: r.get("id"),
+        repository_id: r.get("repository_id"),
+        oid: r.get("oid"),
+        branch: r.get("branch"),
+        author_name: r.get("author_name"),
+        author_email: r.get("author_email"),
+        author_time: ts_to_datetime(r.get("author_time")),
+        committer_name: r.get("committer_name"),
+        committer_email: r.get("committer_email"),
+        committer_time: ts_to_datetime(r.get("committer_time")),
+        summary: r.get("summary"),
+        message: r.get("message"),
+        parent_oids: r.get("parent_oids"),
+        created_at: ts_to_datetime(r.get("created_at")),
+        files_changed: r.get("files_changed"),
+        insertions: r.get("insertions"),
+        deletions: r.get("deletions"),
+        raw_author_name: r.get("raw_author_name"),
+        raw_author_email: r.get("raw_author_email"),
+        tree_oid: r.get("tree_oid"),
+    }
+}
+
 #[async_trait]
 impl CommitPort for SqliteCommitRepository {
+    fn stream_by_repository(&self, repository_id: i64) -> BoxStream<'_, Result<Commit>> {
+        // 按 id 分批读取：每批单独借出连接，语句超时只作用于一批，导出不会被整体截断
+        futures::stream::unfold(Some(0i64), move |after| async move {
+            let rows = sqlx::query(
+                r#"
+                SELECT id, repository_id, oid, branch,
+                       author_name, author_email, author_time,
+                       committer_name, committer_email, committer_time,
+                       summary, message, parent_oids, created_at,
+                       files_changed, insertions, deletions,
+                       raw_author_name, raw_author_email, tree_oid
+                FROM commits
+                WHERE repository_id = ? AND id > ?
+                ORDER BY id
+                LIMIT ?
+                "#,
+            )
+            .bind(repository_id)
+            .bind(after?)
+            .bind(STREAM_BATCH_SIZE)
+            .fetch_all(&self.pool)
+            .await;
+            match rows {
+                Ok(rows) if rows.is_empty() => None,
+                Ok(rows) => {
+                    let last = rows.last().map(|r| r.get::<i64, _>("id"));
+                    Some((futures::stream::iter(rows.into_iter().map(Ok)).left_stream(), last))
+                }
+                Err(e) => Some((futures::stream::once(async { Err(e) }).right_stream(), None)),
+            }
+        })
+        .flatten()
+        .map(|row: sqlx::Result<SqliteRow>| Ok(commit_from_row(&row?)))
+        .boxed()
+    }
+
     async fn find_by_oid(&self, repository_id: i64, oid: &str) -> Result<Option<Commit>> {
         let row = sqlx::query(
             r#"
             SELECT id, repository_id, oid, branch,
                    author_name, author_email, author_time,
                    committer_name, committer_email, committer_time,
-                   summary, message, parent_oids, created_at
+                   summary, message, parent_oids, created_at,
+                   files_changed, insertions, deletions,
+                   raw_author_name, raw_author_email, tree_oid
             FROM commits
             WHERE repository_id = ? AND oid = ?
             LIMIT 1
@@ -35,88 +145,143 @@ impl CommitPort for SqliteCommitRepository {
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|r| Commit {
-            id: r.get("id"),
-            repository_id: r.get("repository_id"),
-            oid: r.get("oid"),
-            branch: r.get("branch"),
-            author_name: r.get("author_name"),
-            author_email: r.get("author_email"),
-            author_time: DateTime::from_timestamp(r.get("author_time"), 0).unwrap(),
-            committer_name: r.get("committer_name"),
-            committer_email: r.get("committer_email"),
-            committer_time: DateTime::from_timestamp(r.get("committer_time"), 0).unwrap(),
-            summary: r.get("summary"),
-            message: r.get("message"),
-            parent_oids: r.get("parent_oids"),
-            created_at: DateTime::from_timestamp(r.get("created_at"), 0).unwrap(),
-        }))
+        Ok(row.map(|r| commit_from_row(&r)))
+    }
+
+    async fn find_by_oid_prefix(&self, repository_id: i64, prefix: &str) -> Result<Option<Commit>> {
+        if prefix.len() < CommitSha::MIN_ABBREV_LEN {
+            return Err(GitxError::InvalidOid(format!("OID prefix must be at least {} characters", CommitSha::MIN_ABBREV_LEN)));
+        }
+        // OID 以小写十六进制存储；转义通配符，按字面前缀匹配
+        let prefix = prefix.to_ascii_lowercase();
+        let pattern = format!("{}%", prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+        // 同一提交在每个分支各有一行，按不同的 OID 判断是否唯一；范围条件用于走 oid 索引
+        let oids: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT oid FROM commits
+            WHERE repository_id = ? AND oid >= ? AND oid < ? || 'g' AND oid LIKE ? ESCAPE '\'
+            LIMIT 2
+            "#,
+        )
+        .bind(repository_id)
+        .bind(&prefix)
+        .bind(&prefix)
+        .bind(pattern)
+        .fetch_all(&self.pool)
+        .await?;
+
+        match oids.as_slice() {
+            [] => Ok(None),
+            [oid] => self.find_by_oid(repository_id, oid).await,
+            _ => Err(GitxError::AmbiguousOid(prefix)),
+        }
+    }
+
+    async fn find_by_oids(&self, repository_id: i64, oids: &[String]) -> Result<Vec<Commit>> {
+        if oids.is_empty() {
+            return Ok(Vec::new());
+        }
+        // 调用方限制了数量，单条查询不会超出 SQLite 参数上限
+        let sql = format!(
+            r#"
+            SELECT id, repository_id, oid, branch,
+                   author_name, author_email, author_time,
+                   committer_name, committer_email, committer_time,
+                   summary, message, parent_oids, created_at,
+                   files_changed, insertions, deletions,
+                   raw_author_name, raw_author_email, tree_oid
+            FROM commits
+            WHERE repository_id = ? AND oid IN ({})
+            ORDER BY id
+            "#,
+            vec!["?"; oids.len()].join(", ")
+        );
+        let mut query = sqlx::query(&sql).bind(repository_id);
+        for oid in oids {
+            query = query.bind(oid);
+        }
+        let rows = query.fetch_all(&self.pool).await?;
+
+        let mut seen = HashSet::new();
+        Ok(rows
+            .into_iter()
+            .filter(|r| seen.insert(r.get::<String, _>("oid")))
+            .map(|r| commit_from_row(&r))
+            .collect())
     }
 
     async fn list_by_repository(
         &self,
         repository_id: i64,
-        branch: Option<&str>,
+        filter: &CommitFilter<'_>,
+        order: CommitOrder,
         limit: i64,
         offset: i64,
     ) -> Result<Vec<Commit>> {
-        let rows = if let Some(branch_name) = branch {
-            sqlx::query(
-                r#"
-                SELECT id, repository_id, oid, branch,
-                       author_name, author_email, author_time,
-                       committer_name, committer_email, committer_time,
-                       summary, message, parent_oids, created_at
-                FROM commits
-                WHERE repository_id = ? AND branch = ?
-                ORDER BY author_time DESC
-                LIMIT ? OFFSET ?
-                "#,
-            )
-            .bind(repository_id)
-            .bind(branch_name)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?
-        } else {
-            sqlx::query(
-                r#"
-                SELECT id, repository_id, oid, branch,
-                       author_name, author_email, author_time,
-                       committer_name, committer_email, committer_time,
-                       summary, message, parent_oids, created_at
-                FROM commits
-                WHERE repository_id = ?
-                ORDER BY author_time DESC
-                LIMIT ? OFFSET ?
-                "#,
-            )
-            .bind(repository_id)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?
-        };
+        let mut sql = String::from(
+            r#"
+            SELECT id, repository_id, oid, branch,
+                   author_name, author_email, author_time,
+                   committer_name, committer_email, committer_time,
+                   summary, message, parent_oids, created_at,
+                   files_changed, insertions, deletions,
+                   raw_author_name, raw_author_email, tree_oid
+            FROM commits
+            WHERE repository_id = ?
+            "#,
+        );
+        push_filter(&mut sql, filter);
+        sql.push_str(" ORDER BY ");
+        sql.push_str(order.sql());
+        sql.push_str(" LIMIT ? OFFSET ?");
+
+        let query = bind_filter(sqlx::query(&sql).bind(repository_id), filter);
+        let rows = query.bind(limit).bind(offset).fetch_all(&self.pool).await?;
 
         Ok(rows
             .into_iter()
-            .map(|r| Commit {
-                id: r.get("id"),
-                repository_id: r.get("repository_id"),
-                oid: r.get("oid"),
-                branch: r.get("branch"),
-                author_name: r.get("author_name"),
-                author_email: r.get("author_email"),
-                author_time: DateTime::from_timestamp(r.get("author_time"), 0).unwrap(),
-                committer_name: r.get("committer_name"),
-                committer_email: r.get("committer_email"),
-                committer_time: DateTime::from_timestamp(r.get("committer_time"), 0).unwrap(),
-                summary: r.get("summary"),
-                message: r.get("message"),
-                parent_oids: r.get("parent_oids"),
-                created_at: DateTime::from_timestamp(r.get("created_at"), 0).unwrap(),
-            })
+            .map(|r| commit_from_row(&r))
+            .collect())
+    }
+
+    async fn list_by_repository_keyset(
+        &self,
+        repository_id: i64,
+        filter: &CommitFilter<'_>,
+        before_committer_time: Option<i64>,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<Commit>> {
+        let cursor = before_committer_time.zip(before_id);
+
+        // 按条件拼接 WHERE，走 idx_commits_committer_time 索引，不需要扫描跳过的行
+        let mut sql = String::from(
+            r#"
+            SELECT id, repository_id, oid, branch,
+                   author_name, author_email, author_time,
+                   committer_name, committer_email, committer_time,
+                   summary, message, parent_oids, created_at,
+                   files_changed, insertions, deletions,
+                   raw_author_name, raw_author_email, tree_oid
+            FROM commits
+            WHERE repository_id = ?
+            "#,
+        );
+        push_filter(&mut sql, filter);
+        if cursor.is_some() {
+            sql.push_str(" AND (committer_time, id) < (?, ?)");
+        }
+        sql.push_str(" ORDER BY committer_time DESC, id DESC LIMIT ?");
+
+        let mut query = bind_filter(sqlx::query(&sql).bind(repository_id), filter);
+        if let Some((committer_time, id)) = cursor {
+            query = query.bind(committer_time).bind(id);
+        }
+        let rows = query.bind(limit).fetch_all(&self.pool).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| commit_from_row(&r))
             .collect())
     }
 
@@ -130,7 +295,9 @@ impl CommitPort for SqliteCommitRepository {
             SELECT id, repository_id, oid, branch,
                    author_name, author_email, author_time,
                    committer_name, committer_email, committer_time,
-                   summary, message, parent_oids, created_at
+                   summary, message, parent_oids, created_at,
+                   files_changed, insertions, deletions,
+                   raw_author_name, raw_author_email, tree_oid
             FROM commits
             WHERE repository_id = ? AND branch = ?
             ORDER BY committer_time DESC
@@ -142,22 +309,28 @@ impl CommitPort for SqliteCommitRepository {
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|r| Commit {
-            id: r.get("id"),
-            repository_id: r.get("repository_id"),
-            oid: r.get("oid"),
-            branch: r.get("branch"),
-            author_name: r.get("author_name"),
-            author_email: r.get("author_email"),
-            author_time: DateTime::from_timestamp(r.get("author_time"), 0).unwrap(),
-            committer_name: r.get("committer_name"),
-            committer_email: r.get("committer_email"),
-            committer_time: DateTime::from_timestamp(r.get("committer_time"), 0).unwrap(),
-            summary: r.get("summary"),
-            message: r.get("message"),
-            parent_oids: r.get("parent_oids"),
-            created_at: DateTime::from_timestamp(r.get("created_at"), 0).unwrap(),
-        }))
+        Ok(row.map(|r| commit_from_row(&r)))
+    }
+
+    async fn existing_oids(&self, repository_id: i64, branch: &str, oids: &[String]) -> Result<HashSet<String>> {
+        let mut existing = HashSet::new();
+
+        // 与 bulk_insert 一样受 SQLite 参数个数限制，分批查询
+        const BATCH_SIZE: usize = 500;
+
+        for chunk in oids.chunks(BATCH_SIZE) {
+            let sql = format!(
+                "SELECT oid FROM commits WHERE repository_id = ? AND branch = ? AND oid IN ({})",
+                vec!["?"; chunk.len()].join(", ")
+            );
+            let mut query = sqlx::query_scalar::<_, String>(&sql).bind(repository_id).bind(branch);
+            for oid in chunk {
+                query = query.bind(oid);
+            }
+            existing.extend(query.fetch_all(&self.pool).await?);
+        }
+
+        Ok(existing)
     }
 
     async fn bulk_insert(&self, commits: &[Commit]) -> Result<usize> {
@@ -165,17 +338,19 @@ impl CommitPort for SqliteCommitRepository {
             return Ok(0);
         }
 
-        let mut tx = self.pool.begin().await?;
+        // 大分支首次索引的写入事务可能较长，不受语句超时限制
+        let mut conn = super::acquire_unlimited(&self.pool).await?;
+        let mut tx = conn.begin().await?;
         let mut total_inserted = 0;
 
-        // SQLite参数限制约999个，每个commit需要13个参数
-        // 所以每批最多插入 999/13 ≈ 76 条记录，保守使用50条
+        // SQLite参数限制约999个，每个commit需要19个参数
+        // 所以每批最多插入 999/19 ≈ 52 条记录，保守使用50条
         const BATCH_SIZE: usize = 50;
 
         for chunk in commits.chunks(BATCH_SIZE) {
             // 构建多值INSERT语句
             let placeholders: Vec<String> = (0..chunk.len())
-                .map(|_| "(?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)".to_string())
+                .map(|_| "(?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)".to_string())
                 .collect();
             
             let sql = format!(
@@ -184,7 +359,9 @@ impl CommitPort for SqliteCommitRepository {
                     repository_id, oid, branch,
                     author_name, author_email, author_time,
                     committer_name, committer_email, committer_time,
-                    summary, message, parent_oids, created_at
+                    summary, message, parent_oids, created_at,
+                    files_changed, insertions, deletions,
+                    raw_author_name, raw_author_email, tree_oid
                 )
                 VALUES {}
                 ON CONFLICT(repository_id, oid, branch) DO NOTHING
@@ -213,7 +390,13 @@ impl CommitPort for SqliteCommitRepository {
                     .bind(&commit.summary)
                     .bind(&commit.message)
                     .bind(&commit.parent_oids)
-                    .bind(created_ts);
+                    .bind(created_ts)
+                    .bind(commit.files_changed)
+                    .bind(commit.insertions)
+                    .bind(commit.deletions)
+                    .bind(&commit.raw_author_name)
+                    .bind(&commit.raw_author_email)
+                    .bind(&commit.tree_oid);
             }
 
             let result = query.execute(&mut *tx).await?;
@@ -224,70 +407,121 @@ impl CommitPort for SqliteCommitRepository {
         Ok(total_inserted)
     }
 
-    async fn save(&self, commit: &Commit) -> Result<i64> {
-        let author_time_ts = commit.author_time.timestamp();
-        let committer_time_ts = commit.committer_time.timestamp();
-        let created_ts = commit.created_at.timestamp();
+    async fn delete_by_branch(&self, repository_id: i64, branch: &str) -> Result<u64> {
+        let mut conn = super::acquire_unlimited(&self.pool).await?;
+        let result = sqlx::query("DELETE FROM commits WHERE repository_id = ? AND branch = ?")
+            .bind(repository_id)
+            .bind(branch)
+            .execute(&mut *conn)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn count_by_repository(&self, repository_id: i64, filter: &CommitFilter<'_>) -> Result<i64> {
+        let mut sql = String::from("SELECT COUNT(*) FROM commits WHERE repository_id = ?");
+        push_filter(&mut sql, filter);
 
-        let result = sqlx::query(
+        let row = bind_filter(sqlx::query(&sql).bind(repository_id), filter)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.get(0))
+    }
+
+    async fn commit_totals(&self, repository_id: i64) -> Result<CommitTotals> {
+        let row = sqlx::query(
             r#"
-            INSERT INTO commits (
-                repository_id, oid, branch,
-                author_name, author_email, author_time,
-                committer_name, committer_email, committer_time,
-                summary, message, parent_oids, created_at
-            )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            ON CONFLICT(repository_id, oid, branch) DO UPDATE SET
-                summary = excluded.summary,
-                message = excluded.message
-            RETURNING id
+            SELECT COUNT(DISTINCT oid) AS total_commits,
+                   MIN(committer_time) AS first_commit_time,
+                   MAX(committer_time) AS last_commit_time
+            FROM commits
+            WHERE repository_id = ?
             "#,
         )
-        .bind(commit.repository_id)
-        .bind(&commit.oid)
-        .bind(&commit.branch)
-        .bind(&commit.author_name)
-        .bind(&commit.author_email)
-        .bind(author_time_ts)
-        .bind(&commit.committer_name)
-        .bind(&commit.committer_email)
-        .bind(committer_time_ts)
-        .bind(&commit.summary)
-        .bind(&commit.message)
-        .bind(&commit.parent_oids)
-        .bind(created_ts)
+        .bind(repository_id)
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(result.get("id"))
+        Ok(CommitTotals {
+            total_commits: row.get("total_commits"),
+            first_commit_time: row.get("first_commit_time"),
+            last_commit_time: row.get("last_commit_time"),
+        })
     }
 
-    async fn delete_by_repository(&self, repository_id: i64) -> Result<()> {
-        sqlx::query("DELETE FROM commits WHERE repository_id = ?")
-            .bind(repository_id)
-            .execute(&self.pool)
-            .await?;
-        Ok(())
+    async fn author_stats(&self, repository_id: i64, limit: i64) -> Result<Vec<AuthorStat>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT LOWER(author_email) AS email,
+                   MAX(author_name) AS name,
+                   COUNT(DISTINCT oid) AS commits
+            FROM commits
+            WHERE repository_id = ?
+            GROUP BY LOWER(author_email)
+            ORDER BY commits DESC, email ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(repository_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| AuthorStat {
+                author_email: r.get("email"),
+                author_name: r.get("name"),
+                commits: r.get("commits"),
+            })
+            .collect())
     }
 
-    async fn count_by_repository(&self, repository_id: i64, branch: Option<&str>) -> Result<i64> {
-        let count: i64 = if let Some(branch_name) = branch {
-            sqlx::query_scalar(
-                "SELECT COUNT(*) FROM commits WHERE repository_id = ? AND branch = ?",
-            )
-            .bind(repository_id)
-            .bind(branch_name)
-            .fetch_one(&self.pool)
-            .await?
-        } else {
-            sqlx::query_scalar("SELECT COUNT(*) FROM commits WHERE repository_id = ?")
-                .bind(repository_id)
-                .fetch_one(&self.pool)
-                .await?
-        };
+    async fn activity_by_day(&self, repository_id: i64) -> Result<Vec<DailyActivity>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT date(committer_time, 'unixepoch') AS day,
+                   COUNT(DISTINCT oid) AS commits
+            FROM commits
+            WHERE repository_id = ?
+            GROUP BY day
+            ORDER BY day ASC
+            "#,
+        )
+        .bind(repository_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| DailyActivity {
+                day: r.get("day"),
+                commits: r.get("commits"),
+            })
+            .collect())
+    }
 
-        Ok(count)
+    async fn branch_commit_counts(&self, repository_id: i64) -> Result<Vec<BranchCommitCount>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT branch, COUNT(*) AS commits
+            FROM commits
+            WHERE repository_id = ?
+            GROUP BY branch
+            ORDER BY branch ASC
+            "#,
+        )
+        .bind(repository_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| BranchCommitCount {
+                branch: r.get("branch"),
+                commits: r.get("commits"),
+            })
+            .collect())
     }
     
     async fn find_diff_commits(
@@ -298,18 +532,22 @@ impl CommitPort for SqliteCommitRepository {
         limit: i64,
     ) -> Result<Vec<Commit>> {
         // 查找在old_branch但不在new_branch的commits（老分支特有的commits）
-        // 通过 (author_name, summary, committer_time) 组合来识别相同的逻辑commit
+        // 通过 (author_email, author_time, summary) 组合来识别相同的逻辑commit：
+        // cherry-pick 保留作者信息但会改变 committer_time，因此不能用 committer_time 匹配
         // 使用 LEFT JOIN + IS NULL 代替 NOT EXISTS，性能更好
         let rows = sqlx::query(
             r#"
             SELECT c.id, c.repository_id, c.oid, c.branch,
                    c.author_name, c.author_email, c.author_time,
                    c.committer_name, c.committer_email, c.committer_time,
-                   c.summary, c.message, c.parent_oids, c.created_at
+                   c.summary, c.message, c.parent_oids, c.created_at,
+                   c.files_changed, c.insertions, c.deletions,
+                   c.raw_author_name, c.raw_author_email, c.tree_oid
             FROM commits c
             LEFT JOIN commits new ON 
                 new.repository_id = c.repository_id
                 AND new.branch = ?
+                AND new.author_email = c.author_email
                 AND new.author_time = c.author_time
                 AND new.summary = c.summary
             WHERE c.repository_id = ? 
@@ -328,22 +566,300 @@ impl CommitPort for SqliteCommitRepository {
 
         Ok(rows
             .into_iter()
-            .map(|r| Commit {
-                id: r.get("id"),
-                repository_id: r.get("repository_id"),
-                oid: r.get("oid"),
-                branch: r.get("branch"),
-                author_name: r.get("author_name"),
-                author_email: r.get("author_email"),
-                author_time: DateTime::from_timestamp(r.get("author_time"), 0).unwrap(),
-                committer_name: r.get("committer_name"),
-                committer_email: r.get("committer_email"),
-                committer_time: DateTime::from_timestamp(r.get("committer_time"), 0).unwrap(),
-                summary: r.get("summary"),
-                message: r.get("message"),
-                parent_oids: r.get("parent_oids"),
-                created_at: DateTime::from_timestamp(r.get("created_at"), 0).unwrap(),
-            })
+            .map(|r| commit_from_row(&r))
             .collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ports::commit::{CommitSort, SortDirection};
+    use chrono::DateTime;
+    use crate::infrastructure::sqlite::run_migrations;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn memory_pool() -> SqlitePool {
+        // 内存库每个连接独立，只能使用单连接
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO repositories (id, name, path, created_at, updated_at) VALUES (1, 'synthetic', '/tmp/synthetic', 0, 0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    fn synthetic_commit(i: i64, time: i64) -> Commit {
+        let time = DateTime::from_timestamp(time, 0).unwrap();
+        Commit::new(
+            1,
+            format!("{:040x}", i),
+            "origin/main".to_string(),
+            "dev".to_string(),
+            "dev@example.com".to_string(),
+            time,
+            "dev".to_string(),
+            "dev@example.com".to_string(),
+            time,
+            format!("commit {}", i),
+        )
+    }
+
+    async fn collect_keyset(store: &SqliteCommitRepository, branch: Option<&str>, limit: i64) -> Vec<i64> {
+        let mut ids = Vec::new();
+        let mut cursor: Option<(i64, i64)> = None;
+        loop {
+            let page = store
+                .list_by_repository_keyset(1, &CommitFilter::branch(branch), cursor.map(|c| c.0), cursor.map(|c| c.1), limit)
+                .await
+                .unwrap();
+            let Some(last) = page.last() else { break };
+            cursor = Some((last.committer_time.timestamp(), last.id));
+            ids.extend(page.iter().map(|c| c.id));
+        }
+        ids
+    }
+
+    #[tokio::test]
+    async fn test_stream_spans_batches() {
+        let store = SqliteCommitRepository::new(memory_pool().await);
+        let total = STREAM_BATCH_SIZE * 2 + 1;
+        let commits: Vec<Commit> = (0..total).map(|i| synthetic_commit(i, 1_600_000_000 + i)).collect();
+        store.bulk_insert(&commits).await.unwrap();
+
+        let ids: Vec<i64> = store
+            .stream_by_repository(1)
+            .map(|c| c.unwrap().id)
+            .collect()
+            .await;
+        assert_eq!(ids.len() as i64, total);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[tokio::test]
+    async fn test_keyset_matches_offset_pagination() {
+        let store = SqliteCommitRepository::new(memory_pool().await);
+
+        // 提交时间唯一且与插入顺序打乱，offset 版本的排序才是确定的；
+        // 作者时间按插入顺序递增，与提交时间的顺序不同
+        let commits: Vec<Commit> = (0..10_000)
+            .map(|i| {
+                let mut commit = synthetic_commit(i, 1_600_000_000 + (i * 7919) % 10_000);
+                commit.author_time = DateTime::from_timestamp(1_600_000_000 + i, 0).unwrap();
+                commit
+            })
+            .collect();
+        assert_eq!(store.bulk_insert(&commits).await.unwrap(), 10_000);
+
+        let limit = 50;
+        let mut offset_ids = Vec::new();
+        let mut offset = 0;
+        loop {
+            let page = store
+                .list_by_repository(1, &CommitFilter::branch(Some("origin/main")), CommitOrder::KEYSET, limit, offset)
+                .await
+                .unwrap();
+            if page.is_empty() {
+                break;
+            }
+            offset += page.len() as i64;
+            offset_ids.extend(page.iter().map(|c| c.id));
+        }
+
+        assert_eq!(offset_ids.len(), 10_000);
+        assert_eq!(collect_keyset(&store, Some("origin/main"), limit).await, offset_ids);
+        assert_eq!(collect_keyset(&store, None, limit).await, offset_ids);
+    }
+
+    #[tokio::test]
+    async fn test_repository_stats() {
+        let store = SqliteCommitRepository::new(memory_pool().await);
+        let day = 1_700_000_000; // 2023-11-14T22:13:20Z
+
+        let mut commits = vec![
+            synthetic_commit(1, day),
+            synthetic_commit(2, day + 60),
+            synthetic_commit(3, day + 86_400),
+        ];
+        // 同一作者邮箱大小写不同
+        commits[1].author_email = "Dev@Example.com".to_string();
+        let mut other = synthetic_commit(4, day + 2 * 86_400);
+        other.author_email = "other@example.com".to_string();
+        commits.push(other);
+        // 同一提交出现在第二个分支上
+        let mut on_dev = synthetic_commit(1, day);
+        on_dev.branch = "origin/dev".to_string();
+        commits.push(on_dev);
+        store.bulk_insert(&commits).await.unwrap();
+
+        let totals = store.commit_totals(1).await.unwrap();
+        assert_eq!(totals.total_commits, 4);
+        assert_eq!(totals.first_commit_time, Some(day));
+        assert_eq!(totals.last_commit_time, Some(day + 2 * 86_400));
+
+        let authors = store.author_stats(1, 10).await.unwrap();
+        assert_eq!(authors.len(), 2);
+        assert_eq!(authors[0].author_email, "dev@example.com");
+        assert_eq!(authors[0].commits, 3);
+        assert_eq!(store.author_stats(1, 1).await.unwrap().len(), 1);
+
+        let activity = store.activity_by_day(1).await.unwrap();
+        let days: Vec<(&str, i64)> = activity.iter().map(|a| (a.day.as_str(), a.commits)).collect();
+        assert_eq!(days, vec![("2023-11-14", 2), ("2023-11-15", 1), ("2023-11-16", 1)]);
+
+        let branches = store.branch_commit_counts(1).await.unwrap();
+        let branches: Vec<(&str, i64)> = branches.iter().map(|b| (b.branch.as_str(), b.commits)).collect();
+        assert_eq!(branches, vec![("origin/dev", 1), ("origin/main", 4)]);
+    }
+
+    #[tokio::test]
+    async fn test_existing_oids() {
+        let store = SqliteCommitRepository::new(memory_pool().await);
+        let commits: Vec<Commit> = (0..600).map(|i| synthetic_commit(i, 1_600_000_000 + i)).collect();
+        store.bulk_insert(&commits).await.unwrap();
+
+        // 超过单批大小，且混有未索引的 OID
+        let mut oids: Vec<String> = commits.iter().map(|c| c.oid.clone()).collect();
+        oids.push(format!("{:040x}", 10_000));
+        let existing = store.existing_oids(1, "origin/main", &oids).await.unwrap();
+        assert_eq!(existing.len(), 600);
+        assert!(!existing.contains(&format!("{:040x}", 10_000)));
+        assert!(store.existing_oids(1, "origin/dev", &oids).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_author_filter() {
+        let store = SqliteCommitRepository::new(memory_pool().await);
+
+        let mut commits: Vec<Commit> = (0..6).map(|i| synthetic_commit(i, 1_600_000_000 + i)).collect();
+        commits[1].author_name = "Alice Liddell".to_string();
+        commits[2].author_email = "ALICE@example.com".to_string();
+        commits[3].author_name = "100%_match".to_string();
+        commits[4].branch = "origin/dev".to_string();
+        commits[4].author_name = "alice".to_string();
+        store.bulk_insert(&commits).await.unwrap();
+
+        let filter = CommitFilter { author: Some("alice"), ..CommitFilter::default() };
+        assert_eq!(store.count_by_repository(1, &filter).await.unwrap(), 3);
+        let on_main = CommitFilter { branch: Some("origin/main"), ..filter };
+        let ids: Vec<String> = store
+            .list_by_repository(1, &on_main, CommitOrder::default(), 10, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.oid)
+            .collect();
+        assert_eq!(ids, vec![format!("{:040x}", 2), format!("{:040x}", 1)]);
+        let oldest_first = CommitOrder { sort: CommitSort::CommitterTime, direction: SortDirection::Asc };
+        let ids: Vec<String> = store
+            .list_by_repository(1, &on_main, oldest_first, 10, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.oid)
+            .collect();
+        assert_eq!(ids, vec![format!("{:040x}", 1), format!("{:040x}", 2)]);
+        let keyset = store.list_by_repository_keyset(1, &on_main, None, None, 10).await.unwrap();
+        assert_eq!(keyset.len(), 2);
+
+        // 通配符按字面匹配
+        let literal = CommitFilter { author: Some("%_"), ..CommitFilter::default() };
+        assert_eq!(store.count_by_repository(1, &literal).await.unwrap(), 1);
+
+        // 时间范围为左闭右开，可与其他条件组合
+        let window = CommitFilter { since: Some(1_600_000_001), until: Some(1_600_000_004), ..CommitFilter::default() };
+        assert_eq!(store.count_by_repository(1, &window).await.unwrap(), 3);
+        let window = CommitFilter { author: Some("alice"), ..window };
+        assert_eq!(store.count_by_repository(1, &window).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_keyset_handles_equal_committer_time() {
+        let store = SqliteCommitRepository::new(memory_pool().await);
+
+        // 大量提交共享同一时间戳，跨页时既不能重复也不能遗漏
+        let commits: Vec<Commit> = (0..120)
+            .map(|i| synthetic_commit(i, 1_600_000_000 + i / 40))
+            .collect();
+        store.bulk_insert(&commits).await.unwrap();
+
+        let ids = collect_keyset(&store, None, 7).await;
+        let mut unique = ids.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(ids.len(), 120);
+        assert_eq!(unique.len(), 120);
+    }
+
+    #[tokio::test]
+    async fn test_find_diff_commits_duplicate_summaries() {
+        let store = SqliteCommitRepository::new(memory_pool().await);
+        let on_branch = |i: i64, time: i64, branch: &str, summary: &str| {
+            let mut commit = synthetic_commit(i, time);
+            commit.branch = branch.to_string();
+            commit.summary = summary.to_string();
+            commit
+        };
+
+        // dev 上同一作者的两个 "fix typo" 提交
+        let picked = on_branch(1, 1_600_000_000, "origin/dev", "fix typo");
+        let unpicked = on_branch(2, 1_600_000_500, "origin/dev", "fix typo");
+        // main 上是第一个的 cherry-pick：作者信息相同，committer_time 与 OID 不同
+        let mut cherry_pick = on_branch(3, 1_600_000_000, "origin/main", "fix typo");
+        cherry_pick.committer_time = DateTime::from_timestamp(1_600_009_000, 0).unwrap();
+        // 另一作者在同一时间提交了同名提交，不能据此隐藏 dev 上的提交
+        let mut other_author = on_branch(4, 1_600_000_500, "origin/main", "fix typo");
+        other_author.author_email = "other@example.com".to_string();
+        store.bulk_insert(&[picked, unpicked, cherry_pick, other_author]).await.unwrap();
+
+        let diff = store.find_diff_commits(1, "origin/dev", "origin/main", 100).await.unwrap();
+        let oids: Vec<String> = diff.iter().map(|c| c.oid.clone()).collect();
+        assert_eq!(oids, vec![format!("{:040x}", 2)]);
+    }
+
+    #[tokio::test]
+    async fn test_find_by_oid_prefix() {
+        let store = SqliteCommitRepository::new(memory_pool().await);
+        let mut commits = vec![synthetic_commit(1, 1_700_000_000), synthetic_commit(2, 1_700_000_060)];
+        commits[0].oid = format!("abcd1{}", "0".repeat(35));
+        commits[1].oid = format!("abcd2{}", "0".repeat(35));
+        // 同一提交出现在两个分支上不算歧义
+        let mut on_dev = commits[0].clone();
+        on_dev.branch = "origin/dev".to_string();
+        commits.push(on_dev);
+        store.bulk_insert(&commits).await.unwrap();
+
+        let found = store.find_by_oid_prefix(1, "ABCD1").await.unwrap().unwrap();
+        assert_eq!(found.oid, commits[0].oid);
+        assert!(store.find_by_oid_prefix(1, "abce").await.unwrap().is_none());
+        assert!(matches!(store.find_by_oid_prefix(1, "abcd").await, Err(GitxError::AmbiguousOid(_))));
+        assert!(matches!(store.find_by_oid_prefix(1, "abc").await, Err(GitxError::InvalidOid(_))));
+        // 通配符按字面匹配
+        assert!(store.find_by_oid_prefix(1, "ab%_").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_find_by_oids() {
+        let store = SqliteCommitRepository::new(memory_pool().await);
+        let mut commits = vec![synthetic_commit(1, 1_700_000_000), synthetic_commit(2, 1_700_000_060)];
+        let mut on_dev = commits[0].clone();
+        on_dev.branch = "origin/dev".to_string();
+        commits.push(on_dev);
+        store.bulk_insert(&commits).await.unwrap();
+
+        let oids = vec![commits[0].oid.clone(), commits[1].oid.clone(), format!("{:040x}", 9)];
+        let found = store.find_by_oids(1, &oids).await.unwrap();
+        // 多个分支上的同一提交只返回一次，缺失的 OID 不报错
+        let mut found: Vec<String> = found.into_iter().map(|c| c.oid).collect();
+        found.sort();
+        assert_eq!(found, vec![commits[0].oid.clone(), commits[1].oid.clone()]);
+        assert!(store.find_by_oids(1, &[]).await.unwrap().is_empty());
+    }
+}
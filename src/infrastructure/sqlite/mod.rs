@@ -2,36 +2,168 @@ pub mod repository_repo;
 pub mod commit_repo;
 pub mod branch_repo;
 
-use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
-use std::path::Path;
+use chrono::{DateTime, Utc};
+use sqlx::pool::PoolConnection;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
+use sqlx::{Sqlite, SqlitePool};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tracing::warn;
+use crate::shared::config::DatabaseConfig;
 use crate::shared::result::Result;
 use crate::shared::error::GitxError;
 
-/// 初始化 SQLite 数据库连接池
-pub async fn create_pool(database_path: &Path, max_connections: u32) -> Result<SqlitePool> {
+/// 初始化 SQLite 数据库连接池，每个连接按配置设置 pragma
+pub async fn create_pool(config: &DatabaseConfig) -> Result<SqlitePool> {
+    let database_path = &config.sqlite_path;
     // 确保数据库文件的父目录存在
     if let Some(parent) = database_path.parent() {
         if !parent.as_os_str().is_empty() && !parent.exists() {
             std::fs::create_dir_all(parent)?;
         }
     }
-    
-    // SQLite连接字符串，添加create_if_missing选项
-    let url = format!("sqlite://{}?mode=rwc", database_path.display());
-    
-    let pool = SqlitePoolOptions::new()
-        .max_connections(max_connections)
-        .connect(&url)
-        .await?;
+
+    let journal_mode = SqliteJournalMode::from_str(&config.journal_mode)
+        .map_err(|_| GitxError::Config(format!("invalid database.journal_mode: {:?}", config.journal_mode)))?;
+    let synchronous = SqliteSynchronous::from_str(&config.synchronous)
+        .map_err(|_| GitxError::Config(format!("invalid database.synchronous: {:?}", config.synchronous)))?;
+
+    let options = SqliteConnectOptions::new()
+        .filename(database_path)
+        .create_if_missing(true)
+        .journal_mode(journal_mode)
+        .synchronous(synchronous)
+        .busy_timeout(Duration::from_millis(config.busy_timeout_ms))
+        // 删除仓库依赖 ON DELETE CASCADE 清理提交、分支与标签，外键约束始终开启
+        .foreign_keys(true);
+
+    let mut pool_options = SqlitePoolOptions::new().max_connections(config.max_connections);
+    if config.statement_timeout_ms > 0 {
+        // 每次借出连接时重新计时，超过截止时间后进度回调中断正在执行的语句
+        let timeout = Duration::from_millis(config.statement_timeout_ms);
+        pool_options = pool_options
+            .after_connect(move |conn, _| Box::pin(arm_statement_timeout(conn, timeout)))
+            .before_acquire(move |conn, _| Box::pin(async move {
+                arm_statement_timeout(conn, timeout).await?;
+                Ok(true)
+            }));
+    }
+
+    let pool = pool_options.connect_with(options).await?;
 
     Ok(pool)
 }
 
+/// 进度回调的调用间隔（SQLite 虚拟机指令数）
+const PROGRESS_HANDLER_OPS: i32 = 1000;
+
+/// 为连接设置截止时间，之后执行的语句超时即被中断（`SQLITE_INTERRUPT`）
+async fn arm_statement_timeout(conn: &mut SqliteConnection, timeout: Duration) -> sqlx::Result<()> {
+    let deadline = Instant::now() + timeout;
+    conn.lock_handle()
+        .await?
+        .set_progress_handler(PROGRESS_HANDLER_OPS, move || Instant::now() < deadline);
+    Ok(())
+}
+
+/// 借出不受 `statement_timeout_ms` 限制的连接，用于迁移、VACUUM 等耗时的维护操作
+pub(crate) async fn acquire_unlimited(pool: &SqlitePool) -> Result<PoolConnection<Sqlite>> {
+    let mut conn = pool.acquire().await?;
+    // 下次借出时 before_acquire 会重新设置
+    conn.lock_handle().await?.remove_progress_handler();
+    Ok(conn)
+}
+
 /// 运行数据库迁移
 pub async fn run_migrations(pool: &SqlitePool) -> Result<()> {
+    let mut conn = acquire_unlimited(pool).await?;
     sqlx::migrate!("./migrations")
-        .run(pool)
+        .run(&mut *conn)
         .await
         .map_err(|e| GitxError::Internal(format!("Migration failed: {}", e)))?;
     Ok(())
 }
+
+/// 让 SQLite 根据查询统计更新索引分析信息（开销很小，可频繁执行）
+pub async fn optimize(pool: &SqlitePool) -> Result<()> {
+    sqlx::query("PRAGMA optimize").execute(pool).await?;
+    Ok(())
+}
+
+/// 重建数据库文件回收空闲页；执行期间持有整个数据库的写锁
+pub async fn vacuum(pool: &SqlitePool) -> Result<()> {
+    let mut conn = acquire_unlimited(pool).await?;
+    sqlx::query("VACUUM").execute(&mut *conn).await?;
+    Ok(())
+}
+
+/// 秒级时间戳转 `DateTime<Utc>`，超出范围时回退到 Unix 纪元并记录警告
+pub(crate) fn ts_to_datetime(ts: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(ts, 0).unwrap_or_else(|| {
+        warn!("Timestamp {} out of range, falling back to Unix epoch", ts);
+        DateTime::UNIX_EPOCH
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ts_to_datetime_out_of_range() {
+        assert_eq!(ts_to_datetime(1_600_000_000).timestamp(), 1_600_000_000);
+        assert_eq!(ts_to_datetime(i64::MAX), DateTime::UNIX_EPOCH);
+        assert_eq!(ts_to_datetime(i64::MIN), DateTime::UNIX_EPOCH);
+    }
+
+    #[tokio::test]
+    async fn test_create_pool_rejects_invalid_pragma() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = DatabaseConfig {
+            sqlite_path: dir.path().join("gitx.db"),
+            journal_mode: "bogus".to_string(),
+            ..DatabaseConfig::default()
+        };
+        assert!(matches!(create_pool(&config).await, Err(GitxError::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn test_optimize_and_vacuum() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = DatabaseConfig {
+            sqlite_path: dir.path().join("gitx.db"),
+            max_connections: 2,
+            ..DatabaseConfig::default()
+        };
+        let pool = create_pool(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode").fetch_one(&pool).await.unwrap();
+        assert_eq!(journal_mode, "wal");
+
+        optimize(&pool).await.unwrap();
+        vacuum(&pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_statement_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = DatabaseConfig {
+            sqlite_path: dir.path().join("gitx.db"),
+            max_connections: 1,
+            statement_timeout_ms: 100,
+            ..DatabaseConfig::default()
+        };
+        let pool = create_pool(&config).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let endless = "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c) SELECT count(*) FROM c";
+        let err = sqlx::query_scalar::<_, i64>(endless).fetch_one(&pool).await.unwrap_err();
+        assert_eq!(GitxError::from(err).code(), "query_timeout");
+
+        // 同一连接再次借出时重新计时
+        let one: i64 = sqlx::query_scalar("SELECT 1").fetch_one(&pool).await.unwrap();
+        assert_eq!(one, 1);
+        vacuum(&pool).await.unwrap();
+    }
+}
@@ -1,9 +1,11 @@
 use async_trait::async_trait;
-use sqlx::{SqlitePool, Row};
-use chrono::{DateTime, Utc};
-use crate::domain::entities::Repository;
+use sqlx::{Connection, SqlitePool, Row};
+use sqlx::sqlite::SqliteRow;
+use chrono::Utc;
+use crate::domain::entities::{IndexProgress, IndexState, Repository, RepositoryStatus};
 use crate::ports::repository::RepositoryPort;
 use crate::shared::result::Result;
+use super::ts_to_datetime;
 
 /// SQLite 仓库仓储实现
 pub struct SqliteRepositoryRepository {
@@ -16,13 +18,31 @@ impl SqliteRepositoryRepository {
     }
 }
 
+/// 将查询 `repositories` 全部列的结果行转换为实体
+fn repository_from_row(r: &SqliteRow) -> Repository {
+    Repository {
+        id: r.get("id"),
+        name: r.get("name"),
+        path: r.get("path"),
+        description: r.get("description"),
+        default_branch: r.get("default_branch"),
+        last_synced_at: r.get::<Option<i64>, _>("last_synced_at").map(ts_to_datetime),
+        created_at: ts_to_datetime(r.get("created_at")),
+        updated_at: ts_to_datetime(r.get("updated_at")),
+        status: RepositoryStatus::parse(r.get("status")),
+        missing_since: r.get::<Option<i64>, _>("missing_since").map(ts_to_datetime),
+        fingerprint: r.get("fingerprint"),
+    }
+}
+
 #[async_trait]
 impl RepositoryPort for SqliteRepositoryRepository {
     async fn find_by_id(&self, id: i64) -> Result<Option<Repository>> {
         let row = sqlx::query(
             r#"
             SELECT id, name, path, description, default_branch,
-                   last_synced_at, created_at, updated_at
+                   last_synced_at, created_at, updated_at,
+                   status, missing_since, fingerprint
             FROM repositories
             WHERE id = ?
             "#,
@@ -31,24 +51,15 @@ impl RepositoryPort for SqliteRepositoryRepository {
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|r| Repository {
-            id: r.get("id"),
-            name: r.get("name"),
-            path: r.get("path"),
-            description: r.get("description"),
-            default_branch: r.get("default_branch"),
-            last_synced_at: r.get::<Option<i64>, _>("last_synced_at")
-                .map(|ts| DateTime::from_timestamp(ts, 0).unwrap()),
-            created_at: DateTime::from_timestamp(r.get("created_at"), 0).unwrap(),
-            updated_at: DateTime::from_timestamp(r.get("updated_at"), 0).unwrap(),
-        }))
+        Ok(row.map(|r| repository_from_row(&r)))
     }
 
     async fn find_by_path(&self, path: &str) -> Result<Option<Repository>> {
         let row = sqlx::query(
             r#"
             SELECT id, name, path, description, default_branch,
-                   last_synced_at, created_at, updated_at
+                   last_synced_at, created_at, updated_at,
+                   status, missing_since, fingerprint
             FROM repositories
             WHERE path = ?
             "#,
@@ -57,24 +68,15 @@ impl RepositoryPort for SqliteRepositoryRepository {
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|r| Repository {
-            id: r.get("id"),
-            name: r.get("name"),
-            path: r.get("path"),
-            description: r.get("description"),
-            default_branch: r.get("default_branch"),
-            last_synced_at: r.get::<Option<i64>, _>("last_synced_at")
-                .map(|ts| DateTime::from_timestamp(ts, 0).unwrap()),
-            created_at: DateTime::from_timestamp(r.get("created_at"), 0).unwrap(),
-            updated_at: DateTime::from_timestamp(r.get("updated_at"), 0).unwrap(),
-        }))
+        Ok(row.map(|r| repository_from_row(&r)))
     }
 
     async fn find_by_name(&self, name: &str) -> Result<Option<Repository>> {
         let row = sqlx::query(
             r#"
             SELECT id, name, path, description, default_branch,
-                   last_synced_at, created_at, updated_at
+                   last_synced_at, created_at, updated_at,
+                   status, missing_since, fingerprint
             FROM repositories
             WHERE name = ?
             "#,
@@ -83,24 +85,15 @@ impl RepositoryPort for SqliteRepositoryRepository {
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|r| Repository {
-            id: r.get("id"),
-            name: r.get("name"),
-            path: r.get("path"),
-            description: r.get("description"),
-            default_branch: r.get("default_branch"),
-            last_synced_at: r.get::<Option<i64>, _>("last_synced_at")
-                .map(|ts| DateTime::from_timestamp(ts, 0).unwrap()),
-            created_at: DateTime::from_timestamp(r.get("created_at"), 0).unwrap(),
-            updated_at: DateTime::from_timestamp(r.get("updated_at"), 0).unwrap(),
-        }))
+        Ok(row.map(|r| repository_from_row(&r)))
     }
 
     async fn list_all(&self) -> Result<Vec<Repository>> {
         let rows = sqlx::query(
             r#"
             SELECT id, name, path, description, default_branch,
-                   last_synced_at, created_at, updated_at
+                   last_synced_at, created_at, updated_at,
+                   status, missing_since, fingerprint
             FROM repositories
             ORDER BY name ASC
             "#,
@@ -110,20 +103,52 @@ impl RepositoryPort for SqliteRepositoryRepository {
 
         Ok(rows
             .into_iter()
-            .map(|r| Repository {
-                id: r.get("id"),
-                name: r.get("name"),
-                path: r.get("path"),
-                description: r.get("description"),
-                default_branch: r.get("default_branch"),
-                last_synced_at: r.get::<Option<i64>, _>("last_synced_at")
-                    .map(|ts| DateTime::from_timestamp(ts, 0).unwrap()),
-                created_at: DateTime::from_timestamp(r.get("created_at"), 0).unwrap(),
-                updated_at: DateTime::from_timestamp(r.get("updated_at"), 0).unwrap(),
-            })
+            .map(|r| repository_from_row(&r))
             .collect())
     }
 
+    async fn list_paged(&self, limit: i64, offset: i64, name_filter: Option<&str>) -> Result<(Vec<Repository>, i64)> {
+        // LIKE 对 ASCII 不区分大小写；转义通配符，按字面子串匹配
+        let pattern = name_filter.map(|f| {
+            let escaped = f.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+            format!("%{}%", escaped)
+        });
+
+        let total: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM repositories
+            WHERE ?1 IS NULL OR name LIKE ?1 ESCAPE '\'
+            "#,
+        )
+        .bind(&pattern)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, path, description, default_branch,
+                   last_synced_at, created_at, updated_at,
+                   status, missing_since, fingerprint
+            FROM repositories
+            WHERE ?1 IS NULL OR name LIKE ?1 ESCAPE '\'
+            ORDER BY name ASC
+            LIMIT ?2 OFFSET ?3
+            "#,
+        )
+        .bind(&pattern)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let repos = rows
+            .into_iter()
+            .map(|r| repository_from_row(&r))
+            .collect();
+
+        Ok((repos, total))
+    }
+
     async fn save(&self, repo: &Repository) -> Result<i64> {
         let created_ts = repo.created_at.timestamp();
         let updated_ts = repo.updated_at.timestamp();
@@ -131,14 +156,15 @@ impl RepositoryPort for SqliteRepositoryRepository {
 
         let result = sqlx::query(
             r#"
-            INSERT INTO repositories (name, path, description, default_branch, last_synced_at, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO repositories (name, path, description, default_branch, last_synced_at, created_at, updated_at, fingerprint)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(path) DO UPDATE SET
                 name = excluded.name,
                 description = excluded.description,
                 default_branch = excluded.default_branch,
                 last_synced_at = excluded.last_synced_at,
-                updated_at = excluded.updated_at
+                updated_at = excluded.updated_at,
+                fingerprint = COALESCE(excluded.fingerprint, repositories.fingerprint)
             RETURNING id
             "#,
         )
@@ -149,6 +175,7 @@ impl RepositoryPort for SqliteRepositoryRepository {
         .bind(last_synced_ts)
         .bind(created_ts)
         .bind(updated_ts)
+        .bind(&repo.fingerprint)
         .fetch_one(&self.pool)
         .await?;
 
@@ -156,13 +183,31 @@ impl RepositoryPort for SqliteRepositoryRepository {
     }
 
     async fn delete(&self, id: i64) -> Result<()> {
+        // 级联删除提交与分支，耗时较长，不受语句超时限制
+        let mut conn = super::acquire_unlimited(&self.pool).await?;
         sqlx::query("DELETE FROM repositories WHERE id = ?")
             .bind(id)
-            .execute(&self.pool)
+            .execute(&mut *conn)
             .await?;
         Ok(())
     }
 
+    async fn clear_index(&self, id: i64) -> Result<()> {
+        // 大仓库的删除耗时较长，不受语句超时限制；中途失败时两张表都回滚
+        let mut conn = super::acquire_unlimited(&self.pool).await?;
+        let mut tx = conn.begin().await?;
+        sqlx::query("DELETE FROM commits WHERE repository_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM branches WHERE repository_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
     async fn update_sync_time(&self, id: i64) -> Result<()> {
         let now = Utc::now().timestamp();
         sqlx::query("UPDATE repositories SET last_synced_at = ?, updated_at = ? WHERE id = ?")
@@ -174,6 +219,15 @@ impl RepositoryPort for SqliteRepositoryRepository {
         Ok(())
     }
 
+    async fn update_default_branch(&self, id: i64, branch: &str) -> Result<()> {
+        sqlx::query("UPDATE repositories SET default_branch = ? WHERE id = ?")
+            .bind(branch)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     async fn exists_by_path(&self, path: &str) -> Result<bool> {
         let row = sqlx::query("SELECT 1 FROM repositories WHERE path = ?")
             .bind(path)
@@ -181,4 +235,213 @@ impl RepositoryPort for SqliteRepositoryRepository {
             .await?;
         Ok(row.is_some())
     }
+
+    async fn find_by_fingerprint(&self, fingerprint: &str) -> Result<Vec<Repository>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, path, description, default_branch,
+                   last_synced_at, created_at, updated_at,
+                   status, missing_since, fingerprint
+            FROM repositories
+            WHERE fingerprint = ?
+            ORDER BY id
+            "#,
+        )
+        .bind(fingerprint)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| repository_from_row(&r))
+            .collect())
+    }
+
+    async fn set_status(&self, id: i64, status: RepositoryStatus) -> Result<()> {
+        let now = Utc::now().timestamp();
+        // 已是 missing 时保留首次发现的时间
+        sqlx::query(
+            r#"
+            UPDATE repositories
+            SET status = ?1,
+                missing_since = CASE WHEN ?1 = 'missing' THEN COALESCE(missing_since, ?2) END,
+                updated_at = ?2
+            WHERE id = ?3
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(now)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn relocate(&self, id: i64, name: &str, path: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE repositories
+            SET name = ?, path = ?, status = ?, missing_since = NULL, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(name)
+        .bind(path)
+        .bind(RepositoryStatus::Active.as_str())
+        .bind(Utc::now().timestamp())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn index_progress(&self, id: i64) -> Result<Option<IndexProgress>> {
+        let row = sqlx::query(
+            r#"
+            SELECT repository_id, state, branches_total, branches_done, last_branch, last_tip_oid,
+                   commits_indexed, error, started_at, updated_at, finished_at
+            FROM index_progress
+            WHERE repository_id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| IndexProgress {
+            repository_id: r.get("repository_id"),
+            state: IndexState::parse(r.get("state")),
+            branches_total: r.get("branches_total"),
+            branches_done: r.get("branches_done"),
+            last_branch: r.get("last_branch"),
+            last_tip_oid: r.get("last_tip_oid"),
+            commits_indexed: r.get("commits_indexed"),
+            error: r.get("error"),
+            started_at: ts_to_datetime(r.get("started_at")),
+            updated_at: ts_to_datetime(r.get("updated_at")),
+            finished_at: r.get::<Option<i64>, _>("finished_at").map(ts_to_datetime),
+        }))
+    }
+
+    async fn save_index_progress(&self, progress: &IndexProgress) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO index_progress (
+                repository_id, state, branches_total, branches_done, last_branch, last_tip_oid,
+                commits_indexed, error, started_at, updated_at, finished_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(repository_id) DO UPDATE SET
+                state = excluded.state,
+                branches_total = excluded.branches_total,
+                branches_done = excluded.branches_done,
+                last_branch = excluded.last_branch,
+                last_tip_oid = excluded.last_tip_oid,
+                commits_indexed = excluded.commits_indexed,
+                error = excluded.error,
+                started_at = excluded.started_at,
+                updated_at = excluded.updated_at,
+                finished_at = excluded.finished_at
+            "#,
+        )
+        .bind(progress.repository_id)
+        .bind(progress.state.as_str())
+        .bind(progress.branches_total)
+        .bind(progress.branches_done)
+        .bind(&progress.last_branch)
+        .bind(&progress.last_tip_oid)
+        .bind(progress.commits_indexed)
+        .bind(&progress.error)
+        .bind(progress.started_at.timestamp())
+        .bind(progress.updated_at.timestamp())
+        .bind(progress.finished_at.map(|t| t.timestamp()))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn mark_index_interrupted(&self) -> Result<u64> {
+        let result = sqlx::query("UPDATE index_progress SET state = ? WHERE state = ?")
+            .bind(IndexState::Interrupted.as_str())
+            .bind(IndexState::Running.as_str())
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::sqlite::run_migrations;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn test_list_paged_filters_by_name() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        let store = SqliteRepositoryRepository::new(pool);
+        for name in ["alpha", "Beta", "gamma_beta", "gammaXbeta", "delta"] {
+            store.save(&Repository::new(name.to_string(), format!("/repos/{}", name))).await.unwrap();
+        }
+
+        let names = |repos: Vec<Repository>| repos.into_iter().map(|r| r.name).collect::<Vec<_>>();
+
+        let (repos, total) = store.list_paged(2, 0, None).await.unwrap();
+        assert_eq!((names(repos), total), (vec!["Beta".to_string(), "alpha".to_string()], 5));
+        let (repos, _) = store.list_paged(2, 4, None).await.unwrap();
+        assert_eq!(names(repos), vec!["gamma_beta"]);
+
+        // 不区分大小写
+        let (repos, total) = store.list_paged(10, 0, Some("BETA")).await.unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(names(repos), vec!["Beta", "gammaXbeta", "gamma_beta"]);
+
+        // `_` 按字面匹配，不作为通配符
+        let (repos, total) = store.list_paged(10, 0, Some("a_b")).await.unwrap();
+        assert_eq!((names(repos), total), (vec!["gamma_beta".to_string()], 1));
+    }
+
+    #[tokio::test]
+    async fn test_delete_cascades_to_child_rows() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        let store = SqliteRepositoryRepository::new(pool.clone());
+        let id = store.save(&Repository::new("alpha".to_string(), "/repos/alpha".to_string())).await.unwrap();
+        let other = store.save(&Repository::new("beta".to_string(), "/repos/beta".to_string())).await.unwrap();
+
+        for repository_id in [id, other] {
+            sqlx::query(
+                "INSERT INTO commits (repository_id, oid, branch, author_name, author_email, author_time, \
+                 committer_name, committer_email, committer_time, summary, created_at) \
+                 VALUES (?, 'abc', 'main', 'a', 'a@x', 0, 'a', 'a@x', 0, 's', 0)",
+            )
+            .bind(repository_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query("INSERT INTO branches (repository_id, name, target_oid, updated_at) VALUES (?, 'main', 'abc', 0)")
+                .bind(repository_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        store.delete(id).await.unwrap();
+
+        for table in ["commits", "branches"] {
+            let remaining: Vec<i64> = sqlx::query_scalar(&format!("SELECT repository_id FROM {}", table))
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+            assert_eq!(remaining, vec![other], "{}", table);
+        }
+    }
 }
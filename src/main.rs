@@ -2,11 +2,13 @@ use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use axum::http::{HeaderValue, Method};
-use tower_http::cors::CorsLayer;
+use tower_http::compression::predicate::{DefaultPredicate, NotForContentType, Predicate};
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
 use tower_http::services::ServeDir;
-use tracing::info;
+use tracing::{debug, info, warn};
 
 mod shared;
 mod domain;
@@ -15,20 +17,25 @@ mod infrastructure;
 mod services;
 mod presentation;
 
-use shared::config::Config;
+use shared::config::{Config, ConfigHandle, ConfigOverrides};
 use shared::result::Result;
 use infrastructure::git::Git2Client;
 use infrastructure::sqlite::repository_repo::SqliteRepositoryRepository;
 use infrastructure::sqlite::commit_repo::SqliteCommitRepository;
 use infrastructure::sqlite::branch_repo::SqliteBranchRepository;
-use infrastructure::cache::MokaCache;
+use infrastructure::cache::{DiskCache, MokaCache, TieredCache};
+use ports::repository::RepositoryPort;
+use presentation::rate_limit::RateLimiter;
+use presentation::recent::RecentCommits;
 use presentation::routes::AppContext;
 
+/// 构建版本号（同时用于 clap 和健康检查）
+pub const VERSION: &str = "0.2.0";
 
 #[derive(Parser, Debug)]
 #[clap(name = "Gitx")]
 #[clap(author = "xiangyuan@gmail.com")]
-#[clap(version = "0.2.0")]
+#[clap(version = VERSION)]
 #[clap(about = "Git repository indexer and browser")]
 pub struct Args {
     /// The database store directory (SQLite database path)
@@ -38,39 +45,74 @@ pub struct Args {
     /// Server bind address
     #[clap(short, long)]
     bind_address: Option<SocketAddr>,
+
+    /// Directory of static assets served under /statics (overrides server.static_dir)
+    #[clap(long, value_parser, value_name = "DIR")]
+    static_dir: Option<PathBuf>,
     
-    /// Base path to scan for git repositories (can be a single repo or directory containing repos)
+    /// Base path to scan for git repositories (can be a single repo or directory containing repos, searched up to 3 levels deep)
     #[clap(short = 'p', long = "path", value_parser, value_name = "PATH")]
     git_base_path: Option<PathBuf>,
+
+    /// Config file path (defaults to ./config.toml, $XDG_CONFIG_HOME/gitx/config.toml, /etc/gitx/config.toml in that order; `.json` files are parsed as JSON)
+    #[clap(short = 'c', long = "config", value_parser, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Subcommand to run (starts the server when omitted)
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Start the web server and indexer (default)
+    Serve,
+    /// List the repositories the current configuration would discover, then exit
+    Discover,
 }
 
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
-    println!("{:?}", args);
     
-    // 初始化日志
-    let subscriber = tracing_subscriber::fmt();
-    subscriber.pretty().init();
-
     // 加载配置
     let config = Config::from_args_and_file(
+        args.config.as_deref(),
         args.db_path.clone(),
         args.bind_address,
+        args.static_dir.clone(),
         args.git_base_path.clone(),
     )?;
-    let config = Arc::new(config);
+
+    // 初始化日志（格式与级别来自配置）
+    shared::logging::init(&config.logging);
+    // 加载配置时日志尚未初始化，这里补充输出配置来源
+    match Config::locate_file(args.config.as_deref())? {
+        Some(path) => info!("Loaded configuration from {}", path.display()),
+        None => info!("No config file found, using default configuration"),
+    }
+    config.validate()?;
+
+    if let Some(Command::Discover) = args.command {
+        return discover(config).await;
+    }
+    debug!("Command line arguments: {:?}", args);
+
+    let config_handle = ConfigHandle::new(config, ConfigOverrides {
+        config_file: args.config.clone(),
+        db_path: args.db_path.clone(),
+        bind_address: args.bind_address,
+        static_dir: args.static_dir.clone(),
+        git_base_path: args.git_base_path.clone(),
+    });
+    let config = config_handle.load();
 
     info!("Starting GitX server...");
     info!("Configuration loaded: {:?}", config);
 
     // 初始化 SQLite 数据库
-    let sqlite_pool = infrastructure::sqlite::create_pool(
-        &config.database.sqlite_path,
-        config.database.max_connections,
-    )
-    .await?;
+    let sqlite_pool = infrastructure::sqlite::create_pool(&config.database).await?;
 
     // 运行数据库迁移
     info!("Running database migrations...");
@@ -79,12 +121,39 @@ async fn main() -> Result<()> {
 
     // 创建新架构的应用上下文
     let repository_store = Arc::new(SqliteRepositoryRepository::new(sqlite_pool.clone()));
+    // 上次退出时未完成的索引，调度器会从中断处继续
+    let interrupted = repository_store.mark_index_interrupted().await?;
+    if interrupted > 0 {
+        info!("{} repositories were interrupted mid-index and will resume", interrupted);
+    }
     let commit_store = Arc::new(SqliteCommitRepository::new(sqlite_pool.clone()));
     let branch_store = Arc::new(SqliteBranchRepository::new(sqlite_pool.clone()));
-    let git_client = Arc::new(Git2Client::new());
-    let cache = Arc::new(MokaCache::new(
+    // 阻塞 git 操作的并发上限，索引与页面请求共用：定时索引最多同时处理 worker_threads 个仓库、
+    // 每个仓库一次执行一个 git 操作，通常余下约一半供页面使用，但并不预留（webhook、手动重建不受此限）
+    let git_client = Arc::new(Git2Client::with_blocking_limit(
+        config.git.clone(),
+        config.indexer.worker_threads.max(1) * 2,
+    ));
+    let memory_cache = MokaCache::new(
         config.cache.max_capacity,
         Duration::from_secs(config.cache.ttl_secs),
+    );
+    let disk_cache = match &config.cache.disk_path {
+        Some(path) => Some(DiskCache::new(path.clone(), config.cache.disk_max_bytes)?),
+        None => None,
+    };
+    let cache = Arc::new(TieredCache::new(memory_cache, disk_cache));
+
+    let metrics = Arc::new(shared::metrics::Metrics::new());
+
+    // 创建新架构的索引调度器
+    let scheduler = Arc::new(services::scheduler::IndexerScheduler::new(
+        config_handle.clone(),
+        repository_store.clone(),
+        commit_store.clone(),
+        branch_store.clone(),
+        git_client.clone(),
+        metrics.clone(),
     ));
 
     let app_context = Arc::new(AppContext {
@@ -92,53 +161,203 @@ async fn main() -> Result<()> {
         commit_store: commit_store.clone(),
         branch_store: branch_store.clone(),
         git_client: git_client.clone(),
+        scheduler: scheduler.clone(),
         cache,
-        config: config.clone(),
+        config: config_handle.clone(),
+        db_pool: sqlite_pool.clone(),
+        metrics,
+        rate_limiter: Arc::new(RateLimiter::new()),
+        recent_commits: Arc::new(RecentCommits::default()),
     });
-
-    // 启动新架构的索引调度器
-    let scheduler = Arc::new(services::scheduler::IndexerScheduler::new(
-        config.clone(),
-        repository_store.clone(),
-        commit_store.clone(),
-        branch_store.clone(),
-        git_client.clone(),
-    ));
     
     info!("Starting indexer scheduler...");
-    tokio::spawn(async move {
-        scheduler.start().await;
+    let scheduler_task = tokio::spawn({
+        let scheduler = scheduler.clone();
+        async move { scheduler.start().await }
     });
 
-    let serve_dir_service = ServeDir::new("statics");
+    // 数据库维护（database.optimize_interval_secs 为 0 时不运行）
+    tokio::spawn(
+        services::maintenance::DatabaseMaintenance::new(
+            config.database.clone(),
+            sqlite_pool.clone(),
+            scheduler.clone(),
+        )
+        .start(),
+    );
+
+    // SIGHUP 触发配置热加载
+    #[cfg(unix)]
+    tokio::spawn(reload_on_sighup(config_handle));
+
+    if !config.server.static_dir.is_dir() {
+        warn!("Static directory {} does not exist, /statics will return 404", config.server.static_dir.display());
+    }
+    let serve_dir_service = ServeDir::new(&config.server.static_dir);
 
     // 创建应用路由（新架构）
-    let app = presentation::routes::create_app_router(app_context)
-        .nest_service("/statics", serve_dir_service)
-        .layer(CorsLayer::new()
-            .allow_origin(config.server.cors_origins[0].parse::<HeaderValue>()
-                .map_err(|e| shared::error::GitxError::Config(e.to_string()))?)
-            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE]));
+    let mut app = presentation::routes::create_app_router(app_context)
+        .nest_service("/statics", serve_dir_service);
+    if config.server.compression {
+        app = app.layer(build_compression_layer());
+    }
+    let app = app.layer(build_cors_layer(&config.server.cors_origins)?);
 
     let listener = tokio::net::TcpListener::bind(&config.server.bind_address)
         .await
-        .map_err(|e| shared::error::GitxError::Io(e))?;
+        .map_err(shared::error::GitxError::Io)?;
 
     info!("Server listening on {}", config.server.bind_address);
     info!("Web UI available at: http://{}/", config.server.bind_address);
     info!("API available at: http://{}/api/", config.server.bind_address);
     
-    axum::serve(listener, app)
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown_signal())
         .await
         .map_err(|e| shared::error::GitxError::Internal(e.to_string()))?;
+
+    // 等待正在进行的索引周期结束，避免中断写入中的事务
+    info!("Stopping indexer scheduler...");
+    scheduler.shutdown();
+    if tokio::time::timeout(SHUTDOWN_TIMEOUT, scheduler_task).await.is_err() {
+        tracing::warn!("Indexer did not stop within {:?}, exiting anyway", SHUTDOWN_TIMEOUT);
+    }
+
+    sqlite_pool.close().await;
+    info!("Shutdown complete");
     
     Ok(())
 }
 
+/// `gitx discover`：只运行仓库发现并打印结果，不启动服务器
+async fn discover(config: Config) -> Result<()> {
+    let repos = services::discovery::RepositoryDiscovery::new(Arc::new(config))
+        .discover_all()
+        .await?;
+
+    for repo in &repos {
+        println!("{}\t{}{}", repo.name, repo.path.display(), if repo.bare { "\t(bare)" } else { "" });
+    }
+    println!("{} repositories discovered", repos.len());
+
+    Ok(())
+}
+
+/// 收到 SIGHUP 时重新加载 config.toml，失败时保留旧配置
+#[cfg(unix)]
+async fn reload_on_sighup(config: ConfigHandle) {
+    let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+        Ok(signal) => signal,
+        Err(e) => {
+            tracing::error!("Failed to listen for SIGHUP: {}", e);
+            return;
+        }
+    };
+
+    while hangup.recv().await.is_some() {
+        info!("Received SIGHUP, reloading configuration");
+        if let Err(e) = config.reload() {
+            tracing::error!("Configuration reload rejected, keeping current config: {}", e);
+        }
+    }
+}
+
+/// 关闭时等待索引完成的最长时间
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 等待 Ctrl-C 或 SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received Ctrl-C, shutting down"),
+        _ = terminate => info!("Received SIGTERM, shutting down"),
+    }
+}
+
+/// 按 `Accept-Encoding` 以 br/gzip 压缩响应；图片、压缩包、字体等已压缩的二进制内容原样返回
+fn build_compression_layer() -> CompressionLayer<impl Predicate> {
+    let predicate = DefaultPredicate::new()
+        .and(NotForContentType::const_new("application/octet-stream"))
+        .and(NotForContentType::const_new("application/zip"))
+        .and(NotForContentType::const_new("application/gzip"))
+        .and(NotForContentType::const_new("application/x-gzip"))
+        .and(NotForContentType::const_new("application/x-bzip2"))
+        .and(NotForContentType::const_new("application/x-xz"))
+        .and(NotForContentType::const_new("application/x-7z-compressed"))
+        .and(NotForContentType::const_new("application/zstd"))
+        .and(NotForContentType::const_new("font/"))
+        .and(NotForContentType::const_new("audio/"))
+        .and(NotForContentType::const_new("video/"));
+    CompressionLayer::new().compress_when(predicate)
+}
+
+/// 根据配置的全部来源构建 CORS 层，`"*"` 表示允许任意来源
+fn build_cors_layer(origins: &[String]) -> Result<CorsLayer> {
+    if origins.is_empty() {
+        return Err(shared::error::GitxError::Config(
+            "server.cors_origins must not be empty".to_string(),
+        ));
+    }
+
+    let allow_origin = if origins.iter().any(|o| o == "*") {
+        AllowOrigin::any()
+    } else {
+        let values = origins
+            .iter()
+            .map(|o| {
+                o.parse::<HeaderValue>().map_err(|e| {
+                    shared::error::GitxError::Config(format!("invalid CORS origin {:?}: {}", o, e))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        AllowOrigin::list(values)
+    };
+
+    Ok(CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE]))
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_basic() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn test_build_cors_layer() {
+        let origins = vec![
+            "https://staging.example.com".to_string(),
+            "https://example.com".to_string(),
+        ];
+        assert!(build_cors_layer(&origins).is_ok());
+        assert!(build_cors_layer(&["*".to_string()]).is_ok());
+
+        assert!(build_cors_layer(&[]).is_err());
+        assert!(build_cors_layer(&["https://ok.example.com".to_string(), "bad\norigin".to_string()]).is_err());
+    }
 }
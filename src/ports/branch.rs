@@ -4,15 +4,21 @@ use crate::shared::result::Result;
 
 #[async_trait]
 pub trait BranchPort: Send + Sync {
-    /// 保存分支信息
-    async fn save(&self, branch: &Branch) -> Result<()>;
-    
     /// 保存多个分支
     async fn save_many(&self, branches: &[Branch]) -> Result<()>;
     
     /// 根据仓库ID查询所有分支
     async fn find_by_repository(&self, repository_id: i64) -> Result<Vec<Branch>>;
     
-    /// 删除仓库的所有分支
-    async fn delete_by_repository(&self, repository_id: i64) -> Result<()>;
+    /// 删除不在 `keep_names` 中的分支及其提交记录，返回被删除的分支名
+    async fn delete_missing(&self, repository_id: i64, keep_names: &[String]) -> Result<Vec<String>>;
+
+    /// 分支上次成功索引时的分支头，从未索引过时为 None
+    async fn indexed_tip(&self, repository_id: i64, name: &str) -> Result<Option<String>>;
+
+    /// 分支索引成功后记录已索引的分支头
+    async fn set_indexed_tip(&self, repository_id: i64, name: &str, oid: &str) -> Result<()>;
+
+    /// 更新分支的已索引提交数
+    async fn set_commit_count(&self, repository_id: i64, name: &str, count: i64) -> Result<()>;
 }
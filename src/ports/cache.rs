@@ -14,10 +14,4 @@ pub trait CachePort: Send + Sync {
 
     /// 删除缓存
     async fn delete(&self, key: &str) -> Result<()>;
-
-    /// 检查键是否存在
-    async fn exists(&self, key: &str) -> Result<bool>;
-
-    /// 清空所有缓存
-    async fn clear(&self) -> Result<()>;
 }
@@ -1,22 +1,47 @@
 use async_trait::async_trait;
+use futures::stream::BoxStream;
+use serde::Deserialize;
+use std::collections::HashSet;
 use crate::domain::entities::Commit;
 use crate::shared::result::Result;
 
 /// 提交仓储接口
 #[async_trait]
 pub trait CommitPort: Send + Sync {
+    /// 按插入顺序分批读取仓库的所有提交，逐条产出；内存中最多保留一批，不把结果集整体载入内存
+    fn stream_by_repository(&self, repository_id: i64) -> BoxStream<'_, Result<Commit>>;
+
     /// 根据 OID 查找提交
     async fn find_by_oid(&self, repository_id: i64, oid: &str) -> Result<Option<Commit>>;
 
+    /// 根据 OID 前缀（至少 4 位）查找提交，多个提交匹配时返回 `AmbiguousOid`
+    async fn find_by_oid_prefix(&self, repository_id: i64, prefix: &str) -> Result<Option<Commit>>;
+
+    /// 批量按 OID 查找提交，每个 OID 最多返回一条（多个分支上的同一提交取最早索引的一行）
+    async fn find_by_oids(&self, repository_id: i64, oids: &[String]) -> Result<Vec<Commit>>;
+
     /// 获取仓库的提交列表（分页）
     async fn list_by_repository(
         &self,
         repository_id: i64,
-        branch: Option<&str>,
+        filter: &CommitFilter<'_>,
+        order: CommitOrder,
         limit: i64,
         offset: i64,
     ) -> Result<Vec<Commit>>;
 
+    /// 获取仓库的提交列表（keyset 分页）
+    ///
+    /// 按 (committer_time, id) 倒序，返回严格位于游标之前的提交；游标为 None 时从最新开始
+    async fn list_by_repository_keyset(
+        &self,
+        repository_id: i64,
+        filter: &CommitFilter<'_>,
+        before_committer_time: Option<i64>,
+        before_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<Commit>>;
+
     /// 获取仓库某分支的最新提交
     async fn get_latest_commit(
         &self,
@@ -24,19 +49,31 @@ pub trait CommitPort: Send + Sync {
         branch: &str,
     ) -> Result<Option<Commit>>;
 
+    /// 返回 `oids` 中已在该分支索引过的提交
+    async fn existing_oids(&self, repository_id: i64, branch: &str, oids: &[String]) -> Result<HashSet<String>>;
+
     /// 批量插入提交
     async fn bulk_insert(&self, commits: &[Commit]) -> Result<usize>;
 
-    /// 保存单个提交
-    async fn save(&self, commit: &Commit) -> Result<i64>;
+    /// 删除某分支的所有提交，返回删除的行数
+    async fn delete_by_branch(&self, repository_id: i64, branch: &str) -> Result<u64>;
 
-    /// 删除仓库的所有提交
-    async fn delete_by_repository(&self, repository_id: i64) -> Result<()>;
+    /// 统计符合过滤条件的提交数量
+    async fn count_by_repository(&self, repository_id: i64, filter: &CommitFilter<'_>) -> Result<i64>;
+    
+    /// 提交总数及首/末提交时间（同一提交出现在多个分支时只计一次）
+    async fn commit_totals(&self, repository_id: i64) -> Result<CommitTotals>;
+
+    /// 按作者邮箱（不区分大小写）统计提交数，返回前 limit 名
+    async fn author_stats(&self, repository_id: i64, limit: i64) -> Result<Vec<AuthorStat>>;
 
-    /// 统计提交数量
-    async fn count_by_repository(&self, repository_id: i64, branch: Option<&str>) -> Result<i64>;
+    /// 按提交日期（UTC）统计每日提交数，按日期升序
+    async fn activity_by_day(&self, repository_id: i64) -> Result<Vec<DailyActivity>>;
+
+    /// 各分支的提交数
+    async fn branch_commit_counts(&self, repository_id: i64) -> Result<Vec<BranchCommitCount>>;
     
-    /// 获取两个分支之间的差异commits（在new_branch但不在old_branch的commits）
+    /// 获取两个分支之间的差异commits（在old_branch但不在new_branch的commits，按作者邮箱、作者时间与标题匹配）
     async fn find_diff_commits(
         &self,
         repository_id: i64,
@@ -45,3 +82,94 @@ pub trait CommitPort: Send + Sync {
         limit: i64,
     ) -> Result<Vec<Commit>>;
 }
+
+/// 提交列表过滤条件，各条件同时生效
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommitFilter<'a> {
+    pub branch: Option<&'a str>,
+    /// 作者名或邮箱（子串，不区分大小写）
+    pub author: Option<&'a str>,
+    /// committer_time 下限（Unix 秒，包含）
+    pub since: Option<i64>,
+    /// committer_time 上限（Unix 秒，不包含）
+    pub until: Option<i64>,
+}
+
+impl<'a> CommitFilter<'a> {
+    /// 只按分支过滤
+    pub fn branch(branch: Option<&'a str>) -> Self {
+        Self { branch, ..Self::default() }
+    }
+}
+
+/// 提交列表排序字段
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitSort {
+    #[default]
+    AuthorTime,
+    CommitterTime,
+}
+
+/// 排序方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    #[default]
+    Desc,
+    Asc,
+}
+
+/// 提交列表排序，默认按 author_time 倒序
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CommitOrder {
+    pub sort: CommitSort,
+    pub direction: SortDirection,
+}
+
+impl CommitOrder {
+    /// 与 keyset 分页相同的 (committer_time, id) 倒序，日志页的 offset 与游标翻页共用
+    pub const KEYSET: CommitOrder = CommitOrder { sort: CommitSort::CommitterTime, direction: SortDirection::Desc };
+
+    /// ORDER BY 子句（固定列名，不拼接外部输入），相同时间按 id 保持稳定顺序
+    pub fn sql(self) -> &'static str {
+        match (self.sort, self.direction) {
+            (CommitSort::AuthorTime, SortDirection::Desc) => "author_time DESC, id DESC",
+            (CommitSort::AuthorTime, SortDirection::Asc) => "author_time ASC, id ASC",
+            (CommitSort::CommitterTime, SortDirection::Desc) => "committer_time DESC, id DESC",
+            (CommitSort::CommitterTime, SortDirection::Asc) => "committer_time ASC, id ASC",
+        }
+    }
+}
+
+/// 提交总体统计
+#[derive(Debug, Clone)]
+pub struct CommitTotals {
+    pub total_commits: i64,
+    pub first_commit_time: Option<i64>,
+    pub last_commit_time: Option<i64>,
+}
+
+/// 作者提交统计
+#[derive(Debug, Clone)]
+pub struct AuthorStat {
+    /// 小写化后的邮箱
+    pub author_email: String,
+    pub author_name: String,
+    pub commits: i64,
+}
+
+/// 每日提交数
+#[derive(Debug, Clone)]
+pub struct DailyActivity {
+    /// YYYY-MM-DD
+    pub day: String,
+    pub commits: i64,
+}
+
+/// 分支提交数
+#[derive(Debug, Clone)]
+pub struct BranchCommitCount {
+    pub branch: String,
+    pub commits: i64,
+}
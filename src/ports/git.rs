@@ -1,30 +1,100 @@
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
+use crate::shared::config::WalkMode;
 use crate::shared::result::Result;
 
+/// diff 默认上下文行数（与 git 一致）
+pub const DEFAULT_CONTEXT_LINES: u32 = 3;
+
+/// diff 上下文行数上限，超出时截断
+pub const MAX_CONTEXT_LINES: u32 = 100;
+
 /// Git 操作接口
 #[async_trait]
 pub trait GitPort: Send + Sync {
-    /// 拉取仓库更新
-    async fn fetch_repository(&self, path: &Path) -> Result<FetchResult>;
+    /// 从指定远程拉取仓库更新（远程不存在时返回 Config 错误）
+    async fn fetch_repository(&self, path: &Path, remote: &str) -> Result<FetchResult>;
 
-    /// 获取提交列表
+    /// 获取提交列表（include_merges 为 false 时跳过合并提交，topological 模式下忽略）
     async fn get_commits(
         &self,
         path: &Path,
         branch: &str,
         limit: usize,
         since_oid: Option<&str>,
+        include_merges: bool,
+        walk: WalkMode,
     ) -> Result<Vec<GitCommit>>;
 
-    /// 获取所有分支
+    /// 计算提交相对第一个父提交的文件变更统计，结果与 `oids` 顺序一致
+    async fn commit_stats(&self, path: &Path, oids: &[String]) -> Result<Vec<CommitStats>>;
+
+    /// 是否为裸仓库（没有工作区）
+    async fn is_bare(&self, path: &Path) -> Result<bool>;
+
+    /// 将 SHA 前缀解析为完整的提交 OID（无匹配返回 CommitNotFound，有歧义返回 AmbiguousOid）
+    async fn resolve_oid(&self, path: &Path, prefix: &str) -> Result<String>;
+
+    /// 判断 `oid` 是否等于 `ancestor` 或是其后代（`ancestor` 不存在时返回 false，用于检测强制推送）
+    async fn is_descendant_of(&self, path: &Path, oid: &str, ancestor: &str) -> Result<bool>;
+
+    /// 两个提交的最近公共祖先，没有共同历史时返回 None
+    async fn merge_base(&self, path: &Path, a_oid: &str, b_oid: &str) -> Result<Option<String>>;
+
+    /// `oid` 相对 `upstream` 领先与落后的提交数 `(ahead, behind)`
+    async fn ahead_behind(&self, path: &Path, oid: &str, upstream: &str) -> Result<(usize, usize)>;
+
+    /// 对象库（objects 目录）占用的磁盘字节数
+    async fn object_storage_size(&self, path: &Path) -> Result<u64>;
+
+    /// 根提交 OID（沿 HEAD，没有 HEAD 时沿所有分支的第一父提交回溯），空仓库返回 None；用作仓库移动后的稳定标识
+    async fn root_commit_oid(&self, path: &Path) -> Result<Option<String>>;
+
+    /// 检测默认分支：读取 `<remote>/HEAD`（裸仓库读取 `HEAD`），返回与 `GitBranch::name` 相同格式的简短名称
+    async fn default_branch(&self, path: &Path, remote: &str) -> Result<Option<String>>;
+
+    /// 读取指定 ref 下的文件内容，文件不存在时返回 None
+    async fn read_file(&self, path: &Path, rev: &str, file_path: &str) -> Result<Option<Vec<u8>>>;
+
+    /// 读取指定版本的文件对象
+    ///
+    /// 路径不存在或不是文件时返回 FileNotFound，超过 `git.max_raw_bytes` 的文件会被拒绝
+    async fn read_blob(&self, path: &Path, rev: &str, file_path: &str) -> Result<GitBlob>;
+
+    /// 列出指定版本下目录的直接子项（`dir` 为空表示根目录），目录在前、按名称排序
+    ///
+    /// 子模块（gitlink）不递归进入，只返回固定的提交 OID 及 `.gitmodules` 中的 URL；
+    /// 路径不存在或不是目录时返回 FileNotFound
+    async fn list_tree(&self, path: &Path, rev: &str, dir: &str) -> Result<Vec<GitTreeEntry>>;
+
+    /// 获取所有分支（裸仓库返回本地分支，否则返回远程分支）
     async fn list_branches(&self, path: &Path) -> Result<Vec<GitBranch>>;
 
-    /// 获取所有标签
+    /// 获取所有标签（target_oid 为剥离后的提交，按 tagger 时间倒序）
     async fn list_tags(&self, path: &Path) -> Result<Vec<GitTag>>;
 
-    /// 获取提交详情（包含 diff）
-    async fn get_commit_detail(&self, path: &Path, oid: &str) -> Result<GitCommitDetail>;
+    /// 按名称查找标签（只匹配 refs/tags/），不存在时返回 None
+    async fn find_tag(&self, path: &Path, name: &str) -> Result<Option<GitTag>>;
+
+    /// 获取提交元数据（不计算 diff，提交不存在时返回 CommitNotFound）
+    async fn get_commit(&self, path: &Path, oid: &str) -> Result<GitCommit>;
+
+    /// 获取提交详情（包含 diff），`context_lines` 为空时使用默认的 3 行上下文
+    async fn get_commit_detail(&self, path: &Path, oid: &str, context_lines: Option<u32>) -> Result<GitCommitDetail>;
+
+    /// 提交中单个文件相对第一个父提交的结构化 diff（`file_path` 可以是重命名前后的任一路径），
+    /// 文件未被该提交修改时返回 None
+    async fn file_diff(
+        &self,
+        path: &Path,
+        oid: &str,
+        file_path: &str,
+        context_lines: Option<u32>,
+    ) -> Result<Option<GitFileDiff>>;
+
+    /// 生成 `git format-patch` 格式的补丁（含 From/Subject 头与二进制补丁），合并提交返回 InvalidRequest
+    async fn format_patch(&self, path: &Path, oid: &str) -> Result<Vec<u8>>;
 
     /// 比较两个提交
     async fn compare_commits(
@@ -32,28 +102,32 @@ pub trait GitPort: Send + Sync {
         path: &Path,
         from_oid: &str,
         to_oid: &str,
+        context_lines: Option<u32>,
     ) -> Result<GitDiff>;
     
-    /// 获取两个分支之间的差异commits（类似 git log old_branch..new_branch）
-    /// 返回在new_branch但不在old_branch的commits
-    async fn get_branch_diff_commits(
+    /// 获取修改过指定文件的提交（类似 git log <rev> -- <file>）
+    async fn file_history(
         &self,
         path: &Path,
-        old_branch: &str,
-        new_branch: &str,
+        rev: &str,
+        file_path: &str,
         limit: usize,
     ) -> Result<Vec<GitCommit>>;
+
+    /// 获取文件在指定版本的逐行 blame
+    ///
+    /// 二进制文件及超过 `git.max_blame_bytes` 的文件会被拒绝
+    async fn blame(&self, path: &Path, rev: &str, file_path: &str) -> Result<Vec<BlameLine>>;
 }
 
 /// Fetch 操作结果
 #[derive(Debug)]
 pub struct FetchResult {
-    pub commits_fetched: usize,
     pub branches_updated: Vec<String>,
 }
 
 /// Git 提交信息（从 git2 提取）
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GitCommit {
     pub oid: String,
     pub author_name: String,
@@ -65,16 +139,27 @@ pub struct GitCommit {
     pub summary: String,
     pub message: Option<String>,
     pub parent_oids: Vec<String>,
+    pub tree_oid: String,
 }
 
 /// Git 分支信息
 #[derive(Debug, Clone)]
 pub struct GitBranch {
     pub name: String,
+    /// 完整引用名，如 refs/remotes/origin/main 或 refs/heads/main（裸仓库）
+    pub ref_name: String,
     pub target_oid: String,
     pub is_head: bool,
 }
 
+/// 提交的文件变更统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CommitStats {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
 /// Git 标签信息
 #[derive(Debug, Clone)]
 pub struct GitTag {
@@ -86,27 +171,192 @@ pub struct GitTag {
     pub message: Option<String>,
 }
 
-/// 提交详情（包含 diff）
-#[derive(Debug)]
+/// 文件对象内容
+#[derive(Debug, Clone)]
+pub struct GitBlob {
+    pub content: Vec<u8>,
+    pub is_binary: bool,
+}
+
+/// 目录树条目类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TreeEntryKind {
+    File,
+    Directory,
+    Symlink,
+    /// gitlink（filemode 160000），指向子模块仓库中的提交
+    Submodule,
+}
+
+/// 目录树条目
+#[derive(Debug, Clone, Serialize)]
+pub struct GitTreeEntry {
+    pub name: String,
+    /// 相对仓库根目录的完整路径
+    pub path: String,
+    pub kind: TreeEntryKind,
+    /// 对象 OID；子模块为固定的提交 OID（该对象不在本仓库中）
+    pub oid: String,
+    pub mode: i32,
+    /// 文件大小（字节），目录和子模块为 None
+    pub size: Option<u64>,
+    /// 子模块 URL（来自同一版本的 `.gitmodules`，未配置时为 None）
+    pub submodule_url: Option<String>,
+}
+
+/// Blame 行信息
+#[derive(Debug, Clone)]
+pub struct BlameLine {
+    pub line_no: usize,
+    pub oid: String,
+    pub author_name: String,
+    pub content: String,
+}
+
+/// 提交详情（包含 diff），可序列化以便写入缓存
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GitCommitDetail {
     pub commit: GitCommit,
     pub diff_stats: String,
+    /// diff 超过 `git.max_diff_lines` 时为空，只保留统计与 `diff_plain`
     pub diff_html: String,
+    pub diff_too_large: bool,
+    /// 统一 diff 原文（与 `git diff` 输出一致，二进制文件为 "Binary files ... differ"）
     pub diff_plain: Vec<u8>,
+    /// 每个文件的增删行数，按 diff 顺序排列（diff 过大时同样保留）
+    #[serde(default)]
+    pub file_stats: Vec<FileStat>,
+    /// 带行号的结构化 diff，与 `diff_html` 一样在 diff 过大时为空
+    pub hunks: Vec<DiffHunk>,
+    /// 提交签名信息，未签名时为 None
+    pub signature: Option<SignatureInfo>,
 }
 
-/// Diff 信息
-#[derive(Debug)]
+/// 提交中单个文件的 diff
+#[derive(Debug, Serialize)]
+pub struct GitFileDiff {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub status: DeltaStatus,
+    /// 二进制文件不收集 hunks
+    pub binary: bool,
+    pub insertions: usize,
+    pub deletions: usize,
+    /// 变更行数超过 `git.max_diff_lines` 时为 true，此时 hunks 为空
+    pub too_large: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// 单个文件的变更行数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileStat {
+    /// 新路径（删除的文件为旧路径）
+    pub path: String,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// diff 中的一个 hunk（对应一个 `@@ -a,b +c,d @@` 头）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffHunk {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    /// hunk 头原文，如 `@@ -1,3 +1,4 @@ fn main()`
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffHunkLine>,
+}
+
+/// hunk 中的一行，行号从 1 开始；新增行没有旧行号，删除行没有新行号
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffHunkLine {
+    /// `+`、`-`、` `，或表示行尾无换行的 `=`、`>`、`<`
+    pub origin: char,
+    pub old_lineno: Option<u32>,
+    pub new_lineno: Option<u32>,
+    /// 行内容（不含 origin 前缀，非 UTF-8 按 lossy 转换）
+    pub content: String,
+    /// 渲染后的单行 HTML（提交详情中带语法高亮与单词级 diff，其余为转义后的内容）
+    pub html: String,
+}
+
+/// 签名类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureKind {
+    Gpg,
+    Ssh,
+    X509,
+    Unknown,
+}
+
+impl SignatureKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignatureKind::Gpg => "GPG",
+            SignatureKind::Ssh => "SSH",
+            SignatureKind::X509 => "X.509",
+            SignatureKind::Unknown => "unknown",
+        }
+    }
+}
+
+/// 提交签名信息（仅识别签名者，尚未对 keyring 验证）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureInfo {
+    pub kind: SignatureKind,
+    /// GPG 为 key fingerprint / key id，SSH 为公钥类型及指纹
+    pub signer: Option<String>,
+}
+
+/// 两个提交之间的 diff 概要，可序列化以便写入缓存
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GitDiff {
-    pub stats: String,
+    pub totals: CommitStats,
+    /// 每个变更文件一项，按 diff 顺序排列
     pub patches: Vec<GitDiffPatch>,
 }
 
-/// Diff Patch
-#[derive(Debug)]
+/// 文件变更类型，API 中序列化为小写字符串（如 `renamed`、`type_changed`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeltaStatus {
+    Added,
+    Deleted,
+    Modified,
+    Renamed,
+    Copied,
+    /// 文件类型变化（如普通文件变为符号链接）
+    TypeChanged,
+    /// 树之间的 diff 不会出现的其他状态（未修改、未跟踪、冲突等）
+    Other,
+}
+
+impl DeltaStatus {
+    /// 与序列化结果相同的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeltaStatus::Added => "added",
+            DeltaStatus::Deleted => "deleted",
+            DeltaStatus::Modified => "modified",
+            DeltaStatus::Renamed => "renamed",
+            DeltaStatus::Copied => "copied",
+            DeltaStatus::TypeChanged => "type_changed",
+            DeltaStatus::Other => "other",
+        }
+    }
+}
+
+/// 单个文件的变更概要（不含内容）
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GitDiffPatch {
     pub old_path: Option<String>,
     pub new_path: Option<String>,
-    pub status: String,
-    pub hunks: Vec<String>,
+    pub status: DeltaStatus,
+    pub binary: bool,
+    pub old_size: u64,
+    pub new_size: u64,
 }
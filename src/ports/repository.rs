@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use crate::domain::entities::Repository;
+use crate::domain::entities::{IndexProgress, Repository, RepositoryStatus};
 use crate::shared::result::Result;
 
 /// 仓库仓储接口（Repository Pattern）
@@ -17,15 +17,42 @@ pub trait RepositoryPort: Send + Sync {
     /// 列出所有仓库
     async fn list_all(&self) -> Result<Vec<Repository>>;
 
+    /// 分页列出仓库，name_filter 按名称子串匹配（不区分大小写），同时返回匹配总数
+    async fn list_paged(&self, limit: i64, offset: i64, name_filter: Option<&str>) -> Result<(Vec<Repository>, i64)>;
+
     /// 保存仓库（插入或更新）
     async fn save(&self, repo: &Repository) -> Result<i64>;
 
-    /// 删除仓库
+    /// 删除仓库（提交、分支、标签由外键 ON DELETE CASCADE 一并删除）
     async fn delete(&self, id: i64) -> Result<()>;
 
+    /// 在同一事务中删除仓库已索引的提交与分支（全量重建索引前调用）
+    async fn clear_index(&self, id: i64) -> Result<()>;
+
     /// 更新同步时间
     async fn update_sync_time(&self, id: i64) -> Result<()>;
 
+    /// 更新默认分支
+    async fn update_default_branch(&self, id: i64, branch: &str) -> Result<()>;
+
     /// 检查路径是否存在
     async fn exists_by_path(&self, path: &str) -> Result<bool>;
+
+    /// 查找指纹（根提交 OID）相同的仓库，按登记顺序
+    async fn find_by_fingerprint(&self, fingerprint: &str) -> Result<Vec<Repository>>;
+
+    /// 更新仓库状态；标记为 missing 时记录首次发现的时间，恢复 active 时清除
+    async fn set_status(&self, id: i64, status: RepositoryStatus) -> Result<()>;
+
+    /// 仓库目录移动后更新名称与路径，并恢复为 active
+    async fn relocate(&self, id: i64, name: &str, path: &str) -> Result<()>;
+
+    /// 仓库当前或最近一次的索引进度
+    async fn index_progress(&self, id: i64) -> Result<Option<IndexProgress>>;
+
+    /// 保存索引进度（每个仓库只保留一行）
+    async fn save_index_progress(&self, progress: &IndexProgress) -> Result<()>;
+
+    /// 将仍为 running 的进度标记为 interrupted（启动时调用，此时不会有索引在进行），返回行数
+    async fn mark_index_interrupted(&self) -> Result<u64>;
 }
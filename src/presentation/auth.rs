@@ -0,0 +1,251 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use crate::presentation::routes::AppContext;
+use crate::shared::config::Config;
+use crate::shared::error::GitxError;
+
+/// 常量时间比较，耗时与内容无关（长度不同直接返回 false）
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// 从 `Authorization` 头取出 API key：Bearer token 或 Basic 认证的密码（用户名忽略）
+fn provided_key(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, credentials) = value.split_once(' ')?;
+    let credentials = credentials.trim();
+    if scheme.eq_ignore_ascii_case("bearer") {
+        return Some(credentials.to_string());
+    }
+    if scheme.eq_ignore_ascii_case("basic") {
+        let decoded = String::from_utf8(STANDARD.decode(credentials).ok()?).ok()?;
+        let (_, password) = decoded.split_once(':')?;
+        return Some(password.to_string());
+    }
+    None
+}
+
+/// key 的 SHA-256 是否与配置中的某一项相同
+fn key_matches(key: &str, api_keys: &[String]) -> bool {
+    let digest = hex::encode(Sha256::digest(key.as_bytes()));
+    // 逐项比较且不提前退出，避免通过耗时推断匹配位置
+    api_keys
+        .iter()
+        .fold(false, |found, k| constant_time_eq(k.to_ascii_lowercase().as_bytes(), digest.as_bytes()) | found)
+}
+
+/// 请求是否需要认证：API、写操作，以及开启 `protect_ui` 时的所有页面（健康检查除外）
+fn requires_auth(config: &Config, method: &Method, path: &str) -> bool {
+    if path == "/healthz" || path == "/readyz" {
+        return false;
+    }
+    // webhook 使用自己的共享密钥
+    if path == "/api/webhook" && config.webhook.secret.is_some() {
+        return false;
+    }
+    config.auth.protect_ui
+        || path.starts_with("/api/")
+        || !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// API key 认证（未启用 `auth.enabled` 时直接放行）
+pub async fn require_api_key(
+    State(ctx): State<Arc<AppContext>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let config = ctx.config.load();
+    if !config.auth.enabled || !requires_auth(&config, request.method(), request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    match provided_key(request.headers()) {
+        Some(key) if key_matches(&key, &config.auth.api_keys) => next.run(request).await,
+        provided => {
+            let reason = if provided.is_some() { "invalid API key" } else { "API key required" };
+            let mut response = GitxError::Unauthorized(reason.to_string()).into_response();
+            let headers = response.headers_mut();
+            headers.append(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer realm=\"gitx\""));
+            headers.append(header::WWW_AUTHENTICATE, HeaderValue::from_static("Basic realm=\"gitx\""));
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_key_credentials() {
+        // echo -n secret | sha256sum
+        let keys = vec!["2BB80D537B1DA3E38BD30361AA855686BDE0EACD7162FEF6A25FE97BF527A25B".to_string()];
+        let mut headers = HeaderMap::new();
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        assert!(key_matches(&provided_key(&headers).unwrap(), &keys));
+        // Basic 认证：用户名任意，密码为 key
+        let basic = format!("Basic {}", STANDARD.encode("ci:secret"));
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_str(&basic).unwrap());
+        assert!(key_matches(&provided_key(&headers).unwrap(), &keys));
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer wrong"));
+        assert!(!key_matches(&provided_key(&headers).unwrap(), &keys));
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Digest secret"));
+        assert!(provided_key(&headers).is_none());
+    }
+
+    /// 与 main 相同的路由组合：应用路由加上认证层之外的 `/statics`
+    async fn test_app(auth: &str, statics: &std::path::Path) -> axum::Router {
+        use crate::infrastructure::cache::{MokaCache, TieredCache};
+        use crate::infrastructure::git::Git2Client;
+        use crate::infrastructure::sqlite::branch_repo::SqliteBranchRepository;
+        use crate::infrastructure::sqlite::commit_repo::SqliteCommitRepository;
+        use crate::infrastructure::sqlite::repository_repo::SqliteRepositoryRepository;
+        use crate::infrastructure::sqlite::run_migrations;
+        use crate::presentation::rate_limit::RateLimiter;
+        use crate::presentation::recent::RecentCommits;
+        use crate::services::scheduler::IndexerScheduler;
+        use crate::shared::config::{ConfigHandle, ConfigOverrides};
+        use crate::shared::metrics::Metrics;
+        use sqlx::sqlite::SqlitePoolOptions;
+
+        let config: Config = toml::from_str(&format!(
+            r#"
+            projects = []
+            [server]
+            bind_address = "127.0.0.1:0"
+            cors_origins = ["*"]
+            [database]
+            sqlite_path = ":memory:"
+            max_connections = 1
+            [git]
+            [indexer]
+            [cache]
+            [webhook]
+            secret = "hook"
+            [auth]
+            {}
+            "#,
+            auth
+        ))
+        .unwrap();
+        let config = ConfigHandle::new(config, ConfigOverrides {
+            config_file: None,
+            db_path: std::path::PathBuf::from(":memory:"),
+            bind_address: None,
+            static_dir: None,
+            git_base_path: None,
+        });
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        let repository_store = Arc::new(SqliteRepositoryRepository::new(pool.clone()));
+        let commit_store = Arc::new(SqliteCommitRepository::new(pool.clone()));
+        let branch_store = Arc::new(SqliteBranchRepository::new(pool.clone()));
+        let git_client = Arc::new(Git2Client::default());
+        let metrics = Arc::new(Metrics::new());
+        let scheduler = Arc::new(IndexerScheduler::new(
+            config.clone(),
+            repository_store.clone(),
+            commit_store.clone(),
+            branch_store.clone(),
+            git_client.clone(),
+            metrics.clone(),
+        ));
+        let ctx = Arc::new(AppContext {
+            repository_store,
+            commit_store,
+            branch_store,
+            git_client,
+            scheduler,
+            cache: Arc::new(TieredCache::new(MokaCache::new(100, std::time::Duration::from_secs(60)), None)),
+            config,
+            db_pool: pool,
+            metrics,
+            rate_limiter: Arc::new(RateLimiter::new()),
+            recent_commits: Arc::new(RecentCommits::default()),
+        });
+        crate::presentation::routes::create_app_router(ctx)
+            .nest_service("/statics", tower_http::services::ServeDir::new(statics))
+    }
+
+    async fn status(app: &axum::Router, method: Method, uri: &str, headers: &[(&str, &str)]) -> u16 {
+        use tower::ServiceExt;
+
+        let mut request = axum::http::Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let body = if uri == "/api/webhook" { "{}" } else { "" };
+        let request = request
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(body))
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap().status().as_u16()
+    }
+
+    #[tokio::test]
+    async fn test_require_api_key_routes() {
+        let statics = tempfile::tempdir().unwrap();
+        std::fs::write(statics.path().join("app.css"), "body {}").unwrap();
+        // echo -n secret | sha256sum
+        let keys = r#"api_keys = ["2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b"]"#;
+        let bearer = [("authorization", "Bearer secret")];
+
+        // 未启用时全部放行
+        let app = test_app("enabled = false", statics.path()).await;
+        assert_eq!(status(&app, Method::GET, "/api/repositories", &[]).await, 200);
+
+        let app = test_app(&format!("enabled = true\n{}", keys), statics.path()).await;
+        assert_eq!(status(&app, Method::GET, "/api/repositories", &[]).await, 401);
+        assert_eq!(status(&app, Method::GET, "/api/repositories", &[("authorization", "Bearer wrong")]).await, 401);
+        assert_eq!(status(&app, Method::GET, "/api/repositories", &bearer).await, 200);
+        // 写操作即使不在 /api 下也需要认证
+        assert_eq!(status(&app, Method::POST, "/demo/api/sync", &[]).await, 401);
+        // 只读页面、指标、健康检查与静态资源默认不需要认证
+        assert_eq!(status(&app, Method::GET, "/", &[]).await, 200);
+        assert_eq!(status(&app, Method::GET, "/metrics", &[]).await, 200);
+        assert_eq!(status(&app, Method::GET, "/healthz", &[]).await, 200);
+        assert_eq!(status(&app, Method::GET, "/statics/app.css", &[]).await, 200);
+        // 配置了 webhook.secret 时由 webhook 自行校验密钥，请求体为空返回 400 而非 401
+        assert_eq!(status(&app, Method::POST, "/api/webhook", &[("x-webhook-secret", "hook")]).await, 400);
+        assert_eq!(status(&app, Method::POST, "/api/webhook", &[]).await, 401);
+
+        // protect_ui 时页面与指标也需要认证，健康检查与静态资源除外
+        let app = test_app(&format!("enabled = true\nprotect_ui = true\n{}", keys), statics.path()).await;
+        assert_eq!(status(&app, Method::GET, "/", &[]).await, 401);
+        assert_eq!(status(&app, Method::GET, "/", &bearer).await, 200);
+        assert_eq!(status(&app, Method::GET, "/metrics", &[]).await, 401);
+        assert_eq!(status(&app, Method::GET, "/healthz", &[]).await, 200);
+        assert_eq!(status(&app, Method::GET, "/statics/app.css", &[]).await, 200);
+    }
+
+    #[tokio::test]
+    async fn test_admin_token_routes() {
+        let statics = tempfile::tempdir().unwrap();
+        let app = test_app("enabled = false\n[admin]\ntoken = \"admin\"", statics.path()).await;
+
+        // 管理接口在 API key 认证之外还需要管理令牌
+        for (method, uri) in [(Method::GET, "/api/config"), (Method::POST, "/api/config/reload")] {
+            assert_eq!(status(&app, method.clone(), uri, &[]).await, 401);
+            assert_eq!(status(&app, method.clone(), uri, &[("x-admin-token", "wrong")]).await, 401);
+            assert_ne!(status(&app, method, uri, &[("x-admin-token", "admin")]).await, 401);
+        }
+    }
+}
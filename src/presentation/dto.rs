@@ -1,5 +1,11 @@
+use chrono::DateTime;
 use serde::{Deserialize, Serialize};
-use crate::domain::entities::{Repository, Commit};
+use crate::domain::entities::{Repository, Commit, IndexProgress};
+use crate::ports::commit::{AuthorStat, BranchCommitCount, DailyActivity};
+use crate::ports::git::GitCommit;
+use crate::presentation::recent::RecentCommit;
+use crate::shared::error::GitxError;
+use crate::shared::result::Result;
 
 /// 仓库 DTO
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +18,11 @@ pub struct RepositoryDto {
     pub last_synced_at: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// active / missing（磁盘上的路径不存在）
+    pub status: String,
+    pub missing_since: Option<String>,
+    /// 根提交 OID，同一仓库的不同路径指纹相同
+    pub fingerprint: Option<String>,
 }
 
 impl From<Repository> for RepositoryDto {
@@ -25,6 +36,64 @@ impl From<Repository> for RepositoryDto {
             last_synced_at: repo.last_synced_at.map(|dt| dt.to_rfc3339()),
             created_at: repo.created_at.to_rfc3339(),
             updated_at: repo.updated_at.to_rfc3339(),
+            status: repo.status.as_str().to_string(),
+            missing_since: repo.missing_since.map(|dt| dt.to_rfc3339()),
+            fingerprint: repo.fingerprint,
+        }
+    }
+}
+
+/// 仓库索引状态 DTO（当前或最近一次索引）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexStatusDto {
+    pub repository_id: i64,
+    /// running / completed / failed / interrupted，从未索引过时为 pending
+    pub state: String,
+    pub branches_total: i64,
+    pub branches_done: i64,
+    /// 最后完成的分支及其索引到的分支头
+    pub last_branch: Option<String>,
+    pub last_tip_oid: Option<String>,
+    pub commits_indexed: i64,
+    pub error: Option<String>,
+    pub started_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+impl IndexStatusDto {
+    /// 尚未开始过索引的仓库
+    pub fn pending(repository_id: i64) -> Self {
+        Self {
+            repository_id,
+            state: "pending".to_string(),
+            branches_total: 0,
+            branches_done: 0,
+            last_branch: None,
+            last_tip_oid: None,
+            commits_indexed: 0,
+            error: None,
+            started_at: None,
+            updated_at: None,
+            finished_at: None,
+        }
+    }
+}
+
+impl From<IndexProgress> for IndexStatusDto {
+    fn from(progress: IndexProgress) -> Self {
+        Self {
+            repository_id: progress.repository_id,
+            state: progress.state.as_str().to_string(),
+            branches_total: progress.branches_total,
+            branches_done: progress.branches_done,
+            last_branch: progress.last_branch,
+            last_tip_oid: progress.last_tip_oid,
+            commits_indexed: progress.commits_indexed,
+            error: progress.error,
+            started_at: Some(progress.started_at.to_rfc3339()),
+            updated_at: Some(progress.updated_at.to_rfc3339()),
+            finished_at: progress.finished_at.map(|dt| dt.to_rfc3339()),
         }
     }
 }
@@ -42,13 +111,21 @@ pub struct CommitDto {
     pub committer_name: String,
     pub committer_email: String,
     pub committer_time: String,
+    /// 提交信息首行
     pub summary: String,
+    /// 完整提交信息（列表接口默认不返回）
     pub message: Option<String>,
     pub created_at: String,
+    pub files_changed: Option<i64>,
+    pub insertions: Option<i64>,
+    pub deletions: Option<i64>,
+    /// 根目录树 OID（较早索引的提交可能为空）
+    pub tree_oid: Option<String>,
 }
 
 impl From<Commit> for CommitDto {
     fn from(commit: Commit) -> Self {
+        let message = commit.full_message();
         Self {
             id: commit.id,
             repository_id: commit.repository_id,
@@ -61,8 +138,153 @@ impl From<Commit> for CommitDto {
             committer_email: commit.committer_email,
             committer_time: commit.committer_time.to_rfc3339(),
             summary: commit.summary,
-            message: commit.message,
+            message: Some(message),
             created_at: commit.created_at.to_rfc3339(),
+            files_changed: commit.files_changed,
+            insertions: commit.insertions,
+            deletions: commit.deletions,
+            tree_oid: commit.tree_oid,
+        }
+    }
+}
+
+/// 导出的提交（JSON Lines 每行一个），附带父提交以便还原提交图
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitExportDto {
+    #[serde(flatten)]
+    pub commit: CommitDto,
+    pub parent_oids: Vec<String>,
+}
+
+impl TryFrom<Commit> for CommitExportDto {
+    type Error = GitxError;
+
+    fn try_from(commit: Commit) -> Result<Self> {
+        let parent_oids = commit.parents()?;
+        Ok(Self {
+            commit: commit.into(),
+            parent_oids,
+        })
+    }
+}
+
+/// 最近浏览的提交 DTO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentCommitDto {
+    pub repository_id: i64,
+    pub repository_name: String,
+    pub oid: String,
+    pub summary: String,
+    pub viewed_at: String,
+}
+
+impl From<RecentCommit> for RecentCommitDto {
+    fn from(entry: RecentCommit) -> Self {
+        Self {
+            repository_id: entry.repo_id,
+            repository_name: entry.repo_name,
+            oid: entry.oid,
+            summary: entry.summary,
+            viewed_at: entry.viewed_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Git 提交 DTO（直接从 git 读取，未经过索引）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitCommitDto {
+    pub oid: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub author_time: String,
+    pub committer_name: String,
+    pub committer_email: String,
+    pub committer_time: String,
+    pub summary: String,
+    pub message: Option<String>,
+    pub parent_oids: Vec<String>,
+    pub tree_oid: String,
+}
+
+impl From<GitCommit> for GitCommitDto {
+    fn from(commit: GitCommit) -> Self {
+        let to_rfc3339 = |ts: i64| {
+            DateTime::from_timestamp(ts, 0)
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default()
+        };
+
+        Self {
+            oid: commit.oid,
+            author_name: commit.author_name,
+            author_email: commit.author_email,
+            author_time: to_rfc3339(commit.author_time),
+            committer_name: commit.committer_name,
+            committer_email: commit.committer_email,
+            committer_time: to_rfc3339(commit.committer_time),
+            summary: commit.summary,
+            message: commit.message,
+            parent_oids: commit.parent_oids,
+            tree_oid: commit.tree_oid,
+        }
+    }
+}
+
+/// 仓库统计 DTO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositoryStatsDto {
+    pub repository_id: i64,
+    pub total_commits: i64,
+    pub first_commit_time: Option<String>,
+    pub last_commit_time: Option<String>,
+    pub top_authors: Vec<AuthorStatDto>,
+    pub branches: Vec<BranchCommitCountDto>,
+    pub activity_by_day: Vec<DailyActivityDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorStatDto {
+    pub author_email: String,
+    pub author_name: String,
+    pub commits: i64,
+}
+
+impl From<AuthorStat> for AuthorStatDto {
+    fn from(stat: AuthorStat) -> Self {
+        Self {
+            author_email: stat.author_email,
+            author_name: stat.author_name,
+            commits: stat.commits,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchCommitCountDto {
+    pub branch: String,
+    pub commits: i64,
+}
+
+impl From<BranchCommitCount> for BranchCommitCountDto {
+    fn from(count: BranchCommitCount) -> Self {
+        Self {
+            branch: count.branch,
+            commits: count.commits,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyActivityDto {
+    pub day: String,
+    pub commits: i64,
+}
+
+impl From<DailyActivity> for DailyActivityDto {
+    fn from(activity: DailyActivity) -> Self {
+        Self {
+            day: activity.day,
+            commits: activity.commits,
         }
     }
 }
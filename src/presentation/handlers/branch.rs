@@ -1,10 +1,14 @@
 use axum::{
-    extract::{State, Path},
+    extract::{State, Path, Query},
     response::Json,
 };
+use std::collections::HashMap;
 use std::sync::Arc;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use crate::presentation::dto::CommitDto;
+use crate::presentation::handlers::repository::pick_default_branch;
 use crate::presentation::routes::AppContext;
+use crate::shared::error::GitxError;
 use crate::shared::result::Result;
 
 #[derive(Serialize)]
@@ -12,6 +16,8 @@ pub struct BranchDto {
     pub name: String,
     pub target_oid: String,
     pub is_head: bool,
+    /// 已索引的提交数，未索引的分支（如本地分支）为 null
+    pub commit_count: Option<i64>,
 }
 
 /// API: 列出仓库的分支
@@ -24,12 +30,20 @@ pub async fn api_list_branches(
         .await?
         .ok_or_else(|| crate::shared::error::GitxError::RepositoryNotFound(id.to_string()))?;
     
-    let repo_path = std::path::PathBuf::from(&repo.path);
+    let repo_path = ctx.repo_path(&repo)?;
     let branches = ctx.git_client.list_branches(&repo_path).await?;
+    // 提交数来自索引，可能滞后一个索引周期
+    let counts: HashMap<String, i64> = ctx.branch_store
+        .find_by_repository(repo.id)
+        .await?
+        .into_iter()
+        .map(|b| (b.name, b.commit_count))
+        .collect();
     
     let dtos: Vec<BranchDto> = branches
         .into_iter()
         .map(|b| BranchDto {
+            commit_count: counts.get(&b.name).copied(),
             name: b.name,
             target_oid: b.target_oid,
             is_head: b.is_head,
@@ -38,3 +52,52 @@ pub async fn api_list_branches(
     
     Ok(Json(dtos))
 }
+
+#[derive(Deserialize)]
+pub struct CompareQuery {
+    /// 源分支（已索引的名称，如 origin/staging）
+    pub old: String,
+    /// 目标分支，省略时使用仓库默认分支
+    pub new: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct CompareDto {
+    pub old: String,
+    pub new: String,
+    /// 返回的提交数（受 limit 限制）
+    pub count: usize,
+    pub commits: Vec<CommitDto>,
+}
+
+/// API: 对比两个分支，返回在 old 中但不在 new 中的提交（`/diff-beta` 的 JSON 版本）
+pub async fn api_compare_branches(
+    State(ctx): State<Arc<AppContext>>,
+    Path(id): Path<i64>,
+    Query(query): Query<CompareQuery>,
+) -> Result<Json<CompareDto>> {
+    let repo = ctx.repository_store
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| GitxError::RepositoryNotFound(id.to_string()))?;
+
+    let branches = ctx.branch_store.find_by_repository(repo.id).await?;
+    let new = query.new.unwrap_or_else(|| pick_default_branch(&repo, &branches));
+    for name in [&query.old, &new] {
+        if !branches.iter().any(|b| &b.name == name) {
+            return Err(GitxError::ReferenceNotFound(name.clone()));
+        }
+    }
+
+    let commits = ctx.commit_store
+        .find_diff_commits(repo.id, &query.old, &new, query.limit.unwrap_or(1000).clamp(1, 10_000))
+        .await?;
+
+    Ok(Json(CompareDto {
+        count: commits.len(),
+        commits: commits.into_iter().map(Into::into).collect(),
+        old: query.old,
+        new,
+    }))
+}
@@ -1,18 +1,59 @@
 use axum::{
+    body::Body,
     extract::{State, Path, Query},
-    response::Json,
+    http::header,
+    response::{IntoResponse, Json, Response},
 };
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use serde::Deserialize;
+use futures::stream::{self, StreamExt};
+use tokio::sync::mpsc;
+use chrono::{DateTime, NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use crate::domain::entities::Commit;
+use crate::domain::value_objects::CommitSha;
+use crate::presentation::handlers::repository::{commit_detail_cached, resolve_commit_id};
+use crate::ports::commit::{CommitFilter, CommitOrder, CommitSort, SortDirection};
 use crate::presentation::routes::AppContext;
-use crate::presentation::dto::CommitDto;
+use crate::presentation::dto::{CommitDto, CommitExportDto, GitCommitDto, RecentCommitDto};
+use crate::presentation::recent::RECENT_COMMITS_CAPACITY;
+use crate::services::graph::assign_lanes;
+use crate::shared::error::GitxError;
 use crate::shared::result::Result;
 
 #[derive(Deserialize)]
 pub struct ListCommitsQuery {
     pub branch: Option<String>,
+    /// 按作者名或邮箱过滤（子串，不区分大小写）
+    pub author: Option<String>,
+    /// 起始时间（包含），RFC3339 或 `YYYY-MM-DD`
+    pub since: Option<String>,
+    /// 截止时间（不包含），RFC3339 或 `YYYY-MM-DD`
+    pub until: Option<String>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    /// 排序字段：author_time（默认）| committer_time
+    #[serde(default)]
+    pub sort: CommitSort,
+    /// 排序方向：desc（默认）| asc
+    #[serde(default)]
+    pub dir: SortDirection,
+    /// 是否返回完整提交信息，默认只返回 summary 以减小列表体积
+    #[serde(default)]
+    pub include_body: bool,
+}
+
+/// 解析时间过滤参数（RFC3339 或 `YYYY-MM-DD`，后者按 UTC 零点），返回 Unix 秒；空值视为未设置
+pub(crate) fn parse_time_param(name: &str, value: Option<&str>) -> Result<Option<i64>> {
+    let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Ok(None);
+    };
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(Some(time.timestamp()));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| Some(date.and_time(NaiveTime::MIN).and_utc().timestamp()))
+        .map_err(|_| GitxError::InvalidRequest(format!("Invalid {} date: {} (expected RFC3339 or YYYY-MM-DD)", name, value)))
 }
 
 /// API: 列出仓库的提交
@@ -21,27 +62,425 @@ pub async fn api_list_commits(
     Path(id): Path<i64>,
     Query(query): Query<ListCommitsQuery>,
 ) -> Result<Json<Vec<CommitDto>>> {
+    Ok(Json(list_commits(&ctx, id, query.branch.as_deref(), &query).await?))
+}
+
+/// API: 列出某分支的提交，分支名可省略远程前缀（`main` 对应 `origin/main`），含 `/` 时需编码为 `%2F`
+pub async fn api_list_branch_commits(
+    State(ctx): State<Arc<AppContext>>,
+    Path((id, name)): Path<(i64, String)>,
+    Query(query): Query<ListCommitsQuery>,
+) -> Result<Json<Vec<CommitDto>>> {
+    let repo = ctx.repository_store
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| GitxError::RepositoryNotFound(id.to_string()))?;
+
+    // 裸仓库按本地分支名存储，其余按 <remote>/<branch> 存储；完全匹配优先
+    let branches = ctx.branch_store.find_by_repository(repo.id).await?;
+    let remote_name = format!("{}/{}", ctx.config.load().remote_for_path(&ctx.repo_path(&repo)?), name);
+    let branch = branches
+        .iter()
+        .find(|b| b.name == name)
+        .or_else(|| branches.iter().find(|b| b.name == remote_name))
+        .ok_or(GitxError::ReferenceNotFound(name))?;
+
+    Ok(Json(list_commits(&ctx, repo.id, Some(&branch.name), &query).await?))
+}
+
+/// 按查询参数列出提交（`branch` 为已索引的分支名）
+async fn list_commits(
+    ctx: &AppContext,
+    id: i64,
+    branch: Option<&str>,
+    query: &ListCommitsQuery,
+) -> Result<Vec<CommitDto>> {
+    let filter = CommitFilter {
+        branch,
+        author: query.author.as_deref().filter(|a| !a.is_empty()),
+        since: parse_time_param("since", query.since.as_deref())?,
+        until: parse_time_param("until", query.until.as_deref())?,
+    };
     let commits = ctx.commit_store.list_by_repository(
         id,
-        query.branch.as_deref(),
+        &filter,
+        CommitOrder { sort: query.sort, direction: query.dir },
         query.limit.unwrap_or(100),
         query.offset.unwrap_or(0),
     ).await?;
     
-    let dtos: Vec<CommitDto> = commits.into_iter().map(Into::into).collect();
+    let dtos: Vec<CommitDto> = commits
+        .into_iter()
+        .map(|commit| {
+            let mut dto = CommitDto::from(commit);
+            if !query.include_body {
+                dto.message = None;
+            }
+            dto
+        })
+        .collect();
     
-    Ok(Json(dtos))
+    Ok(dtos)
+}
+
+#[derive(Deserialize)]
+pub struct GraphQuery {
+    /// 分支名，为空时合并所有分支（同一提交只出现一次）
+    pub branch: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct GraphNodeDto {
+    pub oid: String,
+    pub summary: String,
+    pub author_name: String,
+    pub committer_time: String,
+    pub parent_oids: Vec<String>,
+    /// 绘制列，从 0 开始
+    pub lane: usize,
+}
+
+/// API: 提交图（按 committer_time 倒序，附带绘制列）
+pub async fn api_commit_graph(
+    State(ctx): State<Arc<AppContext>>,
+    Path(id): Path<i64>,
+    Query(query): Query<GraphQuery>,
+) -> Result<Json<Vec<GraphNodeDto>>> {
+    let repo = ctx.repository_store
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| GitxError::RepositoryNotFound(id.to_string()))?;
+
+    let filter = CommitFilter::branch(query.branch.as_deref().filter(|b| !b.is_empty()));
+    let commits = ctx.commit_store
+        .list_by_repository_keyset(repo.id, &filter, None, None, query.limit.unwrap_or(200).clamp(1, 5_000))
+        .await?;
+
+    let mut seen = HashSet::new();
+    let commits: Vec<Commit> = commits.into_iter().filter(|c| seen.insert(c.oid.clone())).collect();
+
+    let edges = commits
+        .iter()
+        .map(|c| Ok((c.oid.clone(), c.parents()?)))
+        .collect::<Result<Vec<(String, Vec<String>)>>>()?;
+    let lanes = assign_lanes(&edges);
+
+    let nodes = commits
+        .into_iter()
+        .zip(edges.into_iter().zip(lanes))
+        .map(|(c, ((_, parent_oids), lane))| GraphNodeDto {
+            oid: c.oid,
+            summary: c.summary,
+            author_name: c.author_name,
+            committer_time: c.committer_time.to_rfc3339(),
+            parent_oids,
+            lane,
+        })
+        .collect();
+
+    Ok(Json(nodes))
 }
 
-/// API: 获取单个提交详情
+/// 导出时后台读取与响应发送之间最多缓冲的行数
+const EXPORT_BUFFER_LINES: usize = 256;
+
+/// 导出文件中的一行（含换行符）
+fn export_line(commit: Commit) -> Result<Vec<u8>> {
+    let mut line = serde_json::to_vec(&CommitExportDto::try_from(commit)?)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+/// API: 以 JSON Lines 导出仓库的所有提交（分批读取数据库并逐行流式发送，不缓冲整个结果集）
+pub async fn api_export_commits(
+    State(ctx): State<Arc<AppContext>>,
+    Path(id): Path<i64>,
+) -> Result<Response> {
+    let repo = ctx.repository_store
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| GitxError::RepositoryNotFound(id.to_string()))?;
+
+    // 有界通道提供背压：客户端读得慢时暂停读取数据库
+    let (tx, rx) = mpsc::channel::<Result<Vec<u8>>>(EXPORT_BUFFER_LINES);
+    let commit_store = ctx.commit_store.clone();
+    tokio::spawn(async move {
+        let mut commits = commit_store.stream_by_repository(repo.id);
+        while let Some(commit) = commits.next().await {
+            let line = commit.and_then(export_line);
+            let failed = line.is_err();
+            if let Err(e) = &line {
+                tracing::error!("Commit export for repository {} failed: {}", repo.id, e);
+            }
+            // 客户端断开或出错时停止读取
+            if tx.send(line).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    // 出错时以错误结束响应体，客户端能发现导出不完整
+    let body = stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|line| (line, rx)) });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}.jsonl\"", repo.name.replace('"', ""))),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response())
+}
+
+/// API: 获取单个提交详情，`{oid}.patch` / `{oid}.diff` 下载补丁
 pub async fn api_get_commit(
     State(ctx): State<Arc<AppContext>>,
     Path((repo_id, oid)): Path<(i64, String)>,
-) -> Result<Json<CommitDto>> {
-    let commit = ctx.commit_store
-        .find_by_oid(repo_id, &oid)
+) -> Result<Response> {
+    if let Some(oid) = oid.strip_suffix(".patch") {
+        return commit_patch(&ctx, repo_id, oid, PatchFormat::Patch).await;
+    }
+    if let Some(oid) = oid.strip_suffix(".diff") {
+        return commit_patch(&ctx, repo_id, oid, PatchFormat::Diff).await;
+    }
+
+    // 缩写 SHA 只在数据库中按前缀查找
+    let sha = CommitSha::new_abbrev(oid.clone()).map_err(GitxError::InvalidOid)?;
+    let commit = match sha.is_full() {
+        true => ctx.commit_store.find_by_oid(repo_id, sha.as_str()).await?,
+        false => ctx.commit_store.find_by_oid_prefix(repo_id, sha.as_str()).await?,
+    }
+    .ok_or_else(|| GitxError::CommitNotFound(oid))?;
+    
+    Ok(Json(CommitDto::from(commit)).into_response())
+}
+
+/// 批量查询一次最多请求的 OID 数
+const BATCH_GET_MAX_OIDS: usize = 200;
+
+#[derive(Deserialize)]
+pub struct BatchGetCommitsRequest {
+    /// 完整的 40 位 OID
+    pub oids: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct BatchGetCommitsResponse {
+    /// 找到的提交，按请求顺序排列
+    pub commits: Vec<CommitDto>,
+    /// 未索引的 OID
+    pub missing: Vec<String>,
+}
+
+/// API: 按 OID 批量获取提交，避免逐个请求 `/commits/{oid}`
+pub async fn api_batch_get_commits(
+    State(ctx): State<Arc<AppContext>>,
+    Path(repo_id): Path<i64>,
+    Json(req): Json<BatchGetCommitsRequest>,
+) -> Result<Json<BatchGetCommitsResponse>> {
+    if req.oids.len() > BATCH_GET_MAX_OIDS {
+        return Err(GitxError::InvalidRequest(format!("at most {} OIDs per request", BATCH_GET_MAX_OIDS)));
+    }
+    ctx.repository_store
+        .find_by_id(repo_id)
+        .await?
+        .ok_or_else(|| GitxError::RepositoryNotFound(repo_id.to_string()))?;
+
+    let mut seen = HashSet::new();
+    let mut oids = Vec::with_capacity(req.oids.len());
+    for oid in req.oids {
+        let sha = CommitSha::new(oid.to_ascii_lowercase()).map_err(GitxError::InvalidOid)?;
+        if seen.insert(sha.as_str().to_string()) {
+            oids.push(sha.as_str().to_string());
+        }
+    }
+
+    let mut found: HashMap<String, Commit> = ctx.commit_store
+        .find_by_oids(repo_id, &oids)
+        .await?
+        .into_iter()
+        .map(|c| (c.oid.clone(), c))
+        .collect();
+
+    let mut commits = Vec::with_capacity(found.len());
+    let mut missing = Vec::new();
+    for oid in oids {
+        match found.remove(&oid) {
+            Some(commit) => commits.push(CommitDto::from(commit)),
+            None => missing.push(oid),
+        }
+    }
+
+    Ok(Json(BatchGetCommitsResponse { commits, missing }))
+}
+
+#[derive(Deserialize)]
+pub struct RecentCommitsQuery {
+    pub limit: Option<usize>,
+}
+
+/// API: 最近浏览过的提交（跨仓库，同一提交只出现一次，最新的在前）
+pub async fn api_recent_commits(
+    State(ctx): State<Arc<AppContext>>,
+    Query(query): Query<RecentCommitsQuery>,
+) -> Json<Vec<RecentCommitDto>> {
+    let limit = query.limit.unwrap_or(20).clamp(1, RECENT_COMMITS_CAPACITY);
+    Json(ctx.recent_commits.recent(limit).into_iter().map(RecentCommitDto::from).collect())
+}
+
+#[derive(Clone, Copy)]
+enum PatchFormat {
+    /// `git format-patch` 格式（From/Subject 头 + diff），可用于 `git am`
+    Patch,
+    /// 纯 diff，可用于 `git apply`
+    Diff,
+}
+
+/// 下载提交补丁，支持缩写 SHA
+async fn commit_patch(ctx: &AppContext, repo_id: i64, oid: &str, format: PatchFormat) -> Result<Response> {
+    let repo = ctx.repository_store
+        .find_by_id(repo_id)
+        .await?
+        .ok_or_else(|| GitxError::RepositoryNotFound(repo_id.to_string()))?;
+    let repo_path = ctx.repo_path(&repo)?;
+
+    let sha = CommitSha::new_abbrev(oid.to_string()).map_err(GitxError::InvalidOid)?;
+    let commit_id = resolve_commit_id(ctx, repo.id, &repo_path, &sha).await?;
+
+    let (body, content_type, extension) = match format {
+        PatchFormat::Patch => (ctx.git_client.format_patch(&repo_path, &commit_id).await?, "text/x-patch", "patch"),
+        PatchFormat::Diff => {
+            let detail = commit_detail_cached(ctx, repo.id, &repo_path, &commit_id, None).await?;
+            (detail.diff_plain, "text/x-diff", "diff")
+        }
+    };
+    let disposition = format!("attachment; filename=\"{}.{}\"", &commit_id[..7], extension);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+#[derive(Deserialize)]
+pub struct FileHistoryQuery {
+    pub path: String,
+    pub rev: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// 文件历史单次最多返回的提交数，遍历历史是阻塞 git 操作，不能由请求任意放大
+const MAX_FILE_HISTORY: usize = 1_000;
+
+/// 文件历史的返回数量，默认 100
+fn file_history_limit(limit: Option<usize>) -> usize {
+    limit.unwrap_or(100).clamp(1, MAX_FILE_HISTORY)
+}
+
+/// API: 获取文件的提交历史
+pub async fn api_file_history(
+    State(ctx): State<Arc<AppContext>>,
+    Path(id): Path<i64>,
+    Query(query): Query<FileHistoryQuery>,
+) -> Result<Json<Vec<GitCommitDto>>> {
+    let repo = ctx.repository_store
+        .find_by_id(id)
         .await?
-        .ok_or_else(|| crate::shared::error::GitxError::CommitNotFound(oid))?;
+        .ok_or_else(|| crate::shared::error::GitxError::RepositoryNotFound(id.to_string()))?;
     
-    Ok(Json(commit.into()))
+    let repo_path = ctx.repo_path(&repo)?;
+    let commits = ctx.git_client.file_history(
+        &repo_path,
+        query.rev.as_deref().unwrap_or("HEAD"),
+        &query.path,
+        file_history_limit(query.limit),
+    ).await?;
+    
+    Ok(Json(commits.into_iter().map(Into::into).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_param() {
+        assert_eq!(parse_time_param("since", None).unwrap(), None);
+        assert_eq!(parse_time_param("since", Some(" ")).unwrap(), None);
+        assert_eq!(parse_time_param("since", Some("2024-01-01")).unwrap(), Some(1_704_067_200));
+        assert_eq!(parse_time_param("until", Some("2024-01-01T08:00:00+08:00")).unwrap(), Some(1_704_067_200));
+        assert!(parse_time_param("until", Some("2024-13-01")).is_err());
+        assert!(parse_time_param("until", Some("yesterday")).is_err());
+    }
+
+    #[test]
+    fn test_file_history_limit() {
+        assert_eq!(file_history_limit(None), 100);
+        assert_eq!(file_history_limit(Some(0)), 1);
+        assert_eq!(file_history_limit(Some(50)), 50);
+        assert_eq!(file_history_limit(Some(usize::MAX)), MAX_FILE_HISTORY);
+    }
+
+    #[tokio::test]
+    async fn test_export_round_trip() {
+        use crate::infrastructure::sqlite::commit_repo::SqliteCommitRepository;
+        use crate::infrastructure::sqlite::run_migrations;
+        use crate::ports::commit::CommitPort;
+        use sqlx::sqlite::SqlitePoolOptions;
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        sqlx::query("INSERT INTO repositories (id, name, path, created_at, updated_at) VALUES (1, 'r', '/tmp/r', 0, 0)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let store = SqliteCommitRepository::new(pool);
+
+        let time = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let commit = |oid: &str, parents: &[&str]| {
+            Commit::new(
+                1,
+                oid.to_string(),
+                "origin/main".to_string(),
+                "dev".to_string(),
+                "dev@example.com".to_string(),
+                time,
+                "dev".to_string(),
+                "dev@example.com".to_string(),
+                time,
+                format!("commit {}", oid),
+            )
+            .with_parents(parents.iter().map(|p| p.to_string()).collect())
+        };
+        let (root, side, merge) = ("a".repeat(40), "b".repeat(40), "c".repeat(40));
+        let commits = vec![commit(&root, &[]), commit(&side, &[&root]), commit(&merge, &[&root, &side])];
+        store.bulk_insert(&commits).await.unwrap();
+
+        let mut exported = Vec::new();
+        let mut rows = store.stream_by_repository(1);
+        while let Some(commit) = rows.next().await {
+            exported.extend(export_line(commit.unwrap()).unwrap());
+        }
+        let lines: Vec<CommitExportDto> = String::from_utf8(exported)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), commits.len());
+        for (line, commit) in lines.iter().zip(&commits) {
+            assert_eq!(line.commit.oid, commit.oid);
+            assert_eq!(line.commit.summary, commit.summary);
+            assert_eq!(line.parent_oids, commit.parents().unwrap());
+        }
+    }
 }
@@ -0,0 +1,42 @@
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::Json,
+};
+use std::sync::Arc;
+use serde::Serialize;
+use crate::presentation::routes::AppContext;
+use crate::shared::config::Config;
+use crate::shared::result::Result;
+
+/// 配置热加载结果
+#[derive(Serialize)]
+pub struct ReloadConfigResponse {
+    pub reloaded: bool,
+    pub projects: usize,
+    pub interval_secs: u64,
+}
+
+/// API: 重新加载 config.toml（校验失败时保留当前配置），需要管理令牌
+pub async fn api_reload_config(
+    State(ctx): State<Arc<AppContext>>,
+    headers: HeaderMap,
+) -> Result<Json<ReloadConfigResponse>> {
+    ctx.require_admin(&headers)?;
+    let config = ctx.config.reload()?;
+
+    Ok(Json(ReloadConfigResponse {
+        reloaded: true,
+        projects: config.projects.len(),
+        interval_secs: config.indexer.interval_secs,
+    }))
+}
+
+/// API: 当前生效的配置（配置文件、环境变量与命令行合并后的结果），敏感项已脱敏，需要管理令牌
+pub async fn api_get_config(
+    State(ctx): State<Arc<AppContext>>,
+    headers: HeaderMap,
+) -> Result<Json<Config>> {
+    ctx.require_admin(&headers)?;
+    Ok(Json(ctx.config.load().redacted()))
+}
@@ -0,0 +1,190 @@
+use axum::{
+    extract::{State, Path, Query},
+    http::header,
+    response::{IntoResponse, Json},
+};
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use crate::domain::value_objects::CommitSha;
+use crate::ports::git::{GitFileDiff, GitTreeEntry, MAX_CONTEXT_LINES};
+use crate::presentation::handlers::repository::resolve_commit_id;
+use crate::presentation::routes::AppContext;
+use crate::shared::error::GitxError;
+use crate::shared::result::Result;
+
+#[derive(Deserialize)]
+pub struct BlameQuery {
+    pub path: String,
+    pub rev: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BlameLineDto {
+    pub line_no: usize,
+    pub oid: String,
+    pub author_name: String,
+    pub content: String,
+}
+
+/// API: 获取文件的逐行 blame（二进制或超大文件返回 400）
+pub async fn api_blame(
+    State(ctx): State<Arc<AppContext>>,
+    Path(id): Path<i64>,
+    Query(query): Query<BlameQuery>,
+) -> Result<Json<Vec<BlameLineDto>>> {
+    let repo = ctx.repository_store
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| crate::shared::error::GitxError::RepositoryNotFound(id.to_string()))?;
+    
+    let repo_path = ctx.repo_path(&repo)?;
+    let lines = ctx.git_client
+        .blame(&repo_path, query.rev.as_deref().unwrap_or("HEAD"), &query.path)
+        .await?;
+    
+    let dtos: Vec<BlameLineDto> = lines
+        .into_iter()
+        .map(|l| BlameLineDto {
+            line_no: l.line_no,
+            oid: l.oid,
+            author_name: l.author_name,
+            content: l.content,
+        })
+        .collect();
+    
+    Ok(Json(dtos))
+}
+
+#[derive(Deserialize)]
+pub struct FileDiffQuery {
+    /// 提交 SHA，支持缩写
+    pub oid: String,
+    pub path: String,
+    pub context: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct FileDiffDto {
+    /// 完整的提交 OID
+    pub oid: String,
+    #[serde(flatten)]
+    pub diff: GitFileDiff,
+}
+
+/// API: 提交中单个文件的结构化 diff，供页面展开文件时按需加载（文件未被修改时返回 404）
+pub async fn api_file_diff(
+    State(ctx): State<Arc<AppContext>>,
+    Path(id): Path<i64>,
+    Query(query): Query<FileDiffQuery>,
+) -> Result<Json<FileDiffDto>> {
+    let repo = ctx.repository_store
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| GitxError::RepositoryNotFound(id.to_string()))?;
+    if query.path.is_empty() {
+        return Err(GitxError::InvalidRequest("path must not be empty".to_string()));
+    }
+
+    let repo_path = ctx.repo_path(&repo)?;
+    let sha = CommitSha::new_abbrev(query.oid).map_err(GitxError::InvalidOid)?;
+    let commit_id = resolve_commit_id(&ctx, repo.id, &repo_path, &sha).await?;
+    let context_lines = query.context.map(|n| n.min(MAX_CONTEXT_LINES));
+
+    let diff = ctx.git_client
+        .file_diff(&repo_path, &commit_id, &query.path, context_lines)
+        .await?
+        .ok_or_else(|| GitxError::FileNotFound(format!("{}:{}", &commit_id[..7], query.path)))?;
+
+    Ok(Json(FileDiffDto { oid: commit_id, diff }))
+}
+
+#[derive(Deserialize)]
+pub struct TreeQuery {
+    pub rev: Option<String>,
+    /// 目录路径，省略时为根目录
+    #[serde(default)]
+    pub path: String,
+}
+
+/// API: 列出指定版本下目录的直接子项，子模块带固定的提交 OID 与 URL
+pub async fn api_tree(
+    State(ctx): State<Arc<AppContext>>,
+    Path(id): Path<i64>,
+    Query(query): Query<TreeQuery>,
+) -> Result<Json<Vec<GitTreeEntry>>> {
+    let repo = ctx.repository_store
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| GitxError::RepositoryNotFound(id.to_string()))?;
+
+    let repo_path = ctx.repo_path(&repo)?;
+    let entries = ctx.git_client
+        .list_tree(&repo_path, query.rev.as_deref().unwrap_or("HEAD"), query.path.trim_matches('/'))
+        .await?;
+
+    Ok(Json(entries))
+}
+
+/// 按扩展名推测 raw 文件的 content-type
+///
+/// HTML 等可执行脚本的文本一律按纯文本返回，避免在本站域名下渲染
+fn guess_content_type(file_path: &str, is_binary: bool) -> &'static str {
+    let ext = std::path::Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        _ if is_binary => "application/octet-stream",
+        "json" => "application/json; charset=utf-8",
+        _ => "text/plain; charset=utf-8",
+    }
+}
+
+/// 原始文件：`/{repo}/raw/{rev}/{*path}`（rev 不能包含 `/`，带斜杠的分支名请使用提交 SHA）
+pub async fn repo_raw_file(
+    State(ctx): State<Arc<AppContext>>,
+    Path((repo_name, rev, file_path)): Path<(String, String, String)>,
+) -> Result<impl IntoResponse> {
+    let repo = ctx.find_repo_by_name(&repo_name).await?;
+    let repo_path = ctx.repo_path(&repo)?;
+    let blob = ctx.git_client.read_blob(&repo_path, &rev, &file_path).await?;
+
+    let content_type = guess_content_type(&file_path, blob.is_binary);
+    let file_name = file_path.rsplit('/').next().unwrap_or(&file_path).replace('"', "");
+    let disposition = format!("inline; filename=\"{}\"", file_name);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            // SVG 等可能内嵌脚本，禁止执行
+            (header::CONTENT_SECURITY_POLICY, "default-src 'none'; style-src 'unsafe-inline'; sandbox".to_string()),
+        ],
+        blob.content,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guess_content_type() {
+        assert_eq!(guess_content_type("docs/logo.PNG", true), "image/png");
+        assert_eq!(guess_content_type("src/main.rs", false), "text/plain; charset=utf-8");
+        assert_eq!(guess_content_type("index.html", false), "text/plain; charset=utf-8");
+        assert_eq!(guess_content_type("build/app", true), "application/octet-stream");
+        assert_eq!(guess_content_type("Makefile", false), "text/plain; charset=utf-8");
+    }
+}
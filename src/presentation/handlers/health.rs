@@ -0,0 +1,50 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+#[derive(Serialize)]
+pub struct HealthResponse {
+    status: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+pub struct ReadyResponse {
+    status: &'static str,
+    error: Option<String>,
+}
+
+/// 存活探针：进程在运行即返回 200
+pub async fn healthz() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok",
+        version: crate::VERSION,
+    })
+}
+
+/// 就绪探针：检查数据库连接，失败时返回 503
+pub async fn readyz(State(pool): State<SqlitePool>) -> impl IntoResponse {
+    match sqlx::query("SELECT 1").execute(&pool).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(ReadyResponse {
+                status: "ready",
+                error: None,
+            }),
+        ),
+        Err(e) => {
+            tracing::warn!("Readiness check failed: {}", e);
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ReadyResponse {
+                    status: "unavailable",
+                    error: Some(e.to_string()),
+                }),
+            )
+        }
+    }
+}
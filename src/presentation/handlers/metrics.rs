@@ -0,0 +1,26 @@
+use axum::{
+    extract::State,
+    http::header,
+    response::IntoResponse,
+};
+use std::sync::Arc;
+use crate::presentation::routes::AppContext;
+use crate::ports::commit::CommitFilter;
+use crate::shared::result::Result;
+
+/// Prometheus 指标
+pub async fn metrics(
+    State(ctx): State<Arc<AppContext>>,
+) -> Result<impl IntoResponse> {
+    let repos = ctx.repository_store.list_all().await?;
+
+    let mut repo_commits = Vec::with_capacity(repos.len());
+    for repo in &repos {
+        let count = ctx.commit_store.count_by_repository(repo.id, &CommitFilter::default()).await?;
+        repo_commits.push((repo.name.clone(), count));
+    }
+
+    let body = ctx.metrics.render(repos.len(), &repo_commits);
+
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body))
+}
@@ -1,3 +1,10 @@
 pub mod repository;
 pub mod commit;
 pub mod branch;
+pub mod tag;
+pub mod health;
+pub mod webhook;
+pub mod metrics;
+pub mod file;
+pub mod stats;
+pub mod config;
@@ -1,25 +1,56 @@
 use askama::Template;
 use axum::{
     extract::{State, Path, Query},
-    response::{Html, IntoResponse, Json},
+    response::{Html, IntoResponse, Json, Response},
+    http::{header, HeaderMap, StatusCode},
     debug_handler,
 };
 use std::sync::Arc;
 use std::fmt;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 use serde::{Serialize, Deserialize, de::{self, Deserializer, Visitor, SeqAccess}};
 use tokio::process::Command;
+use chrono::DateTime;
+use crate::domain::entities::{Branch, Commit, Repository, FALLBACK_DEFAULT_BRANCH};
+use crate::domain::value_objects::CommitSha;
 use crate::presentation::routes::AppContext;
-use crate::presentation::dto::RepositoryDto;
+use crate::ports::cache::CachePort;
+use crate::ports::commit::{CommitFilter, CommitOrder};
+use crate::ports::git::{GitCommit, GitCommitDetail, GitDiff, DEFAULT_CONTEXT_LINES, MAX_CONTEXT_LINES};
+use crate::presentation::dto::{IndexStatusDto, RepositoryDto};
+use crate::presentation::handlers::commit::parse_time_param;
+use crate::presentation::linkify::linkify;
 use crate::presentation::templates::*;
+use crate::shared::config::Config;
 use crate::shared::result::Result;
-use crate::services::worker::IndexWorker;
+use crate::services::discovery::DiscoveredRepo;
+use crate::services::worker::{IndexResult, IndexWorker};
 
-/// 列出所有仓库（Web UI）- 使用模板
+/// 仓库列表每页条数
+const REPOS_PER_PAGE: i64 = 50;
+
+#[derive(Deserialize)]
+pub struct ListRepositoriesQuery {
+    /// 按名称搜索（子串，不区分大小写）
+    q: Option<String>,
+    /// 页码，从 1 开始
+    page: Option<i64>,
+}
+
+/// 列出仓库（Web UI）- 支持搜索与分页
 pub async fn list_repositories(
     State(ctx): State<Arc<AppContext>>,
+    Query(query): Query<ListRepositoriesQuery>,
 ) -> Result<impl IntoResponse> {
-    let repos = ctx.repository_store.list_all().await?;
+    let q = query.q.as_deref().map(str::trim).unwrap_or_default().to_string();
+    let page = query.page.unwrap_or(1).max(1);
+    let offset = (page - 1)
+        .checked_mul(REPOS_PER_PAGE)
+        .ok_or_else(|| crate::shared::error::GitxError::InvalidRequest(format!("page out of range: {}", page)))?;
+    let (repos, total) = ctx.repository_store
+        .list_paged(REPOS_PER_PAGE, offset, (!q.is_empty()).then_some(q.as_str()))
+        .await?;
     
     let repo_items: Vec<RepoItem> = repos
         .iter()
@@ -30,52 +61,121 @@ pub async fn list_repositories(
             last_modified: r.last_synced_at
                 .unwrap_or(r.created_at)
                 .to_rfc3339(),
+            missing: r.is_missing(),
         })
         .collect();
     
     let template = IndexTemplate {
+        layout: ctx.layout(),
         repositories: repo_items,
+        query: q,
+        page,
+        total_pages: ((total + REPOS_PER_PAGE - 1) / REPOS_PER_PAGE).max(1),
+        total,
     };
     
     Ok(Html(template.render()?))
 }
 
+/// 千位分隔，如 1204 -> "1,204"
+fn thousands(n: i64) -> String {
+    let digits = n.unsigned_abs().to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    if n < 0 {
+        out.push('-');
+    }
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
 /// UI: 仓库概览页 - 使用模板
 pub async fn repo_summary(
     State(ctx): State<Arc<AppContext>>,
     Path(repo_name): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let repo = ctx.repository_store
-        .find_by_name(&repo_name)
-        .await?
-        .ok_or_else(|| crate::shared::error::GitxError::RepositoryNotFound(repo_name.clone()))?;
-    
-    let repo_path = std::path::PathBuf::from(&repo.path);
+    let repo = ctx.find_repo_by_name(&repo_name).await?;
     
-    // 获取分支列表
-    let branches = ctx.git_client.list_branches(&repo_path).await?;
+    // 从数据库读取已索引的分支，避免每次打开 git 仓库
+    let branches = ctx.branch_store
+        .find_by_repository(repo.id)
+        .await?;
     
-    let branch_items: Vec<BranchItem> = branches
+    // 用分支的 target_oid 一次查询 commits 表，填充最新提交信息
+    let tip_oids: Vec<String> = branches
         .iter()
-        .map(|b| BranchItem {
-            name: b.name.clone(),
-            commit_sha: b.target_oid.clone(),
-            commit_message: "".to_string(),
-            author: "".to_string(),
-            time: "".to_string(),
-        })
+        .map(|b| b.target_oid.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
         .collect();
+    let tips: HashMap<String, Commit> = ctx.commit_store
+        .find_by_oids(repo.id, &tip_oids)
+        .await?
+        .into_iter()
+        .map(|c| (c.oid.clone(), c))
+        .collect();
+
+    let mut branch_items: Vec<BranchItem> = Vec::with_capacity(branches.len());
+    for b in &branches {
+        branch_items.push(match tips.get(&b.target_oid) {
+            Some(c) => BranchItem {
+                name: b.name.clone(),
+                commit_count: thousands(b.commit_count),
+                commit_sha: b.target_oid.clone(),
+                commit_message: c.summary.clone(),
+                author: c.author_name.clone(),
+                time: c.committer_time.to_rfc3339(),
+            },
+            // 分支顶端提交尚未索引（例如合并提交）
+            None => BranchItem {
+                name: b.name.clone(),
+                commit_count: thousands(b.commit_count),
+                commit_sha: b.target_oid.clone(),
+                commit_message: "".to_string(),
+                author: "".to_string(),
+                time: "".to_string(),
+            },
+        });
+    }
     
     let all_branches: Vec<String> = branches
         .iter()
         .map(|b| b.name.clone())
         .collect();
     
+    // 标签未入库，直接从 git 读取（已按时间排序）；读取失败时只隐藏标签，页面照常显示
+    let tags = match ctx.repo_path(&repo) {
+        Ok(repo_path) => ctx.git_client.list_tags(&repo_path).await,
+        Err(e) => Err(e),
+    };
+    let tags = tags.unwrap_or_else(|e| {
+        tracing::warn!("Failed to list tags for {}: {}", repo.name, e);
+        Vec::new()
+    });
+    let tag_items: Vec<TagItem> = tags
+        .into_iter()
+        .map(|t| TagItem {
+            name: t.name,
+            commit_sha: t.target_oid,
+            message: t.message.as_deref().and_then(|m| m.lines().next()).unwrap_or("").to_string(),
+            tagger: t.tagger_name.unwrap_or_default(),
+            time: t.tagger_time
+                .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default(),
+        })
+        .collect();
 
     let template = SummaryTemplate {
+        layout: ctx.layout(),
         repo_name: repo_name.clone(),
         repo_path: repo.path.clone(),
         branches: branch_items,
+        tags: tag_items,
         all_branches,
     };
     
@@ -86,7 +186,29 @@ pub async fn repo_summary(
 #[derive(Deserialize)]
 pub struct LogQuery {
     br: Option<String>,
+    /// 按作者名或邮箱过滤（子串，不区分大小写）
+    author: Option<String>,
+    /// 起始日期（包含），RFC3339 或 `YYYY-MM-DD`
+    since: Option<String>,
+    /// 截止日期（不包含），RFC3339 或 `YYYY-MM-DD`
+    until: Option<String>,
+    /// 旧版 offset 分页参数，保留以兼容已有链接；与 cursor 同时出现时仅表示已翻过的提交数
     ofs: Option<usize>,
+    /// keyset 分页游标：`<committer_time>_<id>`
+    cursor: Option<String>,
+}
+
+/// 生成日志分页游标
+fn format_log_cursor(commit: &crate::domain::entities::Commit) -> String {
+    format!("{}_{}", commit.committer_time.timestamp(), commit.id)
+}
+
+/// 解析日志分页游标
+fn parse_log_cursor(cursor: &str) -> Result<(i64, i64)> {
+    cursor
+        .split_once('_')
+        .and_then(|(time, id)| Some((time.parse().ok()?, id.parse().ok()?)))
+        .ok_or_else(|| crate::shared::error::GitxError::InvalidRequest(format!("Invalid cursor: {}", cursor)))
 }
 
 pub async fn repo_log(
@@ -94,42 +216,65 @@ pub async fn repo_log(
     Path(repo_name): Path<String>,
     Query(query): Query<LogQuery>,
 ) -> Result<impl IntoResponse> {
-    let repo = ctx.repository_store
-        .find_by_name(&repo_name)
-        .await?
-        .ok_or_else(|| crate::shared::error::GitxError::RepositoryNotFound(repo_name.clone()))?;
+    let repo = ctx.find_repo_by_name(&repo_name).await?;
     
-    let branch = query.br.as_deref();
-    let offset = query.ofs.unwrap_or(0) as i64;
+    let author = query.author.as_deref().map(str::trim).filter(|a| !a.is_empty());
+    let filter = CommitFilter {
+        branch: query.br.as_deref(),
+        author,
+        since: parse_time_param("since", query.since.as_deref())?,
+        until: parse_time_param("until", query.until.as_deref())?,
+    };
     let limit = 50i64;
     
-    let commits = ctx.commit_store
-        .list_by_repository(repo.id, branch, limit, offset)
-        .await?;
+    // 多取一条判断是否还有下一页，避免最后一页恰好满页时出现空的下一页
+    let mut commits = match (&query.cursor, query.ofs) {
+        (None, Some(offset)) => ctx.commit_store
+            .list_by_repository(repo.id, &filter, CommitOrder::KEYSET, limit + 1, offset as i64)
+            .await?,
+        (cursor, _) => {
+            let cursor = cursor.as_deref().map(parse_log_cursor).transpose()?;
+            ctx.commit_store
+                .list_by_repository_keyset(repo.id, &filter, cursor.map(|c| c.0), cursor.map(|c| c.1), limit + 1)
+                .await?
+        }
+    };
+    let has_more = commits.len() as i64 > limit;
+    commits.truncate(limit as usize);
     
     let commit_items: Vec<CommitItem> = commits
         .iter()
         .map(|c| CommitItem {
             sha: c.oid.clone(),
             sha_short: c.oid[..8.min(c.oid.len())].to_string(),
-            message: c.message.as_ref().and_then(|m| m.lines().next()).unwrap_or("").to_string(),
+            body: c.message.as_deref().map(str::trim).unwrap_or_default().to_string(),
             summary: c.summary.to_string(),
             author_name: c.author_name.clone(),
             author_email: c.author_email.clone(),
             committer_time: c.committer_time.to_rfc3339(),
             is_empty: false,
+            insertions: c.insertions,
+            deletions: c.deletions,
         })
         .collect();
     
-    let has_more = commit_items.len() >= limit as usize;
-    let next_offset = (offset + limit) as usize;
+    let next_offset = query.ofs.unwrap_or(0) as i64 + commit_items.len() as i64;
+    let next_cursor = commits
+        .last()
+        .map(format_log_cursor)
+        .unwrap_or_default();
     let all_branches = get_all_branches(&ctx, repo.id).await?;
 
     let template = LogTemplate {
+        layout: ctx.layout(),
         repo_name: repo_name.clone(),
         commits: commit_items,
         branch: query.br.clone(),
+        author: author.map(String::from),
+        since: query.since.clone().filter(|v| !v.trim().is_empty()),
+        until: query.until.clone().filter(|v| !v.trim().is_empty()),
         has_more,
+        next_cursor,
         next_offset,
         all_branches,
     };
@@ -137,40 +282,150 @@ pub async fn repo_log(
     Ok(Html(template.render()?))
 }
 
+/// 仓库的默认分支：优先使用索引时检测到的 `repositories.default_branch`，
+/// 其次是标记为默认的分支、第一个分支；还没有索引到分支时使用 `default_branch` 原值（为空时回退到 `main`）
+pub(crate) async fn resolve_default_branch(ctx: &AppContext, repo: &Repository) -> Result<String> {
+    let branches = ctx.branch_store.find_by_repository(repo.id).await?;
+    Ok(pick_default_branch(repo, &branches))
+}
+
+/// 同 `resolve_default_branch`，用于已查询过分支列表的场景
+pub(crate) fn pick_default_branch(repo: &Repository, branches: &[Branch]) -> String {
+    branches
+        .iter()
+        .find(|b| b.name == repo.default_branch)
+        .or_else(|| branches.iter().find(|b| b.is_default))
+        .or_else(|| branches.first())
+        .map(|b| b.name.clone())
+        .unwrap_or_else(|| match repo.default_branch.as_str() {
+            "" => FALLBACK_DEFAULT_BRANCH.to_string(),
+            name => name.to_string(),
+        })
+}
+
+/// 将完整或缩写 SHA 解析为完整 OID：缩写先在数据库中按前缀查找，未索引时再交给 git
+pub(crate) async fn resolve_commit_id(
+    ctx: &AppContext,
+    repo_id: i64,
+    repo_path: &std::path::Path,
+    sha: &CommitSha,
+) -> Result<String> {
+    if sha.is_full() {
+        return Ok(sha.to_string());
+    }
+    if let Some(commit) = ctx.commit_store.find_by_oid_prefix(repo_id, sha.as_str()).await? {
+        return Ok(commit.oid);
+    }
+    ctx.git_client.resolve_oid(repo_path, sha.as_str()).await
+}
+
+/// 缓存的 `GitCommitDetail` 结构版本，字段变化时递增，旧条目不再命中
+const COMMIT_DETAIL_SCHEMA: u32 = 2;
+
+/// 提交详情的缓存键：提交 diff 不可变，按版本 + 结构版本 + 仓库 + OID + 上下文行数区分；
+/// 影响渲染的配置（`git.max_diff_lines`、`git.intraline_diff`、语法高亮）变化时 HTML 不同，同样计入
+fn commit_detail_cache_key(config: &Config, repo_id: i64, commit_id: &str, context_lines: Option<u32>) -> String {
+    format!(
+        "commit_detail/{}/s{}/{}/{}/{}/{}-{}-{}",
+        crate::VERSION,
+        COMMIT_DETAIL_SCHEMA,
+        repo_id,
+        commit_id,
+        context_lines.unwrap_or(DEFAULT_CONTEXT_LINES),
+        config.git.max_diff_lines,
+        config.git.intraline_diff,
+        cfg!(feature = "highlight"),
+    )
+}
+
+/// 读取提交详情，优先使用缓存
+pub(crate) async fn commit_detail_cached(
+    ctx: &AppContext,
+    repo_id: i64,
+    repo_path: &std::path::Path,
+    commit_id: &str,
+    context_lines: Option<u32>,
+) -> Result<GitCommitDetail> {
+    let config = ctx.config.load();
+    let key = commit_detail_cache_key(&config, repo_id, commit_id, context_lines);
+    match ctx.cache.get::<GitCommitDetail>(&key).await {
+        Ok(Some(detail)) => return Ok(detail),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Commit detail cache read failed for {}: {}", key, e),
+    }
+
+    let detail = ctx.git_client.get_commit_detail(repo_path, commit_id, context_lines).await?;
+    let ttl = Duration::from_secs(config.cache.ttl_secs);
+    if let Err(e) = ctx.cache.set(&key, &detail, ttl).await {
+        tracing::warn!("Commit detail cache write failed for {}: {}", key, e);
+    }
+    Ok(detail)
+}
+
+/// 源分支末端相对目标分支末端的分叉点与领先/落后提交数
+async fn branch_divergence(
+    ctx: &AppContext,
+    repo_path: &std::path::Path,
+    from_oid: &str,
+    to_oid: &str,
+) -> Result<DivergenceItem> {
+    let merge_base = ctx.git_client.merge_base(repo_path, from_oid, to_oid).await?;
+    let (ahead, behind) = ctx.git_client.ahead_behind(repo_path, from_oid, to_oid).await?;
+    Ok(DivergenceItem {
+        merge_base_sha_short: merge_base.as_ref().map(|oid| oid[..7.min(oid.len())].to_string()),
+        merge_base_sha: merge_base,
+        ahead,
+        behind,
+    })
+}
+
+/// 两个分支末端之间的变更统计与文件列表，按两个末端 OID 缓存（末端移动后键随之变化）
+async fn branch_tips_diff(
+    ctx: &AppContext,
+    repo_id: i64,
+    repo_path: &std::path::Path,
+    from_oid: &str,
+    to_oid: &str,
+) -> Result<GitDiff> {
+    let key = format!("branch_diff/{}/{}/{}", repo_id, from_oid, to_oid);
+    match ctx.cache.get::<GitDiff>(&key).await {
+        Ok(Some(diff)) => return Ok(diff),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Branch compare cache read failed for {}: {}", key, e),
+    }
+
+    let diff = ctx.git_client.compare_commits(repo_path, from_oid, to_oid, None).await?;
+    let ttl = Duration::from_secs(ctx.config.load().cache.ttl_secs);
+    if let Err(e) = ctx.cache.set(&key, &diff, ttl).await {
+        tracing::warn!("Branch compare cache write failed for {}: {}", key, e);
+    }
+    Ok(diff)
+}
+
 /// UI: 单个提交详情页 - 使用模板
 #[derive(Deserialize)]
 pub struct CommitQuery {
     id: Option<String>,
+    /// diff 上下文行数（0..=100，超出截断）
+    context: Option<u32>,
 }
 
 pub async fn repo_commit(
     State(ctx): State<Arc<AppContext>>,
     Path(repo_name): Path<String>,
     Query(query): Query<CommitQuery>,
-) -> Result<impl IntoResponse> {
-    let repo = ctx.repository_store
-        .find_by_name(&repo_name)
-        .await?
-        .ok_or_else(|| crate::shared::error::GitxError::RepositoryNotFound(repo_name.clone()))?;
+    headers: HeaderMap,
+) -> Result<Response> {
+    let repo = ctx.find_repo_by_name(&repo_name).await?;
     
     // 如果没有指定commit id，显示默认分支的commit列表
     if query.id.is_none() {
-        // 从branches表获取默认分支
-        let branches = ctx.branch_store
-            .find_by_repository(repo.id)
-            .await?;
-        
-        // 找到默认分支，或使用第一个分支
-        let default_branch_name = branches
-            .iter()
-            .find(|b| b.is_default)
-            .or_else(|| branches.first())
-            .map(|b| b.name.as_str())
-            .unwrap_or("origin/main");
+        let default_branch = resolve_default_branch(&ctx, &repo).await?;
+        let default_branch_name = default_branch.as_str();
         
         let limit = 50i64;
         let commits = ctx.commit_store
-            .list_by_repository(repo.id, Some(default_branch_name), limit, 0)
+            .list_by_repository_keyset(repo.id, &CommitFilter::branch(Some(default_branch_name)), None, None, limit)
             .await?;
         
         let commit_items: Vec<CommitItem> = commits
@@ -178,72 +433,237 @@ pub async fn repo_commit(
             .map(|c| CommitItem {
                 sha: c.oid.clone(),
                 sha_short: c.oid[..8.min(c.oid.len())].to_string(),
-                message: c.message.as_ref().and_then(|m| m.lines().next()).unwrap_or("").to_string(),
+                body: c.message.as_deref().map(str::trim).unwrap_or_default().to_string(),
                 summary: c.summary.clone(),
                 author_name: c.author_name.clone(),
                 author_email: c.author_email.clone(),
                 committer_time: c.committer_time.to_rfc3339(),
                 is_empty: false,
+                insertions: c.insertions,
+                deletions: c.deletions,
             })
             .collect();
         
         let all_branches = get_all_branches(&ctx, repo.id).await?;
 
-        let len = commit_items.len();
+        let next_cursor = commits
+            .last()
+            .map(format_log_cursor)
+            .unwrap_or_default();
+        let next_offset = commit_items.len() as i64;
+        let total = ctx.commit_store.count_by_repository(repo.id, &CommitFilter::branch(Some(default_branch_name))).await?;
         let template = LogTemplate {
+            layout: ctx.layout(),
             repo_name: repo_name.clone(),
             commits: commit_items,
             branch: Some(default_branch_name.to_string()),
-            has_more: len >= limit as usize,
-            next_offset: limit as usize,
+            author: None,
+            since: None,
+            until: None,
+            has_more: total > limit,
+            next_cursor,
+            next_offset,
             all_branches,
         };
         
-        return Ok(Html(template.render()?));
+        // 列表随新提交变化，不能缓存
+        return Ok(([(header::CACHE_CONTROL, "no-cache")], Html(template.render()?)).into_response());
     }
+
+    let oid = query.id.unwrap();
+    render_commit(&ctx, &repo, &repo_name, oid, query.context, &headers).await
+}
+
+#[derive(Deserialize)]
+pub struct CommitPageQuery {
+    /// diff 上下文行数（0..=100，超出截断）
+    context: Option<u32>,
+}
+
+/// UI: 单个提交详情页（`/{repo}/commits/{oid}`，与 `?id=` 形式等价）
+pub async fn repo_commit_by_oid(
+    State(ctx): State<Arc<AppContext>>,
+    Path((repo_name, oid)): Path<(String, String)>,
+    Query(query): Query<CommitPageQuery>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let repo = ctx.find_repo_by_name(&repo_name).await?;
+    render_commit(&ctx, &repo, &repo_name, oid, query.context, &headers).await
+}
+
+/// 渲染提交详情页
+async fn render_commit(
+    ctx: &AppContext,
+    repo: &Repository,
+    repo_name: &str,
+    oid: String,
+    context: Option<u32>,
+    headers: &HeaderMap,
+) -> Result<Response> {
+    let repo_path = ctx.repo_path(repo)?;
+    // 也接受标签名：只按 refs/tags/ 解析，与分支同名时也指向标签
+    let is_full_sha = CommitSha::new_abbrev(oid.clone()).is_ok_and(|sha| sha.is_full());
+    let oid = match is_full_sha {
+        true => oid,
+        false => match ctx.git_client.find_tag(&repo_path, &oid).await? {
+            Some(tag) => tag.target_oid,
+            None => oid,
+        },
+    };
+
+    // 支持缩写 SHA（如 abc123），先解析为完整 OID
+    let sha = CommitSha::new_abbrev(oid)
+        .map_err(crate::shared::error::GitxError::InvalidOid)?;
+    let commit_id = resolve_commit_id(ctx, repo.id, &repo_path, &sha).await?;
     
-    let commit_id = query.id.unwrap();
-    
-    let commit = ctx.commit_store
-        .find_by_oid(repo.id, &commit_id)
-        .await?
-        .ok_or_else(|| crate::shared::error::GitxError::Internal(format!("Commit {} not found", commit_id)))?;
+    // 刚推送、尚未索引的提交仍可从 git 读取，页面标记为未索引
+    let indexed = ctx.commit_store.find_by_oid(repo.id, &commit_id).await?;
+    let is_indexed = indexed.is_some();
+
+    // 提交内容不可变，ETag 由 OID 和版本号（页面模板可能随版本变化）决定；未索引时不长期缓存
+    let etag = format!("\"{}-{}{}\"", commit_id, crate::VERSION, if is_indexed { "" } else { "-unindexed" });
+    let cache_control = match is_indexed {
+        true => format!("public, max-age={}", COMMIT_CACHE_MAX_AGE_SECS),
+        false => "no-cache".to_string(),
+    };
+    let cache_headers = [(header::ETAG, etag.clone()), (header::CACHE_CONTROL, cache_control)];
+    if etag_matches(headers, &etag) {
+        if let Some(commit) = &indexed {
+            ctx.recent_commits.record(repo.id, repo_name, &commit.oid, &commit.summary);
+        }
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
     
     // 从 git 获取完整的 commit detail（包含 diff）
-    let repo_path = std::path::PathBuf::from(&repo.path);
-    let git_detail = ctx.git_client.get_commit_detail(&repo_path, &commit_id).await?;
+    let context_lines = context.map(|n| n.min(MAX_CONTEXT_LINES));
+    let git_detail = commit_detail_cached(ctx, repo.id, &repo_path, &commit_id, context_lines).await?;
+    let commit = match indexed {
+        Some(commit) => commit,
+        None => commit_from_git(repo.id, &git_detail.commit),
+    };
+    ctx.recent_commits.record(repo.id, repo_name, &commit.oid, &commit.summary);
     
+    // issue 链接模板按仓库所属项目配置
+    let linkify_config = ctx.config
+        .load()
+        .project_for_path(&repo_path)
+        .map(|p| p.linkify.clone())
+        .unwrap_or_default();
+
+    // 父提交标题优先取数据库，未索引的（如合并提交）只读取元数据，不计算 diff
+    let mut parents = Vec::with_capacity(git_detail.commit.parent_oids.len());
+    for parent_oid in &git_detail.commit.parent_oids {
+        let summary = match ctx.commit_store.find_by_oid(repo.id, parent_oid).await? {
+            Some(parent) => parent.summary,
+            None => match ctx.git_client.get_commit(&repo_path, parent_oid).await {
+                Ok(parent) => parent.summary,
+                Err(e) => {
+                    tracing::warn!("Failed to read parent commit {}: {}", parent_oid, e);
+                    String::new()
+                }
+            },
+        };
+        parents.push(ParentItem { sha: parent_oid.clone(), summary });
+    }
+
     let detail = CommitDetail {
         sha: commit.oid.clone(),
-        tree: "".to_string(), // GitCommit没有tree_oid字段，暂时留空
-        parents: git_detail.commit.parent_oids.clone(),
+        tree: git_detail.commit.tree_oid.clone(),
+        parents,
         author_name: commit.author_name.clone(),
         author_email: commit.author_email.clone(),
         author_time: commit.author_time.to_rfc3339(),
         committer_name: commit.committer_name.clone(),
         committer_email: commit.committer_email.clone(),
         committer_time: commit.committer_time.to_rfc3339(),
-        message: commit.message.clone().unwrap_or_default(),
+        message: linkify(&commit.full_message(), &linkify_config),
         diff_stats: git_detail.diff_stats.clone(),
+        files: git_detail.file_stats.iter().map(|f| FileStatItem {
+            path: f.path.clone(),
+            insertions: f.insertions,
+            deletions: f.deletions,
+        }).collect(),
+        hunks: git_detail.hunks.iter().map(|h| HunkItem {
+            path: h.new_path.clone().or_else(|| h.old_path.clone()).unwrap_or_default(),
+            header: h.header.clone(),
+            lines: h.lines.iter().filter_map(|l| {
+                let class = match l.origin {
+                    '+' => "diff-add-line",
+                    '-' => "diff-remove-line",
+                    ' ' => "diff-context",
+                    // 行尾无换行的标记不单独成行
+                    _ => return None,
+                };
+                let lineno = |n: Option<u32>| n.map(|n| n.to_string()).unwrap_or_default();
+                Some(HunkLineItem {
+                    old_lineno: lineno(l.old_lineno),
+                    new_lineno: lineno(l.new_lineno),
+                    class,
+                    html: l.html.clone(),
+                })
+            }).collect(),
+        }).collect(),
         diff: git_detail.diff_html.clone(),
+        diff_too_large: git_detail.diff_too_large,
+        signature: git_detail.signature.as_ref().map(|s| SignatureItem {
+            kind: s.kind.as_str().to_string(),
+            signer: s.signer.clone().unwrap_or_default(),
+        }),
+        indexed: is_indexed,
     };
     
-    let all_branches = get_all_branches(&ctx, repo.id).await?;
+    let all_branches = get_all_branches(ctx, repo.id).await?;
 
     let template = CommitTemplate {
-        repo_name: repo_name.clone(),
+        layout: ctx.layout(),
+        repo_id: repo.id,
+        repo_name: repo_name.to_string(),
         commit: detail,
         all_branches,
     };
     
-    Ok(Html(template.render()?))
+    Ok((cache_headers, Html(template.render()?)).into_response())
+}
+
+/// 未索引提交的元数据（作者未经 mailmap 规范化）
+fn commit_from_git(repository_id: i64, commit: &GitCommit) -> Commit {
+    let time = |ts| DateTime::from_timestamp(ts, 0).unwrap_or(DateTime::UNIX_EPOCH);
+    Commit::new(
+        repository_id,
+        commit.oid.clone(),
+        String::new(),
+        commit.author_name.clone(),
+        commit.author_email.clone(),
+        time(commit.author_time),
+        commit.committer_name.clone(),
+        commit.committer_email.clone(),
+        time(commit.committer_time),
+        commit.summary.clone(),
+    )
+    .with_message(commit.message.clone().unwrap_or_default())
+    .with_parents(commit.parent_oids.clone())
+}
+
+/// 提交详情页的缓存时间（秒）
+const COMMIT_CACHE_MAX_AGE_SECS: u64 = 86400;
+
+/// If-None-Match 是否包含给定 ETag（支持列表、弱校验前缀和 `*`）
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
 }
 
 /// UI: 分支对比页 - 使用模板
 #[derive(Deserialize)]
 pub struct DiffQuery {
     o: String,
-    n: String,
+    /// 目标分支，省略时使用仓库默认分支
+    n: Option<String>,
 }
 
 pub async fn repo_diff(
@@ -251,10 +671,11 @@ pub async fn repo_diff(
     Path(repo_name): Path<String>,
     Query(query): Query<DiffQuery>,
 ) -> Result<impl IntoResponse> {
-    let repo = ctx.repository_store
-        .find_by_name(&repo_name)
-        .await?
-        .ok_or_else(|| crate::shared::error::GitxError::RepositoryNotFound(repo_name.clone()))?;
+    let repo = ctx.find_repo_by_name(&repo_name).await?;
+    let to_branch = match query.n {
+        Some(n) => n,
+        None => resolve_default_branch(&ctx, &repo).await?,
+    };
     
     // 获取所有分支列表用于下拉选择
     let all_branches = ctx.branch_store
@@ -267,20 +688,20 @@ pub async fn repo_diff(
         .collect();
     
     // 使用数据库中已索引的commits进行对比
-    // 通过 author_name + summary + committer_time 识别相同的逻辑commit
+    // 通过 author_email + author_time + summary 识别相同的逻辑commit
     // 这样可以正确处理cherry-pick的情况
     let commits = ctx.commit_store
-        .find_diff_commits(repo.id, &query.o, &query.n, 1000)
+        .find_diff_commits(repo.id, &query.o, &to_branch, 1000)
         .await?;
     
     // 使用 git cherry 检测哪些提交已经被 cherry-pick 过（空提交）
     // git cherry 会返回 "-" 开头的行表示已存在，"+" 开头表示新提交
-    let repo_path = std::path::PathBuf::from(&repo.path);
+    let repo_path = ctx.repo_path(&repo)?;
     let cherry_output = Command::new("git")
         .arg("-C")
         .arg(&repo_path)
         .arg("cherry")
-        .arg(format!("origin/{}", query.n))  // upstream (目标分支)
+        .arg(format!("origin/{}", to_branch))  // upstream (目标分支)
         .arg(format!("origin/{}", query.o))  // head (源分支)
         .output()
         .await
@@ -294,11 +715,7 @@ pub async fn repo_diff(
                 .lines()
                 .filter_map(|line: &str| {
                     // 格式: "- <sha>" 或 "+ <sha>"
-                    if line.starts_with("- ") {
-                        Some(line[2..].trim().to_string())
-                    } else {
-                        None
-                    }
+                    line.strip_prefix("- ").map(|sha| sha.trim().to_string())
                 })
                 .collect::<HashSet<String>>()
         })
@@ -314,23 +731,63 @@ pub async fn repo_diff(
             CommitItem {
                 sha: c.oid.clone(),
                 sha_short: c.oid[..8.min(c.oid.len())].to_string(),
-                message: c.summary.clone(),
+                body: c.message.as_deref().map(str::trim).unwrap_or_default().to_string(),
                 summary: c.summary.clone(),
                 author_name: c.author_name.clone(),
                 author_email: c.author_email.clone(),
                 committer_time: c.committer_time.to_rfc3339(),
                 is_empty,
+                insertions: c.insertions,
+                deletions: c.deletions,
             }
         })
         .collect();
     
+    let tip = |name: &str| all_branches.iter().find(|b| b.name == name).map(|b| b.target_oid.clone());
+    let divergence = match (tip(&query.o), tip(&to_branch)) {
+        (Some(from_oid), Some(to_oid)) => match branch_divergence(&ctx, &repo_path, &from_oid, &to_oid).await {
+            Ok(divergence) => Some(divergence),
+            Err(e) => {
+                tracing::warn!("Failed to compute divergence of {}..{}: {}", from_oid, to_oid, e);
+                None
+            }
+        },
+        _ => None,
+    };
+    let summary = match (tip(&query.o), tip(&to_branch)) {
+        (Some(from_oid), Some(to_oid)) => {
+            match branch_tips_diff(&ctx, repo.id, &repo_path, &from_oid, &to_oid).await {
+                Ok(diff) => Some(DiffSummary {
+                    from_sha_short: from_oid[..7.min(from_oid.len())].to_string(),
+                    to_sha_short: to_oid[..7.min(to_oid.len())].to_string(),
+                    files_changed: diff.totals.files_changed,
+                    insertions: diff.totals.insertions,
+                    deletions: diff.totals.deletions,
+                    files: diff.patches.into_iter().map(|p| DiffFileItem {
+                        path: p.new_path.clone().or_else(|| p.old_path.clone()).unwrap_or_default(),
+                        // 重命名与复制时显示原路径
+                        old_path: p.old_path.filter(|old| p.new_path.as_ref() != Some(old)),
+                        status: p.status.as_str(),
+                    }).collect(),
+                }),
+                Err(e) => {
+                    tracing::warn!("Failed to compare branch tips {}..{}: {}", from_oid, to_oid, e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
 
     let template = DiffTemplate {
+        layout: ctx.layout(),
         repo_name: repo_name.clone(),
         from_branch: query.o.clone(),
-        to_branch: query.n.clone(),
+        to_branch,
         branches: branch_names,
         commits: commit_items,
+        summary,
+        divergence,
     };
     
     Ok(Html(template.render()?))
@@ -359,6 +816,100 @@ pub async fn api_get_repository(
     Ok(Json(repo.into()))
 }
 
+/// API: 仓库的索引进度（正在进行的或最近一次索引）
+pub async fn api_index_status(
+    State(ctx): State<Arc<AppContext>>,
+    Path(id): Path<i64>,
+) -> Result<Json<IndexStatusDto>> {
+    if ctx.repository_store.find_by_id(id).await?.is_none() {
+        return Err(crate::shared::error::GitxError::RepositoryNotFound(id.to_string()));
+    }
+    let status = match ctx.repository_store.index_progress(id).await? {
+        Some(progress) => progress.into(),
+        None => IndexStatusDto::pending(id),
+    };
+    Ok(Json(status))
+}
+
+/// 手动登记仓库请求
+#[derive(Deserialize)]
+pub struct CreateRepositoryRequest {
+    pub path: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// API: 手动登记仓库并立即索引
+pub async fn api_create_repository(
+    State(ctx): State<Arc<AppContext>>,
+    Json(req): Json<CreateRepositoryRequest>,
+) -> Result<(StatusCode, Json<RepositoryDto>)> {
+    use crate::shared::error::GitxError;
+    use crate::shared::validation;
+
+    // 路径必须位于配置的项目根目录之下，与其它接口的校验一致
+    let path = validation::resolve_repo_path(&req.path, &validation::allowed_base_paths(&ctx.config.load()))?;
+    let bare = ctx.git_client
+        .is_bare(&path)
+        .await
+        .map_err(|_| GitxError::InvalidPath(format!("not a git repository: {}", req.path)))?;
+
+    let name = match req.name {
+        Some(name) => name,
+        None => path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string(),
+    };
+    validation::validate_repo_name(&name)?;
+
+    let path_str = path.display().to_string();
+    if ctx.repository_store.exists_by_path(&path_str).await? {
+        return Err(GitxError::Conflict(format!("repository already registered: {}", path_str)));
+    }
+    // UI 路由按名称查找仓库，名称同样不能重复
+    if ctx.repository_store.find_by_name(&name).await?.is_some() {
+        return Err(GitxError::Conflict(format!("repository name already in use: {}", name)));
+    }
+    // 同一仓库经其他路径（如 bind mount）已登记；指纹相同但目录不同的是 fork 或独立克隆
+    let fingerprint = ctx.git_client.root_commit_oid(&path).await?;
+    if let Some(fp) = &fingerprint {
+        let registered = ctx.repository_store.find_by_fingerprint(fp).await?;
+        for existing in registered.iter().filter(|r| !r.is_missing()) {
+            if validation::same_location(std::path::Path::new(&existing.path), &path).await {
+                return Err(GitxError::Conflict(format!(
+                    "same repository already registered as {} at {}",
+                    existing.name, existing.path
+                )));
+            }
+            tracing::info!("{} shares fingerprint {} with {} at {}", path_str, fp, existing.name, existing.path);
+        }
+    }
+
+    let mut repo = Repository::new(name.clone(), path_str);
+    repo.fingerprint = fingerprint;
+    if let Some(description) = req.description {
+        repo = repo.with_description(description);
+    }
+    let id = ctx.repository_store.save(&repo).await?;
+    let repo = ctx.repository_store
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| GitxError::RepositoryNotFound(id.to_string()))?;
+
+    // 后台立即索引
+    let scheduler = ctx.scheduler.clone();
+    let discovered = DiscoveredRepo { name, path, bare };
+    tokio::spawn(async move {
+        if let Err(e) = scheduler.index_repository(&discovered).await {
+            tracing::error!("Initial index failed for {}: {}", discovered.name, e);
+        }
+    });
+
+    Ok((StatusCode::CREATED, Json(repo.into())))
+}
+
 pub async fn api_sync_repository(
     State(ctx): State<Arc<AppContext>>,
     Path(id): Path<i64>,
@@ -368,8 +919,9 @@ pub async fn api_sync_repository(
         .await?
         .ok_or_else(|| crate::shared::error::GitxError::RepositoryNotFound(id.to_string()))?;
     
-    let repo_path = std::path::PathBuf::from(&repo.path);
-    let result = ctx.git_client.fetch_repository(&repo_path).await?;
+    let repo_path = ctx.repo_path(&repo)?;
+    let remote = ctx.config.load().remote_for_path(&repo_path).to_string();
+    let result = ctx.git_client.fetch_repository(&repo_path, &remote).await?;
     ctx.repository_store.update_sync_time(id).await?;
     
     Ok(Json(SyncResponse {
@@ -378,6 +930,25 @@ pub async fn api_sync_repository(
     }))
 }
 
+#[derive(Deserialize)]
+pub struct ReindexQuery {
+    /// 删除已索引的数据后从头重建，默认增量索引
+    #[serde(default)]
+    full: bool,
+}
+
+/// API: 重新索引仓库（管理接口），`full=true` 时丢弃已有索引完全重建
+pub async fn api_reindex_repository(
+    State(ctx): State<Arc<AppContext>>,
+    Path(id): Path<i64>,
+    Query(query): Query<ReindexQuery>,
+    headers: HeaderMap,
+) -> Result<Json<IndexResult>> {
+    ctx.require_admin(&headers)?;
+    let result = ctx.scheduler.reindex(id, query.full).await?;
+    Ok(Json(result))
+}
+
 #[derive(Serialize)]
 pub struct SyncResponse {
     success: bool,
@@ -389,19 +960,17 @@ pub async fn api_sync_repository_by_name(
     State(ctx): State<Arc<AppContext>>,
     Path(repo_name): Path<String>,
 ) -> Result<Json<SyncResponse>> {
-    let repo = ctx.repository_store
-        .find_by_name(&repo_name)
-        .await?
-        .ok_or_else(|| crate::shared::error::GitxError::RepositoryNotFound(repo_name.clone()))?;
+    let repo = ctx.find_repo_by_name(&repo_name).await?;
     
-    let repo_path = std::path::PathBuf::from(&repo.path);
+    let repo_path = ctx.repo_path(&repo)?;
     
     // 1. Fetch from remote
-    let result = ctx.git_client.fetch_repository(&repo_path).await?;
+    let remote = ctx.config.load().remote_for_path(&repo_path).to_string();
+    let result = ctx.git_client.fetch_repository(&repo_path, &remote).await?;
     
     // 2. Re-index the repository
     let worker = crate::services::worker::IndexWorker::new(
-        ctx.config.clone(),
+        ctx.config.load(),
         ctx.repository_store.clone(),
         ctx.commit_store.clone(),
         ctx.branch_store.clone(),
@@ -484,12 +1053,11 @@ pub async fn api_cherry_pick(
     Path(repo_name): Path<String>,
     Json(req): Json<CherryPickRequest>,
 ) -> Result<Json<CherryPickResponse>> {
-    let repo = ctx.repository_store
-        .find_by_name(&repo_name)
-        .await?
-        .ok_or_else(|| crate::shared::error::GitxError::RepositoryNotFound(repo_name.clone()))?;
+    let repo = ctx.find_repo_by_name(&repo_name).await?;
     
-    let repo_path = std::path::PathBuf::from(&repo.path);
+    let repo_path = ctx.repo_path(&repo)?;
+    ensure_working_tree(&ctx, &repo_name, &repo_path, "cherry-pick").await?;
+    let remote = ctx.config.load().remote_for_path(&repo_path).to_string();
     
     use tokio::process::Command;
     
@@ -498,7 +1066,7 @@ pub async fn api_cherry_pick(
         .arg("-C")
         .arg(&repo_path)
         .arg("fetch")
-        .arg("origin")
+        .arg(&remote)
         .output()
         .await?;
     
@@ -512,12 +1080,11 @@ pub async fn api_cherry_pick(
         }));
     }
     
-    // 2. 处理目标分支名称（如果是origin/xxx，去掉origin/前缀）
-    let local_branch = if req.target_branch.starts_with("origin/") {
-        req.target_branch.strip_prefix("origin/").unwrap().to_string()
-    } else {
-        req.target_branch.clone()
-    };
+    // 2. 处理目标分支名称（如果是 <remote>/xxx，去掉远程名前缀）
+    let local_branch = req.target_branch
+        .strip_prefix(&format!("{}/", remote))
+        .unwrap_or(&req.target_branch)
+        .to_string();
     
     // 3. Checkout到目标分支（如果本地分支不存在，基于远程分支创建）
     let checkout_output = Command::new("git")
@@ -526,7 +1093,7 @@ pub async fn api_cherry_pick(
         .arg("checkout")
         .arg("-B")  // 创建或重置本地分支
         .arg(&local_branch)
-        .arg(format!("origin/{}", local_branch))
+        .arg(format!("{}/{}", remote, local_branch))
         .output()
         .await?;
     
@@ -620,28 +1187,24 @@ pub async fn api_push(
     Path(repo_name): Path<String>,
     Json(req): Json<PushRequest>,
 ) -> Result<Json<PushResponse>> {
-    let repo = ctx.repository_store
-        .find_by_name(&repo_name)
-        .await?
-        .ok_or_else(|| crate::shared::error::GitxError::RepositoryNotFound(repo_name.clone()))?;
+    let repo = ctx.find_repo_by_name(&repo_name).await?;
     
-    let repo_path = std::path::PathBuf::from(&repo.path);
+    let repo_path = ctx.repo_path(&repo)?;
+    ensure_working_tree(&ctx, &repo_name, &repo_path, "push").await?;
+    let remote = ctx.config.load().remote_for_path(&repo_path).to_string();
     
     use tokio::process::Command;
 
-    // 处理分支名称：如果包含 origin/ 前缀，去掉它
-    let branch_name = if req.branch.starts_with("origin/") {
-        req.branch.strip_prefix("origin/").unwrap()
-    } else {
-        &req.branch
-    };
+    // 处理分支名称：如果包含远程名前缀，去掉它
+    let remote_prefix = format!("{}/", remote);
+    let branch_name = req.branch.strip_prefix(&remote_prefix).unwrap_or(&req.branch);
     
     // 执行git push
     let output = Command::new("git")
         .arg("-C")
         .arg(&repo_path)
         .arg("push")
-        .arg("origin")
+        .arg(&remote)
         .arg(branch_name)
         .output()
         .await?;
@@ -649,7 +1212,7 @@ pub async fn api_push(
     if output.status.success() {
         // 触发索引更新，确保前端 Diff 视图能及时刷新
         let worker = IndexWorker::new(
-            ctx.config.clone(),
+            ctx.config.load(),
             ctx.repository_store.clone(),
             ctx.commit_store.clone(),
             ctx.branch_store.clone(),
@@ -675,13 +1238,13 @@ pub async fn api_push(
                 .arg(&repo_path)
                 .arg("pull")
                 .arg("--rebase")
-                .arg("origin")
+                .arg(&remote)
                 .arg(branch_name)
                 .output()
                 .await?;
 // 触发索引更新
                     let worker = IndexWorker::new(
-                        ctx.config.clone(),
+                        ctx.config.load(),
                         ctx.repository_store.clone(),
                         ctx.commit_store.clone(),
                         ctx.branch_store.clone(),
@@ -698,7 +1261,7 @@ pub async fn api_push(
                     .arg("-C")
                     .arg(&repo_path)
                     .arg("push")
-                    .arg("origin")
+                    .arg(&remote)
                     .arg(branch_name)
                     .output()
                     .await?;
@@ -740,6 +1303,17 @@ pub async fn api_push(
     }
 }
 
+/// 需要工作区的操作拒绝裸仓库
+async fn ensure_working_tree(ctx: &AppContext, repo_name: &str, repo_path: &std::path::Path, action: &str) -> Result<()> {
+    if ctx.git_client.is_bare(repo_path).await? {
+        return Err(crate::shared::error::GitxError::InvalidRequest(format!(
+            "{} is a bare repository; {} requires a working tree",
+            repo_name, action
+        )));
+    }
+    Ok(())
+}
+
 async fn get_all_branches(ctx: &AppContext, repo_id: i64) -> Result<Vec<String>> {
     let branches = ctx.branch_store
         .find_by_repository(repo_id)
@@ -766,19 +1340,18 @@ pub async fn api_merge(
     Path(repo_name): Path<String>,
     Json(req): Json<MergeRequest>,
 ) -> Result<Json<MergeResponse>> {
-    let repo = ctx.repository_store
-        .find_by_name(&repo_name)
-        .await?
-        .ok_or_else(|| crate::shared::error::GitxError::RepositoryNotFound(repo_name.clone()))?;
+    let repo = ctx.find_repo_by_name(&repo_name).await?;
     
-    let repo_path = std::path::PathBuf::from(&repo.path);
+    let repo_path = ctx.repo_path(&repo)?;
+    ensure_working_tree(&ctx, &repo_name, &repo_path, "merge").await?;
+    let remote = ctx.config.load().remote_for_path(&repo_path).to_string();
     
     // 1. Fetch latest from remote
     let fetch_output = Command::new("git")
         .arg("-C")
         .arg(&repo_path)
         .arg("fetch")
-        .arg("origin")
+        .arg(&remote)
         .output()
         .await?;
     
@@ -791,18 +1364,18 @@ pub async fn api_merge(
         }));
     }
     
-    // 2. Process branch names (remove origin/ prefix if present)
-    let source_branch = if req.source_branch.starts_with("origin/") {
+    // 2. Process branch names (source is always the remote-tracking branch, target is local)
+    let remote_prefix = format!("{}/", remote);
+    let source_branch = if req.source_branch.starts_with(&remote_prefix) {
         req.source_branch.clone()
     } else {
-        format!("origin/{}", req.source_branch)
+        format!("{}{}", remote_prefix, req.source_branch)
     };
     
-    let local_target = if req.target_branch.starts_with("origin/") {
-        req.target_branch.strip_prefix("origin/").unwrap().to_string()
-    } else {
-        req.target_branch.clone()
-    };
+    let local_target = req.target_branch
+        .strip_prefix(&remote_prefix)
+        .unwrap_or(&req.target_branch)
+        .to_string();
     
     // 3. Checkout target branch
     let checkout_output = Command::new("git")
@@ -811,7 +1384,7 @@ pub async fn api_merge(
         .arg("checkout")
         .arg("-B")
         .arg(&local_target)
-        .arg(format!("origin/{}", local_target))
+        .arg(format!("{}{}", remote_prefix, local_target))
         .output()
         .await?;
     
@@ -880,3 +1453,75 @@ pub async fn api_merge(
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thousands() {
+        assert_eq!(thousands(0), "0");
+        assert_eq!(thousands(999), "999");
+        assert_eq!(thousands(1204), "1,204");
+        assert_eq!(thousands(1_234_567), "1,234,567");
+        assert_eq!(thousands(-1000), "-1,000");
+        assert_eq!(thousands(i64::MIN), "-9,223,372,036,854,775,808");
+    }
+
+    #[test]
+    fn test_pick_default_branch() {
+        let branch = |name: &str, is_default: bool| Branch {
+            id: 0,
+            repository_id: 1,
+            name: name.to_string(),
+            target_oid: String::new(),
+            is_default,
+            updated_at: chrono::Utc::now(),
+            commit_count: 0,
+        };
+        let mut repo = Repository::new("r".into(), "/r".into());
+
+        // 仓库记录的默认分支不存在时依次回退到 is_default、第一个分支
+        let branches = vec![branch("origin/dev", false), branch("origin/trunk", true)];
+        assert_eq!(pick_default_branch(&repo, &branches), "origin/trunk");
+        assert_eq!(pick_default_branch(&repo, &branches[..1]), "origin/dev");
+        repo.default_branch = "origin/dev".into();
+        assert_eq!(pick_default_branch(&repo, &branches), "origin/dev");
+
+        // 尚未索引分支时使用记录值，为空时回退到 main
+        assert_eq!(pick_default_branch(&repo, &[]), "origin/dev");
+        repo.default_branch.clear();
+        assert_eq!(pick_default_branch(&repo, &[]), FALLBACK_DEFAULT_BRANCH);
+    }
+
+    #[test]
+    fn test_commit_detail_cache_key() {
+        let mut config: Config = toml::from_str(
+            r#"
+            projects = []
+            [server]
+            bind_address = "127.0.0.1:0"
+            cors_origins = ["*"]
+            [database]
+            sqlite_path = ":memory:"
+            max_connections = 1
+            [git]
+            [indexer]
+            [cache]
+            "#,
+        )
+        .unwrap();
+        let oid = "a".repeat(40);
+        let key = commit_detail_cache_key(&config, 1, &oid, None);
+        assert_eq!(key, commit_detail_cache_key(&config, 1, &oid, Some(DEFAULT_CONTEXT_LINES)));
+        assert_ne!(key, commit_detail_cache_key(&config, 1, &oid, Some(10)));
+        assert!(key.contains(&format!("/s{}/", COMMIT_DETAIL_SCHEMA)));
+
+        // 影响渲染结果的配置变化时不命中旧条目
+        config.git.max_diff_lines += 1;
+        let resized = commit_detail_cache_key(&config, 1, &oid, None);
+        assert_ne!(key, resized);
+        config.git.intraline_diff = !config.git.intraline_diff;
+        assert_ne!(resized, commit_detail_cache_key(&config, 1, &oid, None));
+    }
+}
@@ -0,0 +1,49 @@
+use axum::{
+    extract::{State, Path, Query},
+    response::Json,
+};
+use chrono::DateTime;
+use std::sync::Arc;
+use serde::Deserialize;
+use crate::presentation::routes::AppContext;
+use crate::presentation::dto::RepositoryStatsDto;
+use crate::shared::result::Result;
+
+#[derive(Deserialize)]
+pub struct StatsQuery {
+    /// 返回的作者数量，默认 10
+    pub top: Option<i64>,
+}
+
+/// API: 仓库统计（提交总数、作者排行、分支提交数、每日活跃度）
+pub async fn api_repository_stats(
+    State(ctx): State<Arc<AppContext>>,
+    Path(id): Path<i64>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<RepositoryStatsDto>> {
+    let repo = ctx.repository_store
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| crate::shared::error::GitxError::RepositoryNotFound(id.to_string()))?;
+
+    let totals = ctx.commit_store.commit_totals(repo.id).await?;
+    let top_authors = ctx.commit_store
+        .author_stats(repo.id, query.top.unwrap_or(10).clamp(1, 100))
+        .await?;
+    let branches = ctx.commit_store.branch_commit_counts(repo.id).await?;
+    let activity = ctx.commit_store.activity_by_day(repo.id).await?;
+
+    let to_rfc3339 = |ts: Option<i64>| {
+        ts.and_then(|ts| DateTime::from_timestamp(ts, 0)).map(|dt| dt.to_rfc3339())
+    };
+
+    Ok(Json(RepositoryStatsDto {
+        repository_id: repo.id,
+        total_commits: totals.total_commits,
+        first_commit_time: to_rfc3339(totals.first_commit_time),
+        last_commit_time: to_rfc3339(totals.last_commit_time),
+        top_authors: top_authors.into_iter().map(Into::into).collect(),
+        branches: branches.into_iter().map(Into::into).collect(),
+        activity_by_day: activity.into_iter().map(Into::into).collect(),
+    }))
+}
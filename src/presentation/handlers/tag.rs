@@ -0,0 +1,84 @@
+use axum::{
+    extract::{State, Path},
+    response::Json,
+};
+use chrono::DateTime;
+use std::sync::Arc;
+use serde::Serialize;
+use crate::ports::git::GitTag;
+use crate::presentation::dto::GitCommitDto;
+use crate::presentation::routes::AppContext;
+use crate::shared::error::GitxError;
+use crate::shared::result::Result;
+
+#[derive(Serialize)]
+pub struct TagDto {
+    pub name: String,
+    /// 标签指向的提交（标注标签已剥离）
+    pub target_oid: String,
+    pub tagger_name: Option<String>,
+    pub tagger_email: Option<String>,
+    pub message: Option<String>,
+    pub date: Option<String>,
+}
+
+impl From<GitTag> for TagDto {
+    fn from(tag: GitTag) -> Self {
+        Self {
+            name: tag.name,
+            target_oid: tag.target_oid,
+            tagger_name: tag.tagger_name,
+            tagger_email: tag.tagger_email,
+            message: tag.message,
+            date: tag.tagger_time
+                .and_then(|ts| DateTime::from_timestamp(ts, 0))
+                .map(|dt| dt.to_rfc3339()),
+        }
+    }
+}
+
+/// 单个标签及其指向的提交
+#[derive(Serialize)]
+pub struct TagDetailDto {
+    pub tag: TagDto,
+    pub commit: GitCommitDto,
+}
+
+/// API: 列出仓库的标签
+pub async fn api_list_tags(
+    State(ctx): State<Arc<AppContext>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<TagDto>>> {
+    let repo = ctx.repository_store
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| GitxError::RepositoryNotFound(id.to_string()))?;
+    
+    let repo_path = ctx.repo_path(&repo)?;
+    let tags = ctx.git_client.list_tags(&repo_path).await?;
+    
+    Ok(Json(tags.into_iter().map(TagDto::from).collect()))
+}
+
+/// API: 获取单个标签（标注标签剥离到提交），标签名可包含 `/`
+pub async fn api_get_tag(
+    State(ctx): State<Arc<AppContext>>,
+    Path((id, name)): Path<(i64, String)>,
+) -> Result<Json<TagDetailDto>> {
+    let repo = ctx.repository_store
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| GitxError::RepositoryNotFound(id.to_string()))?;
+
+    let repo_path = ctx.repo_path(&repo)?;
+    let tag = ctx.git_client
+        .find_tag(&repo_path, &name)
+        .await?
+        .ok_or(GitxError::ReferenceNotFound(name))?;
+    let commit = ctx.git_client.get_commit(&repo_path, &tag.target_oid).await?;
+
+    Ok(Json(TagDetailDto {
+        tag: TagDto::from(tag),
+        commit: GitCommitDto::from(commit),
+    }))
+}
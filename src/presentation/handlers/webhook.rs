@@ -0,0 +1,126 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use crate::presentation::auth::constant_time_eq;
+use crate::presentation::routes::AppContext;
+use crate::services::discovery::RepositoryDiscovery;
+use crate::shared::error::GitxError;
+use crate::shared::result::Result;
+use crate::shared::validation;
+
+/// Webhook 请求体，repo_path 与 repo_name 至少提供一个
+#[derive(Deserialize)]
+pub struct WebhookRequest {
+    repo_path: Option<String>,
+    repo_name: Option<String>,
+    branch: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct WebhookResponse {
+    accepted: bool,
+    repository: String,
+    message: String,
+}
+
+/// API: 接收推送通知并在后台触发索引
+pub async fn api_webhook(
+    State(ctx): State<Arc<AppContext>>,
+    headers: HeaderMap,
+    Json(req): Json<WebhookRequest>,
+) -> Result<(StatusCode, Json<WebhookResponse>)> {
+    // 1. 校验共享密钥
+    let config = ctx.config.load();
+    if let Some(secret) = &config.webhook.secret {
+        let provided = headers.get("X-Webhook-Secret").map(|v| v.as_bytes()).unwrap_or_default();
+        if !constant_time_eq(provided, secret.expose().as_bytes()) {
+            return Err(GitxError::Unauthorized("invalid webhook secret".to_string()));
+        }
+    }
+
+    if req.repo_path.is_none() && req.repo_name.is_none() {
+        return Err(GitxError::InvalidRequest(
+            "either repo_path or repo_name is required".to_string(),
+        ));
+    }
+
+    if let Some(name) = &req.repo_name {
+        validation::validate_repo_name(name)?;
+    }
+
+    // 路径统一规范化，与发现服务保存的格式一致
+    let canonical_path = req.repo_path.as_ref().map(|p| {
+        PathBuf::from(p)
+            .canonicalize()
+            .unwrap_or_else(|_| PathBuf::from(p))
+    });
+
+    // 2. 查找已登记的仓库
+    let existing = match (&canonical_path, &req.repo_name) {
+        (Some(path), _) => ctx.repository_store.find_by_path(&path.display().to_string()).await?,
+        (None, Some(name)) => ctx.repository_store.find_by_name(name).await?,
+        (None, None) => None,
+    };
+
+    let branch_note = req.branch.as_deref().unwrap_or("all branches");
+
+    if let Some(repo) = existing {
+        tracing::info!("Webhook received for {} ({}), scheduling index", repo.name, branch_note);
+
+        let scheduler = ctx.scheduler.clone();
+        let repo_id = repo.id;
+        tokio::spawn(async move {
+            if let Err(e) = scheduler.trigger_index(repo_id).await {
+                tracing::error!("Webhook-triggered index failed for repository {}: {}", repo_id, e);
+            }
+        });
+
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(WebhookResponse {
+                accepted: true,
+                repository: repo.name,
+                message: format!("Indexing scheduled for {}", branch_note),
+            }),
+        ));
+    }
+
+    // 3. 仓库尚未登记，先执行发现
+    let discovery = RepositoryDiscovery::new(config);
+    let discovered = discovery
+        .discover_all()
+        .await?
+        .into_iter()
+        .find(|r| match (&canonical_path, &req.repo_name) {
+            (Some(path), _) => &r.path == path,
+            (None, Some(name)) => &r.name == name,
+            (None, None) => false,
+        })
+        .ok_or_else(|| GitxError::RepositoryNotFound(
+            req.repo_path.clone().or(req.repo_name.clone()).unwrap_or_default(),
+        ))?;
+
+    tracing::info!("Webhook discovered new repository {}, scheduling index", discovered.name);
+
+    let name = discovered.name.clone();
+    let scheduler = ctx.scheduler.clone();
+    tokio::spawn(async move {
+        if let Err(e) = scheduler.index_repository(&discovered).await {
+            tracing::error!("Webhook-triggered index failed for {}: {}", discovered.name, e);
+        }
+    });
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(WebhookResponse {
+            accepted: true,
+            repository: name,
+            message: "Repository discovered, initial indexing scheduled".to_string(),
+        }),
+    ))
+}
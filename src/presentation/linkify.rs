@@ -0,0 +1,169 @@
+use crate::shared::config::LinkifyConfig;
+
+/// 提交信息转 HTML：先转义，再把 URL 与 issue 引用（`#123`、`org/repo#123`）替换为链接
+///
+/// 链接识别在转义后的文本上进行，原文中的 `<`、`"` 等无法逃出 `href` 属性或注入标签
+pub fn linkify(text: &str, config: &LinkifyConfig) -> String {
+    let escaped = escape_html(text);
+    let mut out = String::with_capacity(escaped.len());
+    let mut i = 0;
+
+    while i < escaped.len() {
+        let rest = &escaped[i..];
+        let prev = escaped[..i].chars().next_back();
+
+        if let Some((len, html)) = match_url(rest, prev)
+            .or_else(|| match_repo_issue(rest, prev, config))
+            .or_else(|| match_issue(rest, prev, config))
+        {
+            out.push_str(&html);
+            i += len;
+            continue;
+        }
+
+        let ch = rest.chars().next().unwrap();
+        out.push(ch);
+        i += ch.len_utf8();
+    }
+
+    out
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn anchor(href: &str, text: &str) -> String {
+    format!("<a href=\"{}\">{}</a>", href, text)
+}
+
+/// `http(s)://` 开头的 URL，遇到空白或转义后的 `<>"'` 结束，去掉末尾标点
+fn match_url(rest: &str, prev: Option<char>) -> Option<(usize, String)> {
+    if prev.is_some_and(|c| c.is_alphanumeric()) {
+        return None;
+    }
+    let scheme_len = ["https://", "http://"]
+        .iter()
+        .find(|scheme| rest.starts_with(*scheme))?
+        .len();
+
+    let mut end = rest.len();
+    for (idx, ch) in rest.char_indices() {
+        let tail = &rest[idx..];
+        if ch.is_whitespace() || ["&lt;", "&gt;", "&quot;", "&#39;"].iter().any(|e| tail.starts_with(e)) {
+            end = idx;
+            break;
+        }
+    }
+    let mut url = &rest[..end];
+    while let Some(last) = url.chars().next_back() {
+        let entity_end = last == ';' && url.ends_with("&amp;");
+        if !".,;:!?)".contains(last) || entity_end {
+            break;
+        }
+        url = &url[..url.len() - 1];
+    }
+
+    if url.len() <= scheme_len {
+        return None;
+    }
+    Some((url.len(), anchor(url, url)))
+}
+
+/// `#123`，前一个字符不能是单词字符或 `&`（避免匹配 `&#39;` 这类实体）
+fn match_issue(rest: &str, prev: Option<char>, config: &LinkifyConfig) -> Option<(usize, String)> {
+    let template = config.issue_url.as_deref()?;
+    if prev.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '&' || c == '/') {
+        return None;
+    }
+    let number = issue_number(rest.strip_prefix('#')?)?;
+
+    let href = escape_html(&template.replace("{number}", number));
+    Some((number.len() + 1, anchor(&href, &format!("#{}", number))))
+}
+
+/// `org/repo#123`
+fn match_repo_issue(rest: &str, prev: Option<char>, config: &LinkifyConfig) -> Option<(usize, String)> {
+    let template = config.repo_issue_url.as_deref()?;
+    if prev.is_some_and(|c| is_repo_char(c) || c == '/') {
+        return None;
+    }
+
+    let hash = rest.find(|c: char| !is_repo_char(c) && c != '/').unwrap_or(rest.len());
+    if !rest[hash..].starts_with('#') {
+        return None;
+    }
+    let repo = &rest[..hash];
+    let (org, name) = repo.split_once('/')?;
+    let valid = |part: &str| !part.is_empty() && part.chars().all(is_repo_char);
+    if !valid(org) || !valid(name) {
+        return None;
+    }
+    let number = issue_number(&rest[hash + 1..])?;
+
+    let href = escape_html(&template.replace("{repo}", repo).replace("{number}", number));
+    let len = hash + 1 + number.len();
+    Some((len, anchor(&href, &rest[..len])))
+}
+
+fn is_repo_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'
+}
+
+/// 开头的数字编号，后面不能紧跟单词字符
+fn issue_number(s: &str) -> Option<&str> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let next = s[end..].chars().next();
+    if end == 0 || next.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some(&s[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LinkifyConfig {
+        LinkifyConfig {
+            issue_url: Some("https://tracker.example.com/issues/{number}".to_string()),
+            repo_issue_url: Some("https://github.com/{repo}/issues/{number}".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_linkify_issues_and_urls() {
+        assert_eq!(
+            linkify("Fix #12 and acme/app#7, see https://example.com/a?b=1&c=2.", &config()),
+            "Fix <a href=\"https://tracker.example.com/issues/12\">#12</a> and \
+             <a href=\"https://github.com/acme/app/issues/7\">acme/app#7</a>, see \
+             <a href=\"https://example.com/a?b=1&amp;c=2\">https://example.com/a?b=1&amp;c=2</a>."
+        );
+
+        // 未配置模板时只链接 URL
+        assert_eq!(linkify("Fix #12", &LinkifyConfig::default()), "Fix #12");
+        // 不是 issue 引用
+        assert_eq!(linkify("color#fff a#1b x#12", &config()), "color#fff a#1b x#12");
+    }
+
+    #[test]
+    fn test_linkify_escapes_before_linking() {
+        assert_eq!(
+            linkify("<script>alert(1)</script> #3", &config()),
+            "&lt;script&gt;alert(1)&lt;/script&gt; <a href=\"https://tracker.example.com/issues/3\">#3</a>"
+        );
+        // 引号不能闭合 href 属性
+        assert_eq!(
+            linkify("https://evil.example.com/\"onmouseover=\"alert(1)", &config()),
+            "<a href=\"https://evil.example.com/\">https://evil.example.com/</a>&quot;onmouseover=&quot;alert(1)"
+        );
+        // 转义后的 `&#39;` 不会被当作 issue 引用
+        assert_eq!(linkify("it's '39", &config()), "it&#39;s &#39;39");
+        // 非 http(s) 协议不生成链接
+        assert_eq!(linkify("javascript:alert(1)", &config()), "javascript:alert(1)");
+    }
+}
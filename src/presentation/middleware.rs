@@ -0,0 +1,127 @@
+use axum::{
+    extract::{MatchedPath, RawPathParams, Request, State},
+    http::{header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+    RequestExt,
+};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{info, info_span, Instrument};
+use uuid::Uuid;
+use crate::shared::error::ErrorBody;
+use crate::shared::metrics::Metrics;
+
+/// 请求 ID 头，客户端或反向代理已设置时沿用
+static REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+static SERVER_TIMING: HeaderName = HeaderName::from_static("server-timing");
+
+/// 为每个请求分配请求 ID，在 span 中记录路由与仓库（处理期间的日志都带有这些字段），
+/// 结束时记录耗时；`server_timing` 为 true 时附加 `Server-Timing` 响应头
+///
+/// 耗时统计到生成响应头为止，流式响应体的传输时间不计入。
+pub async fn trace_requests(
+    State(server_timing): State<bool>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= 128 && id.bytes().all(|b| b.is_ascii_graphic()))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    // UI 路由按名称（{repo}），API 路由按 ID（{id}）
+    let repo = request
+        .extract_parts::<RawPathParams>()
+        .await
+        .ok()
+        .and_then(|params| params.iter().find(|(k, _)| *k == "repo" || *k == "id").map(|(_, v)| v.to_string()));
+
+    let span = info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        route = %route,
+        repo = tracing::field::Empty,
+    );
+    if let Some(repo) = &repo {
+        span.record("repo", tracing::field::display(repo));
+    }
+
+    let started = Instant::now();
+    let mut response = next.run(request).instrument(span.clone()).await;
+    let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+    span.in_scope(|| {
+        info!(
+            status = response.status().as_u16(),
+            duration_ms = elapsed_ms,
+            "Request completed in {:.1}ms",
+            elapsed_ms
+        );
+    });
+
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        headers.insert(REQUEST_ID.clone(), value);
+    }
+    if server_timing {
+        if let Ok(value) = HeaderValue::from_str(&format!("app;dur={:.1}", elapsed_ms)) {
+            headers.append(SERVER_TIMING.clone(), value);
+        }
+    }
+    response
+}
+
+/// 按路由模板统计 HTTP 请求数
+pub async fn track_http_requests(
+    State(metrics): State<Arc<Metrics>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    // 使用路由模板（如 /{repo}/log）而不是实际路径，避免标签基数爆炸
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(request).await;
+    metrics.record_http_request(&method, &route, response.status().as_u16());
+
+    response
+}
+
+/// `/api` 下的请求或声明 `Accept: application/json` 的请求，错误以 JSON 返回
+pub async fn json_errors(request: Request, next: Next) -> Response {
+    let wants_json = request.uri().path().starts_with("/api/")
+        || request
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.contains("application/json"));
+
+    let mut response = next.run(request).await;
+    if !wants_json {
+        return response;
+    }
+
+    match response.extensions_mut().remove::<ErrorBody>() {
+        Some(body) => {
+            // 保留 Retry-After 等原有响应头，只替换正文
+            let (mut parts, _) = response.into_parts();
+            let json = Json(body).into_response();
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts.headers.extend(json.headers().clone());
+            Response::from_parts(parts, json.into_body())
+        }
+        None => response,
+    }
+}
@@ -2,3 +2,8 @@ pub mod routes;
 pub mod handlers;
 pub mod dto;
 pub mod templates;
+pub mod middleware;
+pub mod linkify;
+pub mod rate_limit;
+pub mod recent;
+pub mod auth;
@@ -0,0 +1,122 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::presentation::routes::AppContext;
+use crate::shared::error::GitxError;
+
+/// 桶数量超过该值时清理已回满的桶，避免内存无限增长
+const MAX_IDLE_BUCKETS: usize = 10_000;
+
+/// 令牌桶：容量为每分钟请求数，按时间匀速回填
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// 按 (客户端 IP, 仓库名) 计数的令牌桶限流器，速率由调用方按当前配置传入
+#[derive(Default)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<(IpAddr, String), Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 消耗一个令牌；超限时返回需要等待的时间
+    fn acquire(&self, key: (IpAddr, String), per_minute: u32, now: Instant) -> Result<(), Duration> {
+        if per_minute == 0 {
+            return Ok(());
+        }
+        let capacity = per_minute as f64;
+        let refill_per_sec = capacity / 60.0;
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_IDLE_BUCKETS {
+            buckets.retain(|_, b| {
+                now.duration_since(b.updated_at).as_secs_f64() * refill_per_sec + b.tokens < capacity
+            });
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket { tokens: capacity, updated_at: now });
+        let elapsed = now.saturating_duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / refill_per_sec))
+        }
+    }
+}
+
+/// 写操作限流中间件，超限返回 429 并附带 `Retry-After`
+pub async fn rate_limit(State(ctx): State<Arc<AppContext>>, request: Request, next: Next) -> Response {
+    let per_minute = ctx.config.load().rate_limit.mutating_per_minute;
+    // 路由形如 /{repo}/api/push，第一段即仓库名
+    let repo = request
+        .uri()
+        .path()
+        .trim_start_matches('/')
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+    match ctx.rate_limiter.acquire((ip, repo), per_minute, Instant::now()) {
+        Ok(()) => next.run(request).await,
+        Err(wait) => {
+            tracing::warn!(client = %ip, path = %request.uri().path(), "Rate limit exceeded");
+            GitxError::RateLimited(wait.as_secs_f64().ceil() as u64).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_is_limited_per_ip_and_repo() {
+        let limiter = RateLimiter::new();
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let key = |repo: &str| (ip, repo.to_string());
+        let start = Instant::now();
+
+        // 突发请求用完容量后被拒绝，等待时间约为一个令牌的回填间隔
+        for _ in 0..6 {
+            assert!(limiter.acquire(key("app"), 6, start).is_ok());
+        }
+        let wait = limiter.acquire(key("app"), 6, start).unwrap_err();
+        assert_eq!(wait.as_secs_f64().ceil() as u64, 10);
+
+        // 其他仓库、其他 IP 独立计数
+        assert!(limiter.acquire(key("lib"), 6, start).is_ok());
+        assert!(limiter.acquire(("10.0.0.2".parse().unwrap(), "app".to_string()), 6, start).is_ok());
+
+        // 回填一个令牌后放行一次
+        let later = start + Duration::from_secs(10);
+        assert!(limiter.acquire(key("app"), 6, later).is_ok());
+        assert!(limiter.acquire(key("app"), 6, later).is_err());
+
+        // 0 表示不限制
+        for _ in 0..100 {
+            assert!(limiter.acquire(key("app"), 0, later).is_ok());
+        }
+    }
+}
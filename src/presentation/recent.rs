@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// 最多记录的最近浏览提交数
+pub const RECENT_COMMITS_CAPACITY: usize = 100;
+
+/// 一次提交详情页浏览
+#[derive(Debug, Clone)]
+pub struct RecentCommit {
+    pub repo_id: i64,
+    pub repo_name: String,
+    pub oid: String,
+    pub summary: String,
+    pub viewed_at: DateTime<Utc>,
+}
+
+/// 最近浏览的提交（仅内存，重启后清空），同一提交只保留最近一次
+pub struct RecentCommits {
+    capacity: usize,
+    /// 最新的在前
+    entries: Mutex<VecDeque<RecentCommit>>,
+}
+
+impl RecentCommits {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// 记录一次浏览，超出容量时丢弃最早的记录
+    pub fn record(&self, repo_id: i64, repo_name: &str, oid: &str, summary: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|e| e.repo_id != repo_id || e.oid != oid);
+        entries.push_front(RecentCommit {
+            repo_id,
+            repo_name: repo_name.to_string(),
+            oid: oid.to_string(),
+            summary: summary.to_string(),
+            viewed_at: Utc::now(),
+        });
+        entries.truncate(self.capacity);
+    }
+
+    /// 最近浏览的 limit 个提交，最新的在前
+    pub fn recent(&self, limit: usize) -> Vec<RecentCommit> {
+        self.entries.lock().unwrap().iter().take(limit).cloned().collect()
+    }
+}
+
+impl Default for RecentCommits {
+    fn default() -> Self {
+        Self::new(RECENT_COMMITS_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_commits_distinct_and_bounded() {
+        let recent = RecentCommits::new(2);
+        recent.record(1, "a", "aaa", "first");
+        recent.record(1, "a", "bbb", "second");
+        // 再次浏览移到最前，不重复记录
+        recent.record(1, "a", "aaa", "first");
+        let oids: Vec<String> = recent.recent(10).into_iter().map(|e| e.oid).collect();
+        assert_eq!(oids, vec!["aaa", "bbb"]);
+
+        // 不同仓库的相同 OID 分别记录；超出容量丢弃最早的
+        recent.record(2, "b", "aaa", "first");
+        let entries: Vec<(i64, String)> = recent.recent(10).into_iter().map(|e| (e.repo_id, e.oid)).collect();
+        assert_eq!(entries, vec![(2, "aaa".to_string()), (1, "aaa".to_string())]);
+        assert_eq!(recent.recent(1).len(), 1);
+    }
+}
@@ -1,7 +1,19 @@
-use axum::{Router, routing::{get, post}};
+use axum::{Router, http::HeaderMap, middleware, routing::{get, post}};
+use std::path::PathBuf;
 use std::sync::Arc;
+use sqlx::SqlitePool;
+use crate::presentation::auth::constant_time_eq;
+use crate::presentation::templates::LayoutContext;
 use crate::presentation::handlers;
-use crate::infrastructure::cache::MokaCache;
+use crate::presentation::rate_limit::RateLimiter;
+use crate::presentation::recent::RecentCommits;
+use crate::infrastructure::cache::TieredCache;
+use crate::services::scheduler::IndexerScheduler;
+use crate::shared::metrics::Metrics;
+use crate::domain::entities::Repository;
+use crate::shared::error::GitxError;
+use crate::shared::result::Result;
+use crate::shared::validation;
 
 /// 应用状态（新架构）
 pub struct AppContext {
@@ -9,25 +21,81 @@ pub struct AppContext {
     pub commit_store: Arc<dyn crate::ports::commit::CommitPort>,
     pub branch_store: Arc<dyn crate::ports::branch::BranchPort>,
     pub git_client: Arc<dyn crate::ports::git::GitPort>,
-    #[allow(dead_code)]  // 后续功能会使用
-    pub cache: Arc<MokaCache>,  // 使用具体类型
-    #[allow(dead_code)]  // 后续功能会使用
-    pub config: Arc<crate::shared::config::Config>,
+    pub scheduler: Arc<IndexerScheduler>,
+    /// 内存 + 磁盘（可选）两级缓存
+    pub cache: Arc<TieredCache>,
+    /// 可热加载的配置
+    pub config: crate::shared::config::ConfigHandle,
+    pub db_pool: SqlitePool,
+    pub metrics: Arc<Metrics>,
+    /// 写操作限流状态
+    pub rate_limiter: Arc<RateLimiter>,
+    /// 最近浏览的提交（仅内存）
+    pub recent_commits: Arc<RecentCommits>,
+}
+
+impl AppContext {
+    /// 按名称查找仓库（名称先经过校验）
+    pub async fn find_repo_by_name(&self, name: &str) -> Result<Repository> {
+        validation::validate_repo_name(name)?;
+        self.repository_store
+            .find_by_name(name)
+            .await?
+            .ok_or_else(|| GitxError::RepositoryNotFound(name.to_string()))
+    }
+
+    /// 页面布局信息，随配置热加载更新
+    pub fn layout(&self) -> LayoutContext {
+        LayoutContext::from(&self.config.load().ui)
+    }
+
+    /// 校验管理令牌（未配置 admin.token 时不校验）
+    pub fn require_admin(&self, headers: &HeaderMap) -> Result<()> {
+        let config = self.config.load();
+        let Some(token) = &config.admin.token else {
+            return Ok(());
+        };
+        let provided = headers.get("X-Admin-Token").map(|v| v.as_bytes()).unwrap_or_default();
+        if !constant_time_eq(provided, token.expose().as_bytes()) {
+            return Err(GitxError::Unauthorized("invalid admin token".to_string()));
+        }
+        Ok(())
+    }
+
+    /// 仓库的磁盘路径，必须位于配置的项目根目录之下
+    pub fn repo_path(&self, repo: &Repository) -> Result<PathBuf> {
+        if repo.is_missing() {
+            return Err(GitxError::RepositoryMissing(repo.name.clone()));
+        }
+        validation::resolve_repo_path(&repo.path, &validation::allowed_base_paths(&self.config.load()))
+    }
 }
 
 /// 创建应用路由
 pub fn create_app_router(ctx: Arc<AppContext>) -> Router {
+    let db_pool = ctx.db_pool.clone();
+    let metrics = ctx.metrics.clone();
+    let server_timing = ctx.config.load().server.server_timing;
+    // 开销较大的写操作按 IP + 仓库限流，只读路由不受影响
+    let rate_limited = middleware::from_fn_with_state(ctx.clone(), crate::presentation::rate_limit::rate_limit);
+    let require_api_key = middleware::from_fn_with_state(ctx.clone(), crate::presentation::auth::require_api_key);
+
     Router::new()
         // 主页 - 仓库列表
         .route("/", get(handlers::repository::list_repositories))
         
+        // Prometheus 指标
+        .route("/metrics", get(handlers::metrics::metrics))
+        
         // UI 路由 - 仓库页面
         .route("/{repo}/summary", get(handlers::repository::repo_summary))
         .route("/{repo}/log", get(handlers::repository::repo_log))
         .route("/{repo}/commit", get(handlers::repository::repo_commit))
+        .route("/{repo}/commits/{oid}", get(handlers::repository::repo_commit_by_oid))
         .route("/{repo}/diff-beta", get(handlers::repository::repo_diff))
-        .route("/{repo}/api/cherry-pick", post(handlers::repository::api_cherry_pick))
-        .route("/{repo}/api/push", post(handlers::repository::api_push))
+        .route("/{repo}/raw/{rev}/{*path}", get(handlers::file::repo_raw_file))
+        .route("/{repo}/api/cherry-pick", post(handlers::repository::api_cherry_pick).layer(rate_limited.clone()))
+        .route("/{repo}/api/push", post(handlers::repository::api_push).layer(rate_limited))
         .route("/{repo}/api/sync", post(handlers::repository::api_sync_repository_by_name))
         .route("/{repo}/api/merge", post(handlers::repository::api_merge))
         
@@ -35,20 +103,73 @@ pub fn create_app_router(ctx: Arc<AppContext>) -> Router {
         .nest("/api", api_routes())
         
         .with_state(ctx)
+        
+        // 健康检查（只依赖数据库连接池）
+        .merge(health_routes(db_pool))
+        
+        // API key 认证（auth.enabled 时生效）
+        .layer(require_api_key)
+        
+        // API 错误转为 JSON
+        .layer(middleware::from_fn(crate::presentation::middleware::json_errors))
+        
+        // 请求计数
+        .layer(middleware::from_fn_with_state(metrics, crate::presentation::middleware::track_http_requests))
+        
+        // 请求 ID、耗时日志与 Server-Timing（最外层，覆盖认证失败等提前返回的请求）
+        .layer(middleware::from_fn_with_state(server_timing, crate::presentation::middleware::trace_requests))
+}
+
+/// 健康检查路由
+fn health_routes(pool: SqlitePool) -> Router {
+    Router::new()
+        .route("/healthz", get(handlers::health::healthz))
+        .route("/readyz", get(handlers::health::readyz))
+        .with_state(pool)
 }
 
 /// API 路由
 fn api_routes() -> Router<Arc<AppContext>> {
     Router::new()
         // 仓库 API
-        .route("/repositories", get(handlers::repository::api_list_repositories))
+        .route("/repositories", get(handlers::repository::api_list_repositories).post(handlers::repository::api_create_repository))
         .route("/repositories/{id}", get(handlers::repository::api_get_repository))
         .route("/repositories/{id}/sync", get(handlers::repository::api_sync_repository))
+        .route("/repositories/{id}/reindex", post(handlers::repository::api_reindex_repository))
+        .route("/repositories/{id}/index-status", get(handlers::repository::api_index_status))
         
         // 提交 API
         .route("/repositories/{id}/commits", get(handlers::commit::api_list_commits))
+        .route("/repositories/{id}/commits:batchGet", post(handlers::commit::api_batch_get_commits))
         .route("/repositories/{id}/commits/{oid}", get(handlers::commit::api_get_commit))
+        .route("/repositories/{id}/history", get(handlers::commit::api_file_history))
+        .route("/repositories/{id}/graph", get(handlers::commit::api_commit_graph))
+        .route("/repositories/{id}/export.jsonl", get(handlers::commit::api_export_commits))
+        
+        // 统计 API
+        .route("/repositories/{id}/stats", get(handlers::stats::api_repository_stats))
+        
+        // 文件 API
+        .route("/repositories/{id}/blame", get(handlers::file::api_blame))
+        .route("/repositories/{id}/file-diff", get(handlers::file::api_file_diff))
+        .route("/repositories/{id}/tree", get(handlers::file::api_tree))
         
         // 分支 API
         .route("/repositories/{id}/branches", get(handlers::branch::api_list_branches))
+        .route("/repositories/{id}/branches/{name}/commits", get(handlers::commit::api_list_branch_commits))
+        .route("/repositories/{id}/compare", get(handlers::branch::api_compare_branches))
+        
+        // 标签 API
+        .route("/repositories/{id}/tags", get(handlers::tag::api_list_tags))
+        .route("/repositories/{id}/tags/{*name}", get(handlers::tag::api_get_tag))
+        
+        // 最近浏览的提交
+        .route("/activity/recent", get(handlers::commit::api_recent_commits))
+        
+        // Webhook API
+        .route("/webhook", post(handlers::webhook::api_webhook))
+        
+        // 配置查看与热加载
+        .route("/config", get(handlers::config::api_get_config))
+        .route("/config/reload", post(handlers::config::api_reload_config))
 }
@@ -1,10 +1,37 @@
 use askama::Template;
+use crate::shared::config::UiConfig;
+
+/// 各页面共用的布局信息（站点名称、logo、自定义样式），来自 `[ui]` 配置
+#[derive(Clone)]
+pub struct LayoutContext {
+    pub site_title: String,
+    pub logo_url: Option<String>,
+    pub custom_css_url: Option<String>,
+}
+
+impl From<&UiConfig> for LayoutContext {
+    fn from(ui: &UiConfig) -> Self {
+        Self {
+            site_title: ui.site_title.clone(),
+            logo_url: ui.logo_url.clone(),
+            custom_css_url: ui.custom_css_url.clone(),
+        }
+    }
+}
 
 /// 主页 - 仓库列表
 #[derive(Template)]
 #[template(path = "index_simple.html")]
 pub struct IndexTemplate {
+    pub layout: LayoutContext,
     pub repositories: Vec<RepoItem>,
+    /// 搜索关键字（空字符串表示不过滤）
+    pub query: String,
+    /// 当前页码，从 1 开始
+    pub page: i64,
+    pub total_pages: i64,
+    /// 匹配的仓库总数
+    pub total: i64,
 }
 
 #[derive(Clone)]
@@ -13,36 +40,57 @@ pub struct RepoItem {
     pub path: String,
     pub description: Option<String>,
     pub last_modified: String,
+    /// 磁盘上的路径不存在
+    pub missing: bool,
 }
 
 /// 仓库概览页
 #[derive(Template)]
 #[template(path = "summary_simple.html")]
 pub struct SummaryTemplate {
+    pub layout: LayoutContext,
     pub repo_name: String,
     pub repo_path: String,
     pub branches: Vec<BranchItem>,
+    pub tags: Vec<TagItem>,
     pub all_branches: Vec<String>,
 }
 
 #[derive(Clone)]
 pub struct BranchItem {
     pub name: String,
+    /// 已索引的提交数（带千位分隔符）
+    pub commit_count: String,
     pub commit_sha: String,
     pub commit_message: String,
     pub author: String,
     pub time: String,
 }
 
+#[derive(Clone)]
+pub struct TagItem {
+    pub name: String,
+    pub commit_sha: String,
+    pub message: String,
+    pub tagger: String,
+    pub time: String,
+}
+
 /// 提交日志页
 #[derive(Template)]
 #[template(path = "log_simple.html")]
 pub struct LogTemplate {
+    pub layout: LayoutContext,
     pub repo_name: String,
     pub commits: Vec<CommitItem>,
     pub branch: Option<String>,
+    pub author: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
     pub has_more: bool,
-    pub next_offset: usize,
+    pub next_cursor: String,
+    /// 下一页之前的提交数，随游标一起传递用于判断是否还有下一页
+    pub next_offset: i64,
     pub all_branches: Vec<String>,
 }
 
@@ -50,12 +98,17 @@ pub struct LogTemplate {
 pub struct CommitItem {
     pub sha: String,
     pub sha_short: String,
-    pub message: String,
-    pub summary: String,  // 为模板兼容性添加，与message相同
+    /// 提交信息首行
+    pub summary: String,
+    /// 提交信息正文（不含首行），列表中折叠在首行下方，没有正文时为空
+    pub body: String,
     pub author_name: String,
     pub author_email: String,
     pub committer_time: String,   
      pub is_empty: bool, // 标识是否为空提交（已被 cherry-pick 过）
+    /// 增删行数（未计算统计时为 None）
+    pub insertions: Option<i64>,
+    pub deletions: Option<i64>,
 }
 
 
@@ -63,6 +116,8 @@ pub struct CommitItem {
 #[derive(Template)]
 #[template(path = "commit_simple.html")]
 pub struct CommitTemplate {
+    pub layout: LayoutContext,
+    pub repo_id: i64,
     pub repo_name: String,
     pub commit: CommitDetail,
     pub all_branches: Vec<String>,
@@ -72,25 +127,108 @@ pub struct CommitTemplate {
 pub struct CommitDetail {
     pub sha: String,
     pub tree: String,
-    pub parents: Vec<String>,
+    pub parents: Vec<ParentItem>,
     pub author_name: String,
     pub author_email: String,
     pub author_time: String,
     pub committer_name: String,
     pub committer_email: String,
     pub committer_time: String,
+    /// 转义并自动链接后的提交信息（HTML）
     pub message: String,
     pub diff_stats: String,
+    /// 每个文件的增删行数（diff 过大时同样显示）
+    pub files: Vec<FileStatItem>,
+    /// 带新旧行号的 diff，为空时（如只有二进制文件）显示 `diff`
+    pub hunks: Vec<HunkItem>,
     pub diff: String,
+    /// diff 超过 `git.max_diff_lines`，只显示统计与下载链接
+    pub diff_too_large: bool,
+    pub signature: Option<SignatureItem>,
+    /// 提交已写入数据库（刚推送的提交可能尚未索引）
+    pub indexed: bool,
+}
+
+#[derive(Clone)]
+pub struct ParentItem {
+    pub sha: String,
+    pub summary: String,
+}
+
+#[derive(Clone)]
+pub struct FileStatItem {
+    pub path: String,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Clone)]
+pub struct HunkItem {
+    /// 新路径（删除的文件为旧路径）
+    pub path: String,
+    pub header: String,
+    pub lines: Vec<HunkLineItem>,
+}
+
+#[derive(Clone)]
+pub struct HunkLineItem {
+    /// 行号，新增行没有旧行号、删除行没有新行号时为空
+    pub old_lineno: String,
+    pub new_lineno: String,
+    /// 行类型对应的 CSS 类，与 `diff` 中的一致
+    pub class: &'static str,
+    /// 渲染后的行内容（HTML）
+    pub html: String,
+}
+
+#[derive(Clone)]
+pub struct SignatureItem {
+    pub kind: String,
+    pub signer: String,
 }
 
 /// 分支对比页
 #[derive(Template)]
 #[template(path = "diff_simple.html")]
 pub struct DiffTemplate {
+    pub layout: LayoutContext,
     pub repo_name: String,
     pub from_branch: String,
     pub to_branch: String,
     pub branches: Vec<String>,
     pub commits: Vec<CommitItem>,
+    /// 两个分支末端之间的总体变更，分支不存在或计算失败时为空
+    pub summary: Option<DiffSummary>,
+    /// 两个分支的分叉点与领先/落后提交数，分支不存在或计算失败时为空
+    pub divergence: Option<DivergenceItem>,
+}
+
+/// 分支末端之间的 diff 统计
+pub struct DiffSummary {
+    pub from_sha_short: String,
+    pub to_sha_short: String,
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub files: Vec<DiffFileItem>,
+}
+
+/// 分支末端之间变更的文件
+pub struct DiffFileItem {
+    pub path: String,
+    /// 重命名或复制前的路径
+    pub old_path: Option<String>,
+    /// 变更类型，如 `modified`、`renamed`
+    pub status: &'static str,
+}
+
+/// 源分支相对目标分支的分叉情况
+pub struct DivergenceItem {
+    /// 最近公共祖先，历史无关时为 None
+    pub merge_base_sha: Option<String>,
+    pub merge_base_sha_short: Option<String>,
+    /// 源分支领先（目标分支没有）的提交数
+    pub ahead: usize,
+    /// 源分支落后（只在目标分支上）的提交数
+    pub behind: usize,
 }
\ No newline at end of file
@@ -1,9 +1,16 @@
+use std::cmp::Reverse;
+use std::collections::{HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use futures::stream::{self, StreamExt};
+use glob::{MatchOptions, Pattern};
 use tracing::{debug, warn};
-use crate::shared::config::Config;
+use crate::shared::config::{Config, ProjectConfig};
 use crate::shared::result::Result;
 
+/// 同时扫描的 scan_path 数量
+const SCAN_CONCURRENCY: usize = 4;
+
 /// 仓库发现服务
 pub struct RepositoryDiscovery {
     config: Arc<Config>,
@@ -14,44 +21,42 @@ impl RepositoryDiscovery {
         Self { config }
     }
 
-    /// 发现所有配置的仓库
+    /// 发现所有配置的仓库（按规范化路径去重）
+    ///
+    /// 各 scan_path 并发扫描，结果按项目优先级从高到低排列（同优先级保持配置顺序），
+    /// 调度器按此顺序索引；同一仓库出现在多个项目中时归入优先级高的项目。
     pub async fn discover_all(&self) -> Result<Vec<DiscoveredRepo>> {
-        let mut all_repos = Vec::new();
+        let mut projects: Vec<&ProjectConfig> = self.config.projects.iter().collect();
+        projects.sort_by_key(|p| Reverse(p.priority));
 
-        for project in &self.config.projects {
+        let mut scans: Vec<(PathBuf, usize, Arc<Vec<Pattern>>)> = Vec::new();
+        for project in projects {
+            // 模式已在加载配置时校验，这里忽略无法解析的条目
+            let exclude: Arc<Vec<Pattern>> = Arc::new(project.exclude.iter().filter_map(|p| Pattern::new(p).ok()).collect());
             for scan_path in &project.scan_paths {
-                let full_path = project.base_path.join(scan_path);
-                
-                if !full_path.exists() {
+                scans.push((project.base_path.join(scan_path), project.max_depth, exclude.clone()));
+            }
+        }
+
+        // buffered 保持输入顺序，优先级排序在并发扫描后依然有效
+        let results: Vec<Vec<DiscoveredRepo>> = stream::iter(scans)
+            .map(|(full_path, max_depth, exclude)| async move {
+                if !tokio::fs::try_exists(&full_path).await.unwrap_or(false) {
                     warn!("Scan path does not exist: {}", full_path.display());
-                    continue;
+                    return Vec::new();
                 }
+                // max_depth 为 0 时只检查 scan_path 本身，否则向下递归查找
+                self.scan(&full_path, max_depth, &exclude).await
+            })
+            .buffered(SCAN_CONCURRENCY)
+            .collect()
+            .await;
 
-                // 只检查 scan_path 指定的路径是否为 git 仓库，不递归子目录
-                if self.is_git_repo(&full_path) {
-                    let name = full_path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("unknown")
-                        .to_string();
-
-                    debug!("Found repository: {}", full_path.display());
-                    
-                    let canonical_path = match full_path.canonicalize() {
-                        Ok(p) => p,
-                        Err(e) => {
-                            warn!("Failed to canonicalize path {}: {}", full_path.display(), e);
-                            full_path.clone()
-                        }
-                    };
-                    
-                    all_repos.push(DiscoveredRepo {
-                        name,
-                        path: canonical_path,
-                    });
-                } else {
-                    debug!("Path is not a git repository: {}", full_path.display());
-                }
+        let mut all_repos = Vec::new();
+        let mut seen = HashSet::new();
+        for repo in results.into_iter().flatten() {
+            if seen.insert(repo.path.clone()) {
+                all_repos.push(repo);
             }
         }
 
@@ -59,9 +64,91 @@ impl RepositoryDiscovery {
         Ok(all_repos)
     }
 
-    /// 检查路径是否为 Git 仓库
+    /// 在目录树中查找仓库（广度优先，深度受限，不跟随符号链接，跳过 .git 目录）
+    async fn scan(&self, root: &Path, max_depth: usize, exclude: &[Pattern]) -> Vec<DiscoveredRepo> {
+        let mut repos = Vec::new();
+        let mut queue = VecDeque::from([(root.to_path_buf(), 0usize)]);
+
+        while let Some((dir, depth)) = queue.pop_front() {
+            let bare = self.is_bare_repo(&dir);
+            if self.is_git_repo(&dir) {
+                // 被排除的仓库连同其下的嵌套仓库一起跳过
+                if Self::is_excluded(&dir, exclude) {
+                    debug!("Skipping excluded repository: {}", dir.display());
+                    continue;
+                }
+                debug!("Found repository: {}{}", dir.display(), if bare { " (bare)" } else { "" });
+                repos.push(Self::to_discovered(&dir, bare));
+            } else if depth == 0 {
+                debug!("Path is not a git repository: {}", dir.display());
+            }
+
+            // 裸仓库目录下只有 git 内部数据，不再向下查找
+            if depth >= max_depth || bare {
+                continue;
+            }
+
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) => {
+                    warn!("Failed to read directory {}: {}", dir.display(), e);
+                    continue;
+                }
+            };
+
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let is_dir = entry.file_type().await.map(|t| t.is_dir()).unwrap_or(false);
+                if is_dir && entry.file_name() != ".git" {
+                    queue.push_back((entry.path(), depth + 1));
+                }
+            }
+        }
+
+        repos
+    }
+
+    /// 目录名或完整路径匹配任一排除模式（不区分大小写）
+    fn is_excluded(path: &Path, exclude: &[Pattern]) -> bool {
+        let options = MatchOptions {
+            case_sensitive: false,
+            ..MatchOptions::new()
+        };
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        exclude
+            .iter()
+            .any(|p| p.matches_with(name, options) || p.matches_path_with(path, options))
+    }
+
+    fn to_discovered(path: &Path, bare: bool) -> DiscoveredRepo {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let canonical_path = match path.canonicalize() {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("Failed to canonicalize path {}: {}", path.display(), e);
+                path.to_path_buf()
+            }
+        };
+
+        DiscoveredRepo {
+            name,
+            path: canonical_path,
+            bare,
+        }
+    }
+
+    /// 检查路径是否为 Git 仓库（工作区或裸仓库）
     fn is_git_repo(&self, path: &Path) -> bool {
-        path.join(".git").exists() || path.join("packed-refs").exists()
+        path.join(".git").exists() || self.is_bare_repo(path)
+    }
+
+    /// 裸仓库：目录下直接包含 HEAD、objects 和 refs
+    fn is_bare_repo(&self, path: &Path) -> bool {
+        path.join("HEAD").is_file() && path.join("objects").is_dir() && path.join("refs").is_dir()
     }
 }
 
@@ -70,4 +157,91 @@ impl RepositoryDiscovery {
 pub struct DiscoveredRepo {
     pub name: String,
     pub path: PathBuf,
+    /// 裸仓库没有工作区，只支持只读操作
+    pub bare: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::config::*;
+
+    fn discovery_for(base: &Path, scan_paths: &[&str], max_depth: usize) -> RepositoryDiscovery {
+        discovery_excluding(base, scan_paths, max_depth, &[])
+    }
+
+    fn discovery_excluding(base: &Path, scan_paths: &[&str], max_depth: usize, exclude: &[&str]) -> RepositoryDiscovery {
+        let config = Config {
+            server: ServerConfig::default(),
+            database: DatabaseConfig::default(),
+            git: GitConfig::default(),
+            indexer: IndexerConfig::default(),
+            cache: CacheConfig::default(),
+            webhook: WebhookConfig::default(),
+            logging: LoggingConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            admin: AdminConfig::default(),
+            auth: AuthConfig::default(),
+            ui: UiConfig::default(),
+            projects: vec![ProjectConfig {
+                name: "test".to_string(),
+                base_path: base.to_path_buf(),
+                scan_paths: scan_paths.iter().map(|s| s.to_string()).collect(),
+                max_depth,
+                remote: DEFAULT_REMOTE.to_string(),
+                exclude: exclude.iter().map(|s| s.to_string()).collect(),
+                branch_include: Vec::new(),
+                branch_exclude: Vec::new(),
+                priority: 0,
+                linkify: LinkifyConfig::default(),
+            }],
+        };
+        RepositoryDiscovery::new(Arc::new(config))
+    }
+
+    #[tokio::test]
+    async fn test_discover_recursive() {
+        let dir = tempfile::tempdir().unwrap();
+        for repo in ["a", "group/b", "group/deep/er/c", "a/nested"] {
+            std::fs::create_dir_all(dir.path().join(repo).join(".git/refs")).unwrap();
+        }
+        // 裸仓库，内部目录不会被继续扫描
+        let bare = dir.path().join("srv/d.git");
+        std::fs::create_dir_all(bare.join("objects")).unwrap();
+        std::fs::create_dir_all(bare.join("refs/heads/.git")).unwrap();
+        std::fs::write(bare.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+
+        let names = |repos: Vec<DiscoveredRepo>| {
+            let mut names: Vec<String> = repos.into_iter().map(|r| r.name).collect();
+            names.sort();
+            names
+        };
+
+        // 默认不递归
+        let repos = discovery_for(dir.path(), &["."], 0).discover_all().await.unwrap();
+        assert!(repos.is_empty());
+
+        // 深度受限，.git 内部不会被当作仓库
+        let repos = discovery_for(dir.path(), &["."], 2).discover_all().await.unwrap();
+        assert_eq!(names(repos), vec!["a", "b", "d.git", "nested"]);
+
+        // 重叠的扫描路径按规范化路径去重
+        let repos = discovery_for(dir.path(), &[".", "group"], 4).discover_all().await.unwrap();
+        assert!(repos.iter().all(|r| r.bare == (r.name == "d.git")));
+        assert_eq!(names(repos), vec!["a", "b", "c", "d.git", "nested"]);
+
+        // 按目录名或完整路径排除，排除的仓库下的嵌套仓库一并跳过
+        let repos = discovery_excluding(dir.path(), &["."], 4, &["A", "*/DEEP/*"]).discover_all().await.unwrap();
+        assert_eq!(names(repos), vec!["b", "d.git"]);
+
+        // 高优先级项目的仓库排在前面
+        let mut config = (*discovery_for(dir.path(), &["group/deep/er/c"], 0).config).clone();
+        let mut urgent = config.projects[0].clone();
+        urgent.scan_paths = vec!["srv/d.git".to_string()];
+        urgent.priority = 10;
+        config.projects.push(urgent);
+        let repos = RepositoryDiscovery::new(Arc::new(config)).discover_all().await.unwrap();
+        let order: Vec<String> = repos.into_iter().map(|r| r.name).collect();
+        assert_eq!(order, vec!["d.git", "c"]);
+    }
 }
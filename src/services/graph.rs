@@ -0,0 +1,78 @@
+/// 为提交图分配绘制列（lane）
+///
+/// `commits` 需按子提交在前的顺序排列（如 committer_time 倒序），元素为 (oid, parent_oids)。
+/// 每列记录“下一个期望出现的提交”：提交占用等待它的最左一列（没有则占用最左空列），
+/// 其余等待它的列在此处汇合后释放；第一个父提交沿用本列，其他父提交各自新开一列。
+pub fn assign_lanes<S: AsRef<str>>(commits: &[(S, Vec<S>)]) -> Vec<usize> {
+    let mut lanes: Vec<Option<&str>> = Vec::new();
+    let mut result = Vec::with_capacity(commits.len());
+
+    for (oid, parents) in commits {
+        let oid = oid.as_ref();
+        let lane = match lanes.iter().position(|l| *l == Some(oid)) {
+            Some(lane) => lane,
+            None => free_lane(&mut lanes),
+        };
+
+        // 其他分支在这里汇合
+        for slot in lanes.iter_mut() {
+            if *slot == Some(oid) {
+                *slot = None;
+            }
+        }
+
+        let mut parents = parents.iter().map(AsRef::as_ref);
+        lanes[lane] = parents.next();
+        for parent in parents {
+            if !lanes.contains(&Some(parent)) {
+                let slot = free_lane(&mut lanes);
+                lanes[slot] = Some(parent);
+            }
+        }
+
+        // 去掉末尾的空列，保持图尽量窄
+        while lanes.last() == Some(&None) {
+            lanes.pop();
+        }
+        result.push(lane);
+    }
+
+    result
+}
+
+/// 返回最左的空列，没有时在末尾新开一列
+fn free_lane(lanes: &mut Vec<Option<&str>>) -> usize {
+    match lanes.iter().position(Option::is_none) {
+        Some(lane) => lane,
+        None => {
+            lanes.push(None);
+            lanes.len() - 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assign_lanes_merge() {
+        // m 合并了 b2，主线 a1 <- a2 <- m，旁支 a1 <- b1 <- b2
+        let commits = vec![
+            ("m", vec!["a2", "b2"]),
+            ("b2", vec!["b1"]),
+            ("a2", vec!["a1"]),
+            ("b1", vec!["a1"]),
+            ("a1", vec![]),
+        ];
+        assert_eq!(assign_lanes(&commits), vec![0, 1, 0, 1, 0]);
+
+        // 线性历史始终在第 0 列
+        let linear = vec![("c", vec!["b"]), ("b", vec!["a"]), ("a", vec![])];
+        assert_eq!(assign_lanes(&linear), vec![0, 0, 0]);
+
+        // 两个互不相关的分支头
+        let heads = vec![("x", vec!["base"]), ("y", vec!["base"]), ("base", vec![])];
+        assert_eq!(assign_lanes(&heads), vec![0, 1, 0]);
+    }
+}
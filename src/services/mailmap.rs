@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+/// git mailmap：把提交中的作者名/邮箱映射为规范值
+///
+/// 支持的行格式（与 `git check-mailmap` 一致，邮箱与名字均不区分大小写匹配）：
+///
+/// ```text
+/// Proper Name <commit@email>
+/// <proper@email> <commit@email>
+/// Proper Name <proper@email> <commit@email>
+/// Proper Name <proper@email> Commit Name <commit@email>
+/// ```
+#[derive(Debug, Default)]
+pub struct Mailmap {
+    /// 键为 (小写的提交邮箱, 小写的提交名字)，名字为 None 表示只按邮箱匹配
+    entries: HashMap<(String, Option<String>), Mapping>,
+}
+
+#[derive(Debug, Default)]
+struct Mapping {
+    name: Option<String>,
+    email: Option<String>,
+}
+
+impl Mailmap {
+    /// 追加另一份 mailmap，后出现的条目覆盖先前的同名条目
+    pub fn extend(&mut self, content: &str) {
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name1, email1, rest)) = parse_name_email(line) else {
+                continue;
+            };
+
+            let (key, mapping) = match parse_name_email(rest) {
+                // Proper Name <proper@email> [Commit Name] <commit@email>
+                Some((name2, email2, _)) => (
+                    (email2.to_lowercase(), name2.map(str::to_lowercase)),
+                    Mapping {
+                        name: name1.map(String::from),
+                        email: Some(email1.to_string()),
+                    },
+                ),
+                // Proper Name <commit@email>
+                None => (
+                    (email1.to_lowercase(), None),
+                    Mapping {
+                        name: name1.map(String::from),
+                        email: None,
+                    },
+                ),
+            };
+
+            let entry = self.entries.entry(key).or_default();
+            if mapping.name.is_some() {
+                entry.name = mapping.name;
+            }
+            if mapping.email.is_some() {
+                entry.email = mapping.email;
+            }
+        }
+    }
+
+    /// 返回规范化后的 (名字, 邮箱)，没有匹配的条目时原样返回
+    pub fn resolve<'a>(&'a self, name: &'a str, email: &'a str) -> (&'a str, &'a str) {
+        let email_key = email.to_lowercase();
+        let mapping = self
+            .entries
+            .get(&(email_key.clone(), Some(name.to_lowercase())))
+            .or_else(|| self.entries.get(&(email_key, None)));
+
+        match mapping {
+            Some(m) => (
+                m.name.as_deref().unwrap_or(name),
+                m.email.as_deref().unwrap_or(email),
+            ),
+            None => (name, email),
+        }
+    }
+}
+
+/// 解析 `[Name] <email>`，返回 (名字, 邮箱, 剩余部分)；名字为空时为 None
+fn parse_name_email(s: &str) -> Option<(Option<&str>, &str, &str)> {
+    let open = s.find('<')?;
+    let close = open + s[open..].find('>')?;
+    let name = s[..open].trim();
+    let email = s[open + 1..close].trim();
+    Some(((!name.is_empty()).then_some(name), email, &s[close + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mailmap_forms() {
+        let mut mailmap = Mailmap::default();
+        mailmap.extend(
+            "# comment\n\
+             Jane Doe <jane@example.com>\n\
+             <jane@example.com> <jane@old.example.com>\n\
+             Joe Dev <joe@example.com> <JOE@laptop.local>\n\
+             Bot Owner <owner@example.com> ci-bot <bot@example.com>\n",
+        );
+
+        // 只替换名字
+        assert_eq!(mailmap.resolve("jdoe", "jane@example.com"), ("Jane Doe", "jane@example.com"));
+        // 只替换邮箱
+        assert_eq!(mailmap.resolve("Jane", "jane@old.example.com"), ("Jane", "jane@example.com"));
+        // 同时替换，邮箱不区分大小写
+        assert_eq!(mailmap.resolve("joe", "joe@Laptop.local"), ("Joe Dev", "joe@example.com"));
+        // 需要名字和邮箱同时匹配
+        assert_eq!(mailmap.resolve("CI-Bot", "bot@example.com"), ("Bot Owner", "owner@example.com"));
+        assert_eq!(mailmap.resolve("someone", "bot@example.com"), ("someone", "bot@example.com"));
+        // 未收录的作者原样返回
+        assert_eq!(mailmap.resolve("Other", "other@example.com"), ("Other", "other@example.com"));
+    }
+}
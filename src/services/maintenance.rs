@@ -0,0 +1,78 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use sqlx::SqlitePool;
+use tokio::time;
+use tracing::{debug, error, info};
+use crate::infrastructure::sqlite;
+use crate::services::scheduler::IndexerScheduler;
+use crate::shared::config::DatabaseConfig;
+
+/// 数据库维护任务 - 定期 `PRAGMA optimize`，空闲时 `VACUUM` 回收空间
+pub struct DatabaseMaintenance {
+    config: DatabaseConfig,
+    pool: SqlitePool,
+    /// 用于判断是否有索引在进行，VACUUM 期间暂停新的索引
+    scheduler: Arc<IndexerScheduler>,
+}
+
+impl DatabaseMaintenance {
+    pub fn new(config: DatabaseConfig, pool: SqlitePool, scheduler: Arc<IndexerScheduler>) -> Self {
+        Self { config, pool, scheduler }
+    }
+
+    /// 启动维护循环（长期运行）；`optimize_interval_secs` 为 0 时立即返回
+    pub async fn start(self) {
+        if self.config.optimize_interval_secs == 0 {
+            return;
+        }
+        let period = Duration::from_secs(self.config.optimize_interval_secs);
+        let vacuum_every = Duration::from_secs(self.config.vacuum_interval_secs);
+        // 启动时通常正在进行首次索引，第一次维护推迟一个周期
+        let mut interval = time::interval_at(time::Instant::now() + period, period);
+        let mut last_vacuum = Instant::now();
+
+        info!(
+            "Database maintenance started, optimize every {}s, vacuum every {}s",
+            self.config.optimize_interval_secs, self.config.vacuum_interval_secs
+        );
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = sqlite::optimize(&self.pool).await {
+                error!("PRAGMA optimize failed: {}", e);
+            }
+
+            if vacuum_every.is_zero() || last_vacuum.elapsed() < vacuum_every {
+                continue;
+            }
+            // VACUUM 锁住整个数据库，索引进行中时推迟到下一个周期
+            let Some(_paused) = self.scheduler.try_pause_indexing() else {
+                debug!("Indexing in progress, postponing VACUUM");
+                continue;
+            };
+            self.vacuum().await;
+            last_vacuum = Instant::now();
+        }
+    }
+
+    async fn vacuum(&self) {
+        let path = &self.config.sqlite_path;
+        let before = file_size(path);
+        let started = Instant::now();
+        match sqlite::vacuum(&self.pool).await {
+            Ok(()) => info!(
+                "VACUUM finished in {:.1}s: {} -> {} bytes",
+                started.elapsed().as_secs_f64(),
+                before,
+                file_size(path)
+            ),
+            Err(e) => error!("VACUUM failed: {}", e),
+        }
+    }
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
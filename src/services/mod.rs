@@ -1,3 +1,6 @@
 pub mod scheduler;
 pub mod worker;
 pub mod discovery;
+pub mod mailmap;
+pub mod graph;
+pub mod maintenance;
@@ -1,68 +1,147 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, OwnedMutexGuard, OwnedRwLockWriteGuard, RwLock, Semaphore};
 use tokio::time;
-use tracing::{info, error};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, info_span, debug, error, warn, Instrument};
+use crate::domain::entities::{IndexState, Repository, RepositoryStatus};
 use crate::ports::repository::RepositoryPort;
 use crate::ports::commit::CommitPort;
 use crate::ports::branch::BranchPort;
 use crate::ports::git::GitPort;
-use crate::shared::config::Config;
+use crate::shared::config::ConfigHandle;
+use crate::shared::metrics::Metrics;
+use crate::shared::error::GitxError;
 use crate::shared::result::Result;
-use crate::services::discovery::RepositoryDiscovery;
-use crate::services::worker::IndexWorker;
+use crate::shared::validation;
+use crate::services::discovery::{DiscoveredRepo, RepositoryDiscovery};
+use crate::services::worker::{IndexResult, IndexWorker};
 
 /// 索引调度器 - 定期扫描和调度索引任务
 pub struct IndexerScheduler {
-    config: Arc<Config>,
+    /// 可热加载的配置，每个周期读取最新快照
+    config: ConfigHandle,
     repository_store: Arc<dyn RepositoryPort>,
     commit_store: Arc<dyn CommitPort>,
     branch_store: Arc<dyn BranchPort>,
     git_client: Arc<dyn GitPort>,
+    /// 限制同时索引的仓库数量（由 indexer.worker_threads 决定）
+    index_permits: Arc<Semaphore>,
+    metrics: Arc<Metrics>,
+    /// 停止信号，调度循环每个 tick 前检查
+    shutdown: CancellationToken,
+    /// 索引期间持有读锁；数据库维护（VACUUM）尝试取写锁，保证两者不同时进行
+    index_lock: Arc<RwLock<()>>,
+    /// 串行化仓库登记，同一周期内经不同路径发现的同一仓库不会被重复登记
+    registration_lock: Arc<Mutex<()>>,
+    /// 每个仓库一把锁，同一仓库的定时索引、手动触发与重建依次进行
+    repository_locks: Arc<std::sync::Mutex<HashMap<i64, Arc<Mutex<()>>>>>,
 }
 
 impl IndexerScheduler {
     pub fn new(
-        config: Arc<Config>,
+        config: ConfigHandle,
         repository_store: Arc<dyn RepositoryPort>,
         commit_store: Arc<dyn CommitPort>,
         branch_store: Arc<dyn BranchPort>,
         git_client: Arc<dyn GitPort>,
+        metrics: Arc<Metrics>,
     ) -> Self {
+        let permits = config.load().indexer.worker_threads.max(1);
         Self {
             config,
             repository_store,
             commit_store,
             branch_store,
             git_client,
+            index_permits: Arc::new(Semaphore::new(permits)),
+            metrics,
+            shutdown: CancellationToken::new(),
+            index_lock: Arc::new(RwLock::new(())),
+            registration_lock: Arc::new(Mutex::new(())),
+            repository_locks: Arc::default(),
         }
     }
 
+    /// 获取仓库的索引锁，持有期间同一仓库的其他索引任务等待
+    async fn lock_repository(&self, repository_id: i64) -> OwnedMutexGuard<()> {
+        let lock = self
+            .repository_locks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(repository_id)
+            .or_default()
+            .clone();
+        lock.lock_owned().await
+    }
+
+    /// 通知调度器停止：正在进行的索引会完成，排队中的仓库不再开始
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+
+    /// 没有索引在进行时返回写锁，持有期间新的索引会等待；正在索引时返回 None
+    pub fn try_pause_indexing(&self) -> Option<OwnedRwLockWriteGuard<()>> {
+        self.index_lock.clone().try_write_owned().ok()
+    }
+
     /// 启动调度器（长期运行）
     pub async fn start(self: &Arc<Self>) {
-        if !self.config.indexer.enabled {
-            info!("Indexer is disabled in configuration");
-            return;
-        }
-
-        let interval_duration = Duration::from_secs(self.config.indexer.interval_secs);
-        let mut interval = time::interval(interval_duration);
+        let mut config_updates = self.config.subscribe();
+        let mut interval_secs = self.config.load().indexer.interval_secs;
+        let mut interval = time::interval(Duration::from_secs(interval_secs));
 
-        info!(
-            "Indexer scheduler started, interval: {}s",
-            self.config.indexer.interval_secs
-        );
+        info!("Indexer scheduler started, interval: {}s", interval_secs);
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                Ok(()) = config_updates.changed() => {
+                    // 配置热加载后按新间隔重新计时
+                    let new_secs = config_updates.borrow_and_update().indexer.interval_secs;
+                    if new_secs != interval_secs {
+                        interval_secs = new_secs;
+                        let period = Duration::from_secs(interval_secs);
+                        interval = time::interval_at(time::Instant::now() + period, period);
+                        info!("Indexer interval changed to {}s", interval_secs);
+                    }
+                    continue;
+                }
+                _ = self.shutdown.cancelled() => {
+                    info!("Indexer scheduler stopped");
+                    return;
+                }
+            }
+
+            let config = self.config.load();
+            if !config.indexer.enabled {
+                info!("Indexer is disabled in configuration, skipping cycle");
+                continue;
+            }
             
             info!("Starting scheduled indexing cycle");
+            let _indexing = self.index_lock.read().await;
+            let started = Instant::now();
             
             match self.run_index_cycle().await {
                 Ok(stats) => {
+                    self.metrics.record_index_cycle(
+                        stats.repos_discovered,
+                        stats.repos_synced,
+                        stats.repos_failed,
+                        started.elapsed(),
+                    );
                     info!(
-                        "Index cycle completed: {} repos discovered, {} synced",
+                        repos_discovered = stats.repos_discovered,
+                        repos_synced = stats.repos_synced,
+                        repos_failed = stats.repos_failed,
+                        repos_skipped = stats.repos_skipped,
+                        "Index cycle completed: {} repos discovered, {} synced, {} skipped",
                         stats.repos_discovered,
-                        stats.repos_synced
+                        stats.repos_synced,
+                        stats.repos_skipped
                     );
                 }
                 Err(e) => {
@@ -77,13 +156,18 @@ impl IndexerScheduler {
         let mut stats = IndexStats::default();
 
         // 1. 发现仓库
-        let discovery = RepositoryDiscovery::new(self.config.clone());
+        let discovery = RepositoryDiscovery::new(self.config.load());
         let discovered_repos = discovery.discover_all().await?;
         stats.repos_discovered = discovered_repos.len();
 
         info!("Discovered {} repositories", stats.repos_discovered);
 
-        // 2. 并行为每个仓库执行索引
+        // 先标记路径已不存在的仓库，移动到新位置的仓库在索引时才能按指纹关联
+        if let Err(e) = self.reconcile_missing(&discovered_repos).await {
+            error!("Failed to check for missing repositories: {}", e);
+        }
+
+        // 2. 并行为每个仓库执行索引（通过信号量限制并发数）
         let repo_count = discovered_repos.len();
         let tasks: Vec<_> = discovered_repos
             .into_iter()
@@ -94,8 +178,40 @@ impl IndexerScheduler {
                 let commit_store = self.commit_store.clone();
                 let branch_store = self.branch_store.clone();
                 let git_client = self.git_client.clone();
+                let index_permits = self.index_permits.clone();
+                let metrics = self.metrics.clone();
+                let shutdown = self.shutdown.clone();
+                let index_lock = self.index_lock.clone();
+                let registration_lock = self.registration_lock.clone();
+                let repository_locks = self.repository_locks.clone();
                 
                 tokio::spawn(async move {
+                    let min_resync = Duration::from_secs(config.load().indexer.min_resync_interval_secs);
+                    if !min_resync.is_zero() && Self::synced_within(&*repository_store, &repo_info, min_resync).await? {
+                        debug!("[{}/{}] Synced recently, skipping: {}", idx + 1, repo_count, repo_info.name);
+                        return Ok(IndexOutcome::Skipped);
+                    }
+
+                    let _permit = match index_permits.clone().try_acquire_owned() {
+                        Ok(permit) => permit,
+                        Err(_) => {
+                            info!(
+                                "[{}/{}] Waiting for an index permit: {} ({} workers busy)",
+                                idx + 1, repo_count, repo_info.name, config.load().indexer.worker_threads
+                            );
+                            index_permits
+                                .clone()
+                                .acquire_owned()
+                                .await
+                                .map_err(|e| GitxError::Internal(e.to_string()))?
+                        }
+                    };
+
+                    if shutdown.is_cancelled() {
+                        info!("[{}/{}] Shutting down, skipping: {}", idx + 1, repo_count, repo_info.name);
+                        return Ok(IndexOutcome::Skipped);
+                    }
+
                     info!("[{}/{}] Starting to index: {}", idx + 1, repo_count, repo_info.name);
                     
                     // 创建临时scheduler实例来调用index_repository
@@ -105,11 +221,20 @@ impl IndexerScheduler {
                         commit_store,
                         branch_store,
                         git_client,
+                        index_permits,
+                        metrics,
+                        shutdown,
+                        index_lock,
+                        registration_lock,
+                        repository_locks,
                     };
                     
-                    let result = temp_scheduler.index_repository(&repo_info).await;
-                    if let Ok(true) = result {
-                        info!("[{}/{}] ✓ Finished indexing: {}", idx + 1, repo_count, repo_info.name);
+                    let result = temp_scheduler.index_repository(&repo_info).await.map(|indexed| match indexed {
+                        true => IndexOutcome::Indexed,
+                        false => IndexOutcome::Skipped,
+                    });
+                    if matches!(result, Ok(IndexOutcome::Indexed)) {
+                        info!(repo = %repo_info.name, "[{}/{}] ✓ Finished indexing: {}", idx + 1, repo_count, repo_info.name);
                     }
                     result
                 })
@@ -119,11 +244,8 @@ impl IndexerScheduler {
         // 等待所有任务完成
         for task in tasks {
             match task.await {
-                Ok(Ok(indexed)) => {
-                    if indexed {
-                        stats.repos_synced += 1;
-                    }
-                }
+                Ok(Ok(IndexOutcome::Indexed)) => stats.repos_synced += 1,
+                Ok(Ok(IndexOutcome::Skipped)) => stats.repos_skipped += 1,
                 Ok(Err(e)) => {
                     error!("Failed to index repository: {}", e);
                     stats.repos_failed += 1;
@@ -138,35 +260,173 @@ impl IndexerScheduler {
         Ok(stats)
     }
 
-    /// 索引单个仓库
-    async fn index_repository(&self, repo_info: &super::discovery::DiscoveredRepo) -> Result<bool> {
-        // 1. 检查仓库是否已存在
-        let existing_repo = self.repository_store
+    /// 检查已登记仓库的路径：不存在的标记为 missing，超过宽限期后删除，重新出现的恢复为 active
+    async fn reconcile_missing(&self, discovered: &[DiscoveredRepo]) -> Result<()> {
+        let grace = Duration::from_secs(self.config.load().indexer.missing_grace_secs);
+        let discovered: HashSet<&Path> = discovered.iter().map(|r| r.path.as_path()).collect();
+
+        for repo in self.repository_store.list_all().await? {
+            // 无法判断时（如权限错误）按存在处理，避免误删
+            let exists = discovered.contains(Path::new(&repo.path))
+                || tokio::fs::try_exists(&repo.path).await.unwrap_or(true);
+            match (exists, repo.status) {
+                (true, RepositoryStatus::Missing) => {
+                    info!("Repository {} is back at {}", repo.name, repo.path);
+                    self.repository_store.set_status(repo.id, RepositoryStatus::Active).await?;
+                }
+                (false, RepositoryStatus::Active) => {
+                    warn!("Repository {} no longer exists at {}, marking as missing", repo.name, repo.path);
+                    self.repository_store.set_status(repo.id, RepositoryStatus::Missing).await?;
+                }
+                (false, RepositoryStatus::Missing) => {
+                    let expired = repo.missing_since.is_some_and(|since| {
+                        chrono::Utc::now().signed_duration_since(since).to_std().is_ok_and(|age| age >= grace)
+                    });
+                    if !grace.is_zero() && expired {
+                        warn!("Repository {} has been missing for over {}s, deleting it", repo.name, grace.as_secs());
+                        self.repository_store.delete(repo.id).await?;
+                    }
+                }
+                (true, RepositoryStatus::Active) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// 按指纹匹配路径尚未登记的仓库：指向同一目录的已登记仓库视为重复，其次是唯一的 missing 仓库。
+    /// fork 与独立克隆共享根提交，指纹相同但目录不同时只记录日志
+    async fn match_fingerprint(&self, fingerprint: &str, path: &Path) -> Result<FingerprintMatch> {
+        let candidates = self.repository_store.find_by_fingerprint(fingerprint).await?;
+        for repo in candidates.iter().filter(|r| !r.is_missing()) {
+            if validation::same_location(Path::new(&repo.path), path).await {
+                return Ok(FingerprintMatch::Duplicate(repo.clone()));
+            }
+            info!(
+                "{} shares fingerprint {} with {} at {}, registering separately",
+                path.display(),
+                fingerprint,
+                repo.name,
+                repo.path
+            );
+        }
+        let mut missing: Vec<Repository> = candidates.into_iter().filter(|r| r.is_missing()).collect();
+        match missing.len() {
+            0 => Ok(FingerprintMatch::New),
+            1 => Ok(FingerprintMatch::Moved(missing.remove(0))),
+            n => {
+                warn!("{} missing repositories share fingerprint {}, not relocating any of them", n, fingerprint);
+                Ok(FingerprintMatch::New)
+            }
+        }
+    }
+
+    /// 仓库指纹（根提交 OID），空仓库或读取失败（如仓库损坏）时返回 None
+    async fn fingerprint(&self, path: &Path) -> Option<String> {
+        match self.git_client.root_commit_oid(path).await {
+            Ok(oid) => oid,
+            Err(e) => {
+                debug!("Failed to compute fingerprint of {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// 已登记的仓库在 `within` 时间内同步过
+    async fn synced_within(
+        repository_store: &dyn RepositoryPort,
+        repo_info: &DiscoveredRepo,
+        within: Duration,
+    ) -> Result<bool> {
+        let Some(repo) = repository_store
             .find_by_path(&repo_info.path.display().to_string())
-            .await?;
+            .await?
+        else {
+            return Ok(false);
+        };
+        // 同步时间在索引开始时写入，上次被中断的仓库需要继续索引
+        let progress = repository_store.index_progress(repo.id).await?;
+        if progress.is_some_and(|p| p.state == IndexState::Interrupted) {
+            return Ok(false);
+        }
+        let last_synced = repo.last_synced_at;
+        Ok(last_synced.is_some_and(|t| {
+            chrono::Utc::now().signed_duration_since(t).to_std().is_ok_and(|age| age < within)
+        }))
+    }
+
+    /// 索引单个仓库（不存在时自动登记），与已登记仓库指纹相同的重复路径跳过并返回 false
+    pub async fn index_repository(&self, repo_info: &DiscoveredRepo) -> Result<bool> {
+        // 周期内已持有读锁时再次获取不会阻塞（维护任务只用 try_write，不会排队等待写锁）
+        let _indexing = self.index_lock.read().await;
+
+        // 1. 检查仓库是否已存在；路径未登记时按指纹识别被移动或经其他路径重复发现的仓库
+        let path = repo_info.path.display().to_string();
+        // 计算指纹需要读取仓库，在登记锁外进行，已有指纹的仓库不再重复计算
+        let fingerprint = match self.repository_store.find_by_path(&path).await? {
+            Some(repo) if repo.fingerprint.is_some() => None,
+            _ => self.fingerprint(&repo_info.path).await,
+        };
+        let registration = self.registration_lock.lock().await;
+        let existing_repo = match self.repository_store.find_by_path(&path).await? {
+            Some(repo) => Some(repo),
+            None => {
+                let matched = match &fingerprint {
+                    Some(fp) => self.match_fingerprint(fp, &repo_info.path).await?,
+                    None => FingerprintMatch::New,
+                };
+                match matched {
+                    FingerprintMatch::Duplicate(canonical) => {
+                        warn!(
+                            "{} is the same repository as {} at {} (fingerprint {}), skipping",
+                            path,
+                            canonical.name,
+                            canonical.path,
+                            canonical.fingerprint.as_deref().unwrap_or_default()
+                        );
+                        return Ok(false);
+                    }
+                    FingerprintMatch::Moved(moved) => {
+                        info!("Repository {} moved: {} -> {}", moved.name, moved.path, path);
+                        self.repository_store.relocate(moved.id, &repo_info.name, &path).await?;
+                        self.repository_store.find_by_id(moved.id).await?
+                    }
+                    FingerprintMatch::New => None,
+                }
+            }
+        };
 
         let repository_id = if let Some(mut repo) = existing_repo {
             // 更新已存在的仓库
             info!("Updating existing repository: {}", repo.name);
+            if repo.fingerprint.is_none() {
+                // 较早登记的仓库没有指纹，在此补上；已存在的重复登记只提示，不自动删除
+                repo.fingerprint = fingerprint;
+                if let Some(fp) = &repo.fingerprint {
+                    for other in self.repository_store.find_by_fingerprint(fp).await? {
+                        warn!("Repository {} shares fingerprint {} with {} at {}", repo.name, fp, other.name, other.path);
+                    }
+                }
+            }
             repo.update_sync_time();
             self.repository_store.save(&repo).await?
         } else {
             // 创建新仓库
             info!("Adding new repository: {}", repo_info.name);
-            let new_repo = crate::domain::entities::Repository::new(
-                repo_info.name.clone(),
-                repo_info.path.display().to_string(),
-            );
+            let mut new_repo = Repository::new(repo_info.name.clone(), path);
+            new_repo.fingerprint = fingerprint;
             self.repository_store.save(&new_repo).await?
         };
+        drop(registration);
+        let _repository = self.lock_repository(repository_id).await;
 
         // 2. 同步仓库（添加超时和错误处理）
         info!("Syncing repository: {}", repo_info.name);
         
-        let fetch_timeout = Duration::from_secs(self.config.git.fetch_timeout_secs);
+        let config = self.config.load();
+        let fetch_timeout = Duration::from_secs(config.git.fetch_timeout_secs);
         let fetch_result = tokio::time::timeout(
             fetch_timeout,
-            self.git_client.fetch_repository(&repo_info.path)
+            self.git_client.fetch_repository(&repo_info.path, config.remote_for_path(&repo_info.path))
         ).await;
         
         match fetch_result {
@@ -176,6 +436,10 @@ impl IndexerScheduler {
                     result.branches_updated.len()
                 );
             }
+            Ok(Err(e)) if repo_info.bare => {
+                // 服务器上的裸仓库通常没有 origin，由推送更新，直接索引本地分支
+                debug!("Skipping fetch for bare repository {}: {}", repo_info.name, e);
+            }
             Ok(Err(e)) => {
                 error!("Failed to fetch repository {}: {}", repo_info.name, e);
                 info!("Continuing with local data...");
@@ -187,14 +451,21 @@ impl IndexerScheduler {
 
         // 3. 创建索引工作者并执行索引
         let worker = IndexWorker::new(
-            Arc::clone(&self.config),
+            self.config.load(),
             Arc::clone(&self.repository_store),
             Arc::clone(&self.commit_store),
             Arc::clone(&self.branch_store),
             Arc::clone(&self.git_client),
         );
 
-        worker.index_repository(repository_id, &repo_info.path).await?;
+        // span 携带仓库名，worker 内的日志事件（JSON 格式下）均可按 repo 检索
+        worker
+            .index_repository(repository_id, &repo_info.path)
+            .instrument(info_span!("index", repo = %repo_info.name, repository_id))
+            .await?;
+
+        // 新登记的仓库此前没有同步时间
+        self.repository_store.update_sync_time(repository_id).await?;
 
         Ok(true)
     }
@@ -204,18 +475,26 @@ impl IndexerScheduler {
         let repo = self.repository_store
             .find_by_id(repository_id)
             .await?
-            .ok_or_else(|| crate::shared::error::GitxError::RepositoryNotFound(
-                repository_id.to_string()
-            ))?;
+            .ok_or_else(|| GitxError::RepositoryNotFound(repository_id.to_string()))?;
+        if repo.is_missing() {
+            return Err(GitxError::RepositoryMissing(repo.name));
+        }
 
-        let repo_path = std::path::PathBuf::from(&repo.path);
+        let repo_path = validation::resolve_repo_path(
+            &repo.path,
+            &validation::allowed_base_paths(&self.config.load()),
+        )?;
         
+        let _indexing = self.index_lock.read().await;
+        let _repository = self.lock_repository(repository_id).await;
+
         // 同步仓库
-        self.git_client.fetch_repository(&repo_path).await?;
+        let remote = self.config.load().remote_for_path(&repo_path).to_string();
+        self.git_client.fetch_repository(&repo_path, &remote).await?;
 
         // 索引仓库
         let worker = IndexWorker::new(
-            Arc::clone(&self.config),
+            self.config.load(),
             Arc::clone(&self.repository_store),
             Arc::clone(&self.commit_store),
             Arc::clone(&self.branch_store),
@@ -229,6 +508,55 @@ impl IndexerScheduler {
 
         Ok(())
     }
+
+    /// 重新索引仓库（不拉取远程）；`full` 时先删除已索引的提交与分支，从头重建
+    pub async fn reindex(&self, repository_id: i64, full: bool) -> Result<IndexResult> {
+        let repo = self.repository_store
+            .find_by_id(repository_id)
+            .await?
+            .ok_or_else(|| GitxError::RepositoryNotFound(repository_id.to_string()))?;
+        if repo.is_missing() {
+            return Err(GitxError::RepositoryMissing(repo.name));
+        }
+
+        let repo_path = validation::resolve_repo_path(
+            &repo.path,
+            &validation::allowed_base_paths(&self.config.load()),
+        )?;
+
+        let _indexing = self.index_lock.read().await;
+        // 删除与重建之间不能穿插同一仓库的其他索引，否则会基于删除前的分支状态写入
+        let _repository = self.lock_repository(repository_id).await;
+        let started = Instant::now();
+        if full {
+            warn!("Full re-index of {}: dropping all indexed commits and branches", repo.name);
+            self.repository_store.clear_index(repository_id).await?;
+        }
+
+        let worker = IndexWorker::new(
+            self.config.load(),
+            Arc::clone(&self.repository_store),
+            Arc::clone(&self.commit_store),
+            Arc::clone(&self.branch_store),
+            Arc::clone(&self.git_client),
+        );
+
+        let result = worker
+            .index_repository(repository_id, &repo_path)
+            .instrument(info_span!("reindex", repo = %repo.name, repository_id))
+            .await?;
+
+        info!(
+            "{} of {} finished in {:.1}s: {} commits, {} branches",
+            if full { "Full re-index" } else { "Re-index" },
+            repo.name,
+            started.elapsed().as_secs_f64(),
+            result.commits_indexed,
+            result.branches_indexed
+        );
+
+        Ok(result)
+    }
 }
 
 #[derive(Debug, Default)]
@@ -236,4 +564,185 @@ pub struct IndexStats {
     pub repos_discovered: usize,
     pub repos_synced: usize,
     pub repos_failed: usize,
+    /// 近期已同步（min_resync_interval_secs）、与已登记仓库重复或停机时跳过的仓库
+    pub repos_skipped: usize,
+}
+
+/// 定时周期中单个仓库的处理结果
+enum IndexOutcome {
+    Indexed,
+    Skipped,
+}
+
+/// 路径尚未登记的仓库按指纹匹配的结果
+enum FingerprintMatch {
+    New,
+    /// 原路径已不存在，仓库被移动到了新路径
+    Moved(Repository),
+    /// 与已登记的仓库指向同一目录（如 bind mount），以已登记的为准
+    Duplicate(Repository),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::infrastructure::git::test_repo::TestRepo;
+    use crate::infrastructure::git::Git2Client;
+    use crate::infrastructure::sqlite::branch_repo::SqliteBranchRepository;
+    use crate::infrastructure::sqlite::commit_repo::SqliteCommitRepository;
+    use crate::infrastructure::sqlite::repository_repo::SqliteRepositoryRepository;
+    use crate::infrastructure::sqlite::run_migrations;
+    use crate::shared::config::{Config, ConfigOverrides};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// `base_path` 为空时不配置项目
+    fn test_config(base_path: Option<&Path>) -> ConfigHandle {
+        let projects = match base_path {
+            Some(base) => format!(r#"[{{ name = "test", base_path = {:?}, scan_paths = ["."] }}]"#, base),
+            None => "[]".to_string(),
+        };
+        let config: Config = toml::from_str(&format!(
+            r#"
+            projects = {}
+            [server]
+            bind_address = "127.0.0.1:0"
+            cors_origins = ["*"]
+            [database]
+            sqlite_path = ":memory:"
+            max_connections = 1
+            [git]
+            [indexer]
+            [cache]
+            "#,
+            projects
+        ))
+        .unwrap();
+        ConfigHandle::new(config, ConfigOverrides {
+            config_file: None,
+            db_path: PathBuf::from(":memory:"),
+            bind_address: None,
+            static_dir: None,
+            git_base_path: None,
+        })
+    }
+
+    fn discovered(path: &Path) -> DiscoveredRepo {
+        DiscoveredRepo {
+            name: path.file_name().unwrap().to_string_lossy().into_owned(),
+            path: path.canonicalize().unwrap(),
+            bare: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_moved_repository_is_relocated() {
+        let t = TestRepo::new();
+        let root = t.commit(&[("a.txt", "a")], &[], "first");
+        let old_path = t.clone_to("old.git", true);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        let repository_store = Arc::new(SqliteRepositoryRepository::new(pool.clone()));
+        let scheduler = IndexerScheduler::new(
+            test_config(None),
+            repository_store.clone(),
+            Arc::new(SqliteCommitRepository::new(pool.clone())),
+            Arc::new(SqliteBranchRepository::new(pool.clone())),
+            Arc::new(Git2Client::default()),
+            Arc::new(Metrics::new()),
+        );
+
+        scheduler.index_repository(&discovered(&old_path)).await.unwrap();
+        let repo = repository_store.list_all().await.unwrap().remove(0);
+        assert_eq!(repo.fingerprint, Some(root.to_string()));
+
+        // 目录移动后原记录标记为 missing，在新位置重新发现时沿用原记录
+        let new_path = t.path().join("new.git");
+        std::fs::rename(&old_path, &new_path).unwrap();
+        scheduler.reconcile_missing(&[]).await.unwrap();
+        assert!(repository_store.find_by_id(repo.id).await.unwrap().unwrap().is_missing());
+
+        scheduler.index_repository(&discovered(&new_path)).await.unwrap();
+        let repos = repository_store.list_all().await.unwrap();
+        assert_eq!(repos.len(), 1);
+        assert_eq!((repos[0].id, repos[0].name.as_str()), (repo.id, "new.git"));
+        assert_eq!(repos[0].status, RepositoryStatus::Active);
+
+        // 同一仓库经另一路径（如 bind mount）发现时不重复登记
+        let mirror = t.path().join("mirror.git");
+        std::os::unix::fs::symlink(&new_path, &mirror).unwrap();
+        let mut duplicate = discovered(&new_path);
+        duplicate.path = mirror.clone();
+        assert!(!scheduler.index_repository(&duplicate).await.unwrap());
+        assert_eq!(repository_store.list_all().await.unwrap().len(), 1);
+        std::fs::remove_file(&mirror).unwrap();
+
+        // 根提交相同的独立克隆是另一个仓库，单独登记
+        let fork = t.clone_to("fork.git", true);
+        assert!(scheduler.index_repository(&discovered(&fork)).await.unwrap());
+        let repos = repository_store.list_all().await.unwrap();
+        assert_eq!(repos.len(), 2);
+        assert!(repos.iter().all(|r| r.fingerprint == Some(root.to_string())));
+        let fork_id = repos.iter().find(|r| r.id != repo.id).unwrap().id;
+        repository_store.delete(fork_id).await.unwrap();
+        std::fs::remove_dir_all(&fork).unwrap();
+
+        // 超过宽限期仍不存在的仓库被删除
+        std::fs::remove_dir_all(&new_path).unwrap();
+        scheduler.reconcile_missing(&[]).await.unwrap();
+        assert!(repository_store.find_by_id(repo.id).await.unwrap().unwrap().missing_since.is_some());
+        sqlx::query("UPDATE repositories SET missing_since = 0").execute(&pool).await.unwrap();
+        scheduler.reconcile_missing(&[]).await.unwrap();
+        assert!(repository_store.list_all().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_full_reindex_is_serialized() {
+        let t = TestRepo::new();
+        t.commit(&[("a.txt", "a")], &[], "first");
+        t.commit(&[("a.txt", "b")], &[], "second");
+        let path = t.clone_to("repo.git", true);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        let repository_store = Arc::new(SqliteRepositoryRepository::new(pool.clone()));
+        let scheduler = IndexerScheduler::new(
+            test_config(Some(t.path())),
+            repository_store.clone(),
+            Arc::new(SqliteCommitRepository::new(pool.clone())),
+            Arc::new(SqliteBranchRepository::new(pool.clone())),
+            Arc::new(Git2Client::default()),
+            Arc::new(Metrics::new()),
+        );
+        scheduler.index_repository(&discovered(&path)).await.unwrap();
+        let id = repository_store.list_all().await.unwrap()[0].id;
+        let count = |table: &'static str| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {} WHERE repository_id = ?", table))
+                    .bind(id)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+        let (commits, branches) = (count("commits").await, count("branches").await);
+        assert!(commits > 0 && branches > 0);
+
+        // 两次全量重建依次进行，各自都从空表重建出完整的索引
+        let (a, b) = tokio::join!(scheduler.reindex(id, true), scheduler.reindex(id, true));
+        for result in [a.unwrap(), b.unwrap()] {
+            assert_eq!(result.commits_indexed as i64, commits);
+        }
+        assert_eq!((count("commits").await, count("branches").await), (commits, branches));
+    }
 }
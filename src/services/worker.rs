@@ -1,19 +1,22 @@
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 use chrono::DateTime;
-use tracing::{info, debug, error};
-use crate::domain::entities::{Commit, Branch};
+use glob::Pattern;
+use serde::Serialize;
+use tracing::{info, debug, error, warn};
+use crate::domain::entities::{Commit, Branch, IndexProgress, IndexState};
 use crate::ports::repository::RepositoryPort;
-use crate::ports::commit::CommitPort;
+use crate::ports::commit::{CommitFilter, CommitPort};
 use crate::ports::branch::BranchPort;
-use crate::ports::git::GitPort;
-use crate::shared::config::Config;
+use crate::ports::git::{GitBranch, GitPort};
+use crate::services::mailmap::Mailmap;
+use crate::shared::config::{Config, ProjectConfig};
 use crate::shared::result::Result;
 
 /// 索引工作者 - 执行实际的索引工作
 pub struct IndexWorker {
     config: Arc<Config>,
-    #[allow(dead_code)]  // 后续功能会使用
     repository_store: Arc<dyn RepositoryPort>,
     commit_store: Arc<dyn CommitPort>,
     branch_store: Arc<dyn BranchPort>,
@@ -41,45 +44,117 @@ impl IndexWorker {
     pub async fn index_repository(&self, repository_id: i64, path: &Path) -> Result<IndexResult> {
         let mut result = IndexResult::default();
 
+        if let Some(size) = self.oversized(path).await {
+            warn!(
+                "Skipping {}: object storage is {} bytes, above indexer.max_repo_bytes ({}); add it to indexer.allow_large_repos to index it",
+                path.display(), size, self.config.indexer.max_repo_bytes
+            );
+            result.skipped = true;
+            return Ok(result);
+        }
+
         // 获取所有分支
         let branches = self.git_client.list_branches(path).await?;
         
         info!("Found {} branches to index", branches.len());
 
-        // 将分支信息转换为实体并保存到数据库
+        let remote = self.config.remote_for_path(path);
+        let remote_prefix = format!("{}/", remote);
+
+        // 默认分支以 <remote>/HEAD 为准，检测失败时退回 HEAD 所在分支或第一个分支
+        let detected = match self.git_client.default_branch(path, remote).await {
+            Ok(branch) => branch.filter(|name| branches.iter().any(|b| &b.name == name)),
+            Err(e) => {
+                warn!("Failed to detect default branch: {}", e);
+                None
+            }
+        };
+        let default_branch = detected.or_else(|| {
+            branches
+                .iter()
+                .find(|b| b.is_head)
+                .or_else(|| branches.iter().find(|b| b.name.starts_with(&remote_prefix)))
+                .or_else(|| branches.first())
+                .map(|b| b.name.clone())
+        });
+        if let Some(ref name) = default_branch {
+            self.repository_store.update_default_branch(repository_id, name).await?;
+        }
+
+        let branch_filter = BranchFilter::new(self.config.project_for_path(path));
+
+        // 将分支信息转换为实体并保存到数据库；被 branch_include/branch_exclude 排除的分支不保存
         let branch_entities: Vec<Branch> = branches
             .iter()
+            .filter(|b| branch_filter.allows(&b.name))
             .map(|b| Branch {
                 id: 0, // 由数据库生成
                 repository_id,
                 name: b.name.clone(),
                 target_oid: b.target_oid.clone(),
-                is_default: b.is_head,
+                is_default: default_branch.as_ref() == Some(&b.name),
                 updated_at: chrono::Utc::now(),
+                commit_count: 0, // 由 mark_branch_indexed 更新，保存分支时不覆盖
             })
             .collect();
 
-        if !branch_entities.is_empty() {
-            self.branch_store.save_many(&branch_entities).await?;
-            info!("Saved {} branches to database", branch_entities.len());
+        if !branches.is_empty() {
+            if !branch_entities.is_empty() {
+                self.branch_store.save_many(&branch_entities).await?;
+                info!("Saved {} branches to database", branch_entities.len());
+            }
+
+            // 清理上游已删除或配置改为排除的分支及其提交
+            let keep_names: Vec<String> = branch_entities.iter().map(|b| b.name.clone()).collect();
+            let removed = self.branch_store.delete_missing(repository_id, &keep_names).await?;
+            if !removed.is_empty() {
+                info!("Removed {} deleted or excluded branches: {:?}", removed.len(), removed);
+            }
+        } else {
+            // 分支列表为空多半是临时错误，不据此清空已索引的分支
+            warn!("No branches found, skipping stale branch cleanup");
         }
 
-        for branch in branches {
-            // 只索引 remote 分支（格式如 origin/main）
-            if !branch.name.starts_with("origin/") {
-                continue;
+        let mailmap = self.load_mailmap(path, &branches, default_branch.as_deref()).await;
+
+        let mut to_index: Vec<GitBranch> = branches
+            .into_iter()
+            // 只索引配置远程的分支（格式如 origin/main），裸仓库索引本地分支
+            .filter(|b| b.name.starts_with(&remote_prefix) || b.ref_name.starts_with("refs/heads/"))
+            .filter(|b| {
+                let allowed = branch_filter.allows(&b.name);
+                if !allowed {
+                    debug!("Skipping branch {}: filtered by branch_include/branch_exclude", b.name);
+                }
+                allowed
+            })
+            .collect();
+
+        // 上次索引被中断时先索引上次完成的分支之后的分支，之前的分支放到最后（通常已无新提交）
+        let previous = self.repository_store.index_progress(repository_id).await?;
+        if let Some(last) = previous.filter(|p| p.state == IndexState::Interrupted).and_then(|p| p.last_branch) {
+            if let Some(pos) = to_index.iter().position(|b| b.name == last) {
+                info!(
+                    "Resuming interrupted indexing after branch {} ({} of {} branches remaining)",
+                    last, to_index.len() - pos - 1, to_index.len()
+                );
+                to_index.rotate_left(pos + 1);
             }
+        }
+
+        let mut progress = IndexProgress::start(repository_id, to_index.len() as i64);
+        self.repository_store.save_index_progress(&progress).await?;
 
+        for branch in to_index {
             debug!("Indexing branch: {}", branch.name);
 
-            // 构建完整的 ref 路径用于 get_commits
-            let ref_name = format!("refs/remotes/{}", branch.name);
-            
-            // 但存储时使用简短名称（origin/main）
-            match self.index_branch(repository_id, path, &ref_name, &branch.name).await {
+            // get_commits 使用完整 ref 路径，存储时使用简短名称（origin/main）
+            match self.index_branch(repository_id, path, &branch, &mailmap).await {
                 Ok(count) => {
                     result.commits_indexed += count;
                     result.branches_indexed += 1;
+                    progress.branch_done(&branch.name, &branch.target_oid, count);
+                    self.repository_store.save_index_progress(&progress).await?;
                 }
                 Err(e) => {
                     error!("Failed to index branch {}: {}", branch.name, e);
@@ -88,7 +163,13 @@ impl IndexWorker {
             }
         }
 
+        progress.finish((result.branches_failed > 0).then(|| format!("{} branches failed to index", result.branches_failed)));
+        self.repository_store.save_index_progress(&progress).await?;
+
         info!(
+            commits = result.commits_indexed,
+            branches = result.branches_indexed,
+            branches_failed = result.branches_failed,
             "Repository indexing completed: {} commits, {} branches",
             result.commits_indexed,
             result.branches_indexed
@@ -97,40 +178,145 @@ impl IndexWorker {
         Ok(result)
     }
 
-    /// 索引单个分支（增量更新）
+    /// 仓库对象库超过 `max_repo_bytes` 且不在白名单中时返回其大小
+    async fn oversized(&self, path: &Path) -> Option<u64> {
+        let indexer = &self.config.indexer;
+        if indexer.max_repo_bytes == 0 {
+            return None;
+        }
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if indexer.allow_large_repos.iter().any(|a| a == name || Path::new(a) == path) {
+            return None;
+        }
+
+        match self.git_client.object_storage_size(path).await {
+            Ok(size) => (size > indexer.max_repo_bytes).then_some(size),
+            Err(e) => {
+                warn!("Failed to measure object storage of {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// 加载 mailmap：仓库默认分支上的 `.mailmap`，再叠加配置的 `mailmap_file`
+    async fn load_mailmap(&self, path: &Path, branches: &[GitBranch], default_branch: Option<&str>) -> Mailmap {
+        let mut mailmap = Mailmap::default();
+
+        if self.config.indexer.mailmap {
+            let ref_name = default_branch
+                .and_then(|name| branches.iter().find(|b| b.name == name))
+                .map(|b| b.ref_name.as_str())
+                .unwrap_or("HEAD");
+            match self.git_client.read_file(path, ref_name, ".mailmap").await {
+                Ok(Some(content)) => mailmap.extend(&String::from_utf8_lossy(&content)),
+                Ok(None) => {}
+                Err(e) => warn!("Failed to read .mailmap at {}: {}", ref_name, e),
+            }
+        }
+
+        if let Some(ref file) = self.config.indexer.mailmap_file {
+            match tokio::fs::read_to_string(file).await {
+                Ok(content) => mailmap.extend(&content),
+                Err(e) => warn!("Failed to read mailmap file {}: {}", file.display(), e),
+            }
+        }
+
+        mailmap
+    }
+
+    /// 索引单个分支（增量更新，检测到强制推送时重建该分支）
     async fn index_branch(
         &self,
         repository_id: i64,
         path: &Path,
-        ref_name: &str,        // 完整ref路径，如 refs/remotes/origin/main
-        branch_name: &str,     // 简短名称，如 origin/main
+        branch: &GitBranch,
+        mailmap: &Mailmap,
     ) -> Result<usize> {
-        // 获取最后索引的提交
-        let last_indexed = self.commit_store.get_latest_commit(repository_id, branch_name).await?;
-        let last_indexed_oid = last_indexed.map(|c| c.oid);
+        let ref_name = branch.ref_name.as_str();   // 完整ref路径，如 refs/remotes/origin/main
+        let branch_name = branch.name.as_str();    // 简短名称，如 origin/main
+
+        // 上次索引到的分支头；早于 indexed_tip_oid 列的数据退回按提交时间推断
+        let mut last_indexed_oid = match self.branch_store.indexed_tip(repository_id, branch_name).await? {
+            Some(oid) => Some(oid),
+            None => self
+                .commit_store
+                .get_latest_commit(repository_id, branch_name)
+                .await?
+                .map(|c| c.oid),
+        };
 
         if let Some(ref oid) = last_indexed_oid {
             debug!("Found last indexed commit for {}: {}", branch_name, oid);
+
+            // 新的分支头不是已索引提交的后代，说明历史被改写，增量遍历会遗漏改写后的提交
+            if !self.git_client.is_descendant_of(path, &branch.target_oid, oid).await? {
+                let removed = self.commit_store.delete_by_branch(repository_id, branch_name).await?;
+                warn!(
+                    branch = branch_name,
+                    "Force push detected on {} ({} is not a descendant of {}), re-indexing branch after removing {} commits",
+                    branch_name, branch.target_oid, oid, removed
+                );
+                last_indexed_oid = None;
+            }
         }
 
+        // 首次索引可以取回完整历史，之后每个周期只取有限的新提交
+        let limit = match last_indexed_oid {
+            None => match self.config.indexer.initial_max_commits {
+                0 => usize::MAX,
+                n => n,
+            },
+            Some(_) => self.config.indexer.incremental_max_commits,
+        };
+
         // 获取新提交
-        let commits = self.git_client.get_commits(
+        let mut commits = self.git_client.get_commits(
             path,
             ref_name,  // 使用完整ref路径
-            self.config.indexer.max_commits_per_branch,
+            limit,
             last_indexed_oid.as_deref(),
+            self.config.indexer.index_merges,
+            self.config.indexer.walk,
         ).await?;
 
+        // since_oid 失效（如被 squash）时会取回已索引的提交，先过滤掉再处理
+        let oids: Vec<String> = commits.iter().map(|c| c.oid.clone()).collect();
+        let existing = self.commit_store.existing_oids(repository_id, branch_name, &oids).await?;
+        if !existing.is_empty() {
+            debug!("Skipping {} already indexed commits on {}", existing.len(), branch_name);
+            commits.retain(|c| !existing.contains(&c.oid));
+        }
+
         if commits.is_empty() {
             debug!("No new commits for branch {}", branch_name);
+            if last_indexed_oid.as_deref() != Some(branch.target_oid.as_str()) {
+                self.mark_branch_indexed(repository_id, branch).await?;
+            }
             return Ok(0);
         }
 
+        // 可选：计算文件变更统计（跳过合并提交）
+        let mut stats = HashMap::new();
+        if self.config.indexer.compute_stats {
+            let oids: Vec<String> = commits
+                .iter()
+                .filter(|c| c.parent_oids.len() <= 1)
+                .map(|c| c.oid.clone())
+                .collect();
+            match self.git_client.commit_stats(path, &oids).await {
+                Ok(results) => stats.extend(oids.into_iter().zip(results)),
+                Err(e) => warn!("Failed to compute commit stats for branch {}: {}", branch_name, e),
+            }
+        }
+
         // 转换为领域实体
         let domain_commits: Vec<Commit> = commits
             .into_iter()
             .map(|c| {
-                Commit::new(
+                let commit_stats = stats.get(&c.oid).copied();
+                let (name, email) = mailmap.resolve(&c.author_name, &c.author_email);
+                let (name, email) = (name.to_string(), email.to_string());
+                let commit = Commit::new(
                     repository_id,
                     c.oid,
                     branch_name.to_string(),  // 存储简短名称
@@ -144,6 +330,13 @@ impl IndexWorker {
                 )
                 .with_message(c.message.unwrap_or_default())
                 .with_parents(c.parent_oids)
+                .with_tree(c.tree_oid)
+                .with_canonical_author(name, email);
+
+                match commit_stats {
+                    Some(s) => commit.with_stats(s.files_changed as i64, s.insertions as i64, s.deletions as i64),
+                    None => commit,
+                }
             })
             .collect();
 
@@ -152,21 +345,321 @@ impl IndexWorker {
         // 使用bulk_insert批量插入，比逐个save快很多
         match self.commit_store.bulk_insert(&domain_commits).await {
             Ok(inserted) => {
-                info!("Indexed {} commits for branch {}", inserted, branch_name);
+                info!(branch = branch_name, commits = inserted, "Indexed {} commits for branch {}", inserted, branch_name);
             }
             Err(e) => {
                 error!("Failed to bulk insert commits: {}", e);
                 return Err(e);
             }
         }
+        // 提交写入成功后才推进分支头；中途失败时下次从旧分支头重新遍历，已写入的提交会被过滤
+        self.mark_branch_indexed(repository_id, branch).await?;
 
         Ok(count)
     }
+
+    /// 分支索引完成：推进已索引的分支头并刷新分支提交数
+    async fn mark_branch_indexed(&self, repository_id: i64, branch: &GitBranch) -> Result<()> {
+        self.branch_store.set_indexed_tip(repository_id, &branch.name, &branch.target_oid).await?;
+        let count = self
+            .commit_store
+            .count_by_repository(repository_id, &CommitFilter::branch(Some(&branch.name)))
+            .await?;
+        self.branch_store.set_commit_count(repository_id, &branch.name, count).await
+    }
+}
+
+/// 项目的 `branch_include` / `branch_exclude` 分支过滤
+struct BranchFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
 }
 
-#[derive(Debug, Default)]
+impl BranchFilter {
+    /// 不属于任何项目的仓库索引全部分支；模式已在加载配置时校验
+    fn new(project: Option<&ProjectConfig>) -> Self {
+        let compile = |patterns: &[String]| -> Vec<Pattern> {
+            patterns.iter().filter_map(|p| Pattern::new(p).ok()).collect()
+        };
+        Self {
+            include: project.map(|p| compile(&p.branch_include)).unwrap_or_default(),
+            exclude: project.map(|p| compile(&p.branch_exclude)).unwrap_or_default(),
+        }
+    }
+
+    fn allows(&self, name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| p.matches(name)))
+            && !self.exclude.iter().any(|p| p.matches(name))
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
 pub struct IndexResult {
     pub commits_indexed: usize,
     pub branches_indexed: usize,
     pub branches_failed: usize,
+    /// 仓库超过 `indexer.max_repo_bytes`，未索引
+    pub skipped: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::Repository;
+    use crate::ports::commit::CommitOrder;
+    use crate::infrastructure::git::test_repo::TestRepo;
+    use crate::infrastructure::git::Git2Client;
+    use crate::infrastructure::sqlite::branch_repo::SqliteBranchRepository;
+    use crate::infrastructure::sqlite::commit_repo::SqliteCommitRepository;
+    use crate::infrastructure::sqlite::repository_repo::SqliteRepositoryRepository;
+    use crate::infrastructure::sqlite::run_migrations;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn test_config() -> Config {
+        toml::from_str(
+            r#"
+            projects = []
+            [server]
+            bind_address = "127.0.0.1:0"
+            cors_origins = ["*"]
+            [database]
+            sqlite_path = ":memory:"
+            max_connections = 1
+            [git]
+            [indexer]
+            [cache]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_index_bare_repository() {
+        let t = TestRepo::new();
+        let first = t.commit(&[("a.txt", "a")], &[], "first");
+        t.commit(&[("a.txt", "b")], &[], "second");
+        let default = t.head_ref().trim_start_matches("refs/heads/").to_string();
+        let bare = t.clone_to("server.git", true);
+        // 裸克隆只包含默认分支，其他分支直接在服务器仓库上创建
+        let server = git2::Repository::open(&bare).unwrap();
+        server.branch("feature", &server.find_commit(first).unwrap(), false).unwrap();
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        let repository_store = Arc::new(SqliteRepositoryRepository::new(pool.clone()));
+        let commit_store = Arc::new(SqliteCommitRepository::new(pool.clone()));
+        let branch_store = Arc::new(SqliteBranchRepository::new(pool.clone()));
+        let repo_id = repository_store
+            .save(&Repository::new("server".to_string(), bare.display().to_string()))
+            .await
+            .unwrap();
+
+        let worker = IndexWorker::new(
+            Arc::new(test_config()),
+            repository_store.clone(),
+            commit_store.clone(),
+            branch_store.clone(),
+            Arc::new(Git2Client::default()),
+        );
+        let result = worker.index_repository(repo_id, &bare).await.unwrap();
+        assert_eq!((result.branches_indexed, result.commits_indexed, result.branches_failed), (2, 3, 0));
+
+        // 默认分支取自裸仓库的 HEAD
+        let repo = repository_store.find_by_id(repo_id).await.unwrap().unwrap();
+        assert_eq!(repo.default_branch, default);
+        let branches = branch_store.find_by_repository(repo_id).await.unwrap();
+        let defaults: Vec<&str> = branches.iter().filter(|b| b.is_default).map(|b| b.name.as_str()).collect();
+        assert_eq!(defaults, vec![default.as_str()]);
+        let counts: Vec<(&str, i64)> = branches.iter().map(|b| (b.name.as_str(), b.commit_count)).collect();
+        assert_eq!(counts, vec![(default.as_str(), 2), ("feature", 1)]);
+
+        assert_eq!(commit_store.count_by_repository(repo_id, &CommitFilter::branch(Some(&default))).await.unwrap(), 2);
+        assert_eq!(commit_store.count_by_repository(repo_id, &CommitFilter::branch(Some("feature"))).await.unwrap(), 1);
+
+        let indexed = commit_store.find_by_oid(repo_id, &first.to_string()).await.unwrap().unwrap();
+        let tree_oid = server.find_commit(first).unwrap().tree_id().to_string();
+        assert_eq!(indexed.tree_oid, Some(tree_oid));
+
+        // 记录已索引的分支头，作为下次增量索引的起点
+        assert_eq!(branch_store.indexed_tip(repo_id, "feature").await.unwrap(), Some(first.to_string()));
+
+        let progress = repository_store.index_progress(repo_id).await.unwrap().unwrap();
+        assert_eq!(progress.state, IndexState::Completed);
+        assert_eq!((progress.branches_total, progress.branches_done, progress.commits_indexed), (2, 2, 3));
+
+        // 再次索引是增量的
+        let result = worker.index_repository(repo_id, &bare).await.unwrap();
+        assert_eq!(result.commits_indexed, 0);
+
+        // 模拟在 feature 完成后进程退出：重启时标记为中断，下次从 feature 之后的分支开始
+        let mut interrupted = IndexProgress::start(repo_id, 2);
+        interrupted.branch_done("feature", &first.to_string(), 1);
+        repository_store.save_index_progress(&interrupted).await.unwrap();
+        assert_eq!(repository_store.mark_index_interrupted().await.unwrap(), 1);
+        worker.index_repository(repo_id, &bare).await.unwrap();
+        let progress = repository_store.index_progress(repo_id).await.unwrap().unwrap();
+        assert_eq!(progress.state, IndexState::Completed);
+        assert_eq!(progress.last_branch.as_deref(), Some("feature"));
+
+        // 强制推送：feature 指向一个与旧历史无关的提交
+        let sig = git2::Signature::now("dev", "dev@example.com").unwrap();
+        let tree = server.find_commit(first).unwrap().tree().unwrap();
+        let rewritten = server.commit(None, &sig, &sig, "rewritten", &tree, &[]).unwrap();
+        server.reference("refs/heads/feature", rewritten, true, "force push").unwrap();
+
+        let result = worker.index_repository(repo_id, &bare).await.unwrap();
+        assert_eq!(result.commits_indexed, 1);
+        let feature = commit_store
+            .list_by_repository(repo_id, &CommitFilter::branch(Some("feature")), CommitOrder::default(), 10, 0)
+            .await
+            .unwrap();
+        let oids: Vec<String> = feature.into_iter().map(|c| c.oid).collect();
+        assert_eq!(oids, vec![rewritten.to_string()]);
+        assert_eq!(branch_store.indexed_tip(repo_id, "feature").await.unwrap(), Some(rewritten.to_string()));
+
+        // 超过对象库大小上限的仓库跳过，白名单中的仓库不受限制
+        let mut config = test_config();
+        config.indexer.max_repo_bytes = 1;
+        let limited = |config: Config| IndexWorker::new(
+            Arc::new(config),
+            repository_store.clone(),
+            commit_store.clone(),
+            branch_store.clone(),
+            Arc::new(Git2Client::default()),
+        );
+        let result = limited(config.clone()).index_repository(repo_id, &bare).await.unwrap();
+        assert!(result.skipped);
+        config.indexer.allow_large_repos = vec!["server.git".to_string()];
+        let result = limited(config).index_repository(repo_id, &bare).await.unwrap();
+        assert!(!result.skipped && result.branches_indexed == 2);
+
+        // 配置改为排除 feature 后，已索引的分支与提交一并删除
+        let mut config = test_config();
+        config.projects = vec![toml::from_str(&format!(
+            "name = \"demo\"\nbase_path = {:?}\nscan_paths = [\".\"]\nbranch_exclude = [\"feature\"]",
+            t.path().display().to_string()
+        ))
+        .unwrap()];
+        let result = limited(config).index_repository(repo_id, &bare).await.unwrap();
+        assert_eq!(result.branches_indexed, 1);
+        let names: Vec<String> = branch_store.find_by_repository(repo_id).await.unwrap().into_iter().map(|b| b.name).collect();
+        assert_eq!(names, vec![default.clone()]);
+        assert_eq!(commit_store.count_by_repository(repo_id, &CommitFilter::branch(Some("feature"))).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_initial_and_incremental_limits() {
+        let t = TestRepo::new();
+        for i in 0..5 {
+            t.commit(&[("a.txt", &i.to_string())], &[], &format!("commit {}", i));
+        }
+        let default = t.head_ref().trim_start_matches("refs/heads/").to_string();
+        let bare = t.clone_to("server.git", true);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        let repository_store = Arc::new(SqliteRepositoryRepository::new(pool.clone()));
+        let commit_store = Arc::new(SqliteCommitRepository::new(pool.clone()));
+        let branch_store = Arc::new(SqliteBranchRepository::new(pool.clone()));
+        let repo_id = repository_store
+            .save(&Repository::new("server".to_string(), bare.display().to_string()))
+            .await
+            .unwrap();
+
+        let mut config = test_config();
+        config.indexer.initial_max_commits = 3;
+        config.indexer.incremental_max_commits = 1;
+        let worker = IndexWorker::new(
+            Arc::new(config),
+            repository_store.clone(),
+            commit_store.clone(),
+            branch_store.clone(),
+            Arc::new(Git2Client::default()),
+        );
+
+        // 首次索引使用 initial_max_commits
+        let result = worker.index_repository(repo_id, &bare).await.unwrap();
+        assert_eq!(result.commits_indexed, 3);
+
+        // 之后的周期使用 incremental_max_commits
+        let server = git2::Repository::open(&bare).unwrap();
+        let sig = git2::Signature::now("dev", "dev@example.com").unwrap();
+        let head_ref = format!("refs/heads/{}", default);
+        for message in ["sixth", "seventh"] {
+            let parent = server.find_reference(&head_ref).unwrap().peel_to_commit().unwrap();
+            server.commit(Some(&head_ref), &sig, &sig, message, &parent.tree().unwrap(), &[&parent]).unwrap();
+        }
+        let result = worker.index_repository(repo_id, &bare).await.unwrap();
+        assert_eq!(result.commits_indexed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_mailmap_keeps_raw_author() {
+        let t = TestRepo::new();
+        let oid = t.commit(&[(".mailmap", "Test Person <tester@example.com>\n")], &[], "add mailmap");
+        let bare = t.clone_to("server.git", true);
+        let mailmap_file = t.path().join("extra.mailmap");
+        std::fs::write(&mailmap_file, "<proper@example.com> <tester@example.com>\n").unwrap();
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        let repository_store = Arc::new(SqliteRepositoryRepository::new(pool.clone()));
+        let commit_store = Arc::new(SqliteCommitRepository::new(pool.clone()));
+        let repo_id = repository_store
+            .save(&Repository::new("server".to_string(), bare.display().to_string()))
+            .await
+            .unwrap();
+
+        let mut config = test_config();
+        config.indexer.mailmap = true;
+        config.indexer.mailmap_file = Some(mailmap_file);
+        let worker = IndexWorker::new(
+            Arc::new(config),
+            repository_store,
+            commit_store.clone(),
+            Arc::new(SqliteBranchRepository::new(pool.clone())),
+            Arc::new(Git2Client::default()),
+        );
+        worker.index_repository(repo_id, &bare).await.unwrap();
+
+        // 仓库内的 .mailmap 改名字，mailmap_file 改邮箱，原始值保存在 raw_author_*
+        let commit = commit_store.find_by_oid(repo_id, &oid.to_string()).await.unwrap().unwrap();
+        assert_eq!((commit.author_name.as_str(), commit.author_email.as_str()), ("Test Person", "proper@example.com"));
+        assert_eq!(commit.raw_author_name.as_deref(), Some("Tester"));
+        assert_eq!(commit.raw_author_email.as_deref(), Some("tester@example.com"));
+    }
+
+    #[test]
+    fn test_branch_filter() {
+        let project: ProjectConfig = toml::from_str(
+            r#"
+            name = "demo"
+            base_path = "/tmp"
+            scan_paths = ["."]
+            branch_include = ["origin/main", "origin/release/*"]
+            branch_exclude = ["origin/release/old-*"]
+            "#,
+        )
+        .unwrap();
+        let filter = BranchFilter::new(Some(&project));
+        assert!(filter.allows("origin/main"));
+        assert!(filter.allows("origin/release/1.2"));
+        assert!(!filter.allows("origin/release/old-1.0"));
+        assert!(!filter.allows("origin/feature/x"));
+
+        // 未配置时索引全部分支
+        assert!(BranchFilter::new(None).allows("origin/feature/x"));
+    }
 }
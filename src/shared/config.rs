@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::watch;
 use crate::shared::result::Result;
 
 /// 应用配置
@@ -11,14 +15,35 @@ pub struct Config {
     pub git: GitConfig,
     pub indexer: IndexerConfig,
     pub cache: CacheConfig,
+    #[serde(default)]
+    pub webhook: WebhookConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub admin: AdminConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
     pub projects: Vec<ProjectConfig>,
 }
 
 /// 服务器配置
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct ServerConfig {
     pub bind_address: SocketAddr,
     pub cors_origins: Vec<String>,
+    /// 静态资源目录，相对路径基于当前工作目录
+    #[serde(default = "default_static_dir")]
+    pub static_dir: PathBuf,
+    /// 在响应中附加 `Server-Timing` 头（浏览器开发者工具可直接查看服务端耗时）
+    #[serde(default)]
+    pub server_timing: bool,
+    /// 按客户端 `Accept-Encoding` 压缩响应（br/gzip），CPU 紧张的主机可关闭
+    #[serde(default = "default_compression")]
+    pub compression: bool,
 }
 
 impl Default for ServerConfig {
@@ -26,15 +51,45 @@ impl Default for ServerConfig {
         Self {
             bind_address: "127.0.0.1:8080".parse().unwrap(),
             cors_origins: vec!["http://localhost:3000".to_string()],
+            static_dir: default_static_dir(),
+            server_timing: false,
+            compression: default_compression(),
         }
     }
 }
 
+fn default_static_dir() -> PathBuf {
+    PathBuf::from("statics")
+}
+
+fn default_compression() -> bool {
+    true
+}
+
 /// 数据库配置
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct DatabaseConfig {
     pub sqlite_path: PathBuf,
     pub max_connections: u32,
+    /// SQLite 日志模式（delete | truncate | persist | memory | wal | off），WAL 允许读写并发
+    #[serde(default = "default_journal_mode")]
+    pub journal_mode: String,
+    /// 同步级别（off | normal | full | extra），WAL 下 normal 已足够安全
+    #[serde(default = "default_synchronous")]
+    pub synchronous: String,
+    /// 数据库被锁定时等待的毫秒数，超时后返回 busy 错误
+    #[serde(default = "default_busy_timeout_ms")]
+    pub busy_timeout_ms: u64,
+    /// 单次借出连接执行查询的最长毫秒数（一条语句或一个事务），超时中断并返回 503；0 表示不限制。
+    /// 迁移、VACUUM、索引批量写入与按仓库/分支的删除不受此限制
+    #[serde(default)]
+    pub statement_timeout_ms: u64,
+    /// 定期执行 `PRAGMA optimize` 的间隔（秒），0 表示不启用数据库维护任务
+    #[serde(default)]
+    pub optimize_interval_secs: u64,
+    /// 两次 `VACUUM` 之间的最小间隔（秒），只在没有索引任务时执行，0 表示从不 VACUUM
+    #[serde(default)]
+    pub vacuum_interval_secs: u64,
 }
 
 impl Default for DatabaseConfig {
@@ -42,15 +97,52 @@ impl Default for DatabaseConfig {
         Self {
             sqlite_path: PathBuf::from("gitx.db"),
             max_connections: 10,
+            journal_mode: default_journal_mode(),
+            synchronous: default_synchronous(),
+            busy_timeout_ms: default_busy_timeout_ms(),
+            statement_timeout_ms: 0,
+            optimize_interval_secs: 0,
+            vacuum_interval_secs: 0,
         }
     }
 }
 
+fn default_journal_mode() -> String {
+    "wal".to_string()
+}
+
+fn default_synchronous() -> String {
+    "normal".to_string()
+}
+
+fn default_busy_timeout_ms() -> u64 {
+    5000
+}
+
 /// Git 配置
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub struct GitConfig {
     pub ssh_key_path: Option<PathBuf>,
     pub fetch_timeout_secs: u64,
+    /// diff 时检测重命名/复制（超大 diff 可关闭以提升性能）
+    pub rename_detection: bool,
+    /// HTTPS 远程认证用户名（GitHub PAT 可使用任意用户名）
+    pub https_username: Option<String>,
+    /// HTTPS 远程认证 token，未设置时读取环境变量 GITX_HTTPS_TOKEN
+    pub https_token: Option<Secret>,
+    /// 按仓库名覆盖的 token
+    pub https_tokens: HashMap<String, Secret>,
+    /// blame 允许的最大文件大小（字节）
+    pub max_blame_bytes: usize,
+    /// raw 文件下载允许的最大文件大小（字节）
+    pub max_raw_bytes: usize,
+    /// 提交详情中计算单词级 diff（开销较大，默认关闭）
+    pub intraline_diff: bool,
+    /// 提交详情渲染 diff 的最大增删行数，超过时只显示统计与下载链接（0 表示不限制）
+    pub max_diff_lines: usize,
+    /// 缓存的已打开仓库句柄数（0 表示每次操作重新打开）
+    pub repo_cache_size: usize,
 }
 
 impl Default for GitConfig {
@@ -58,17 +150,50 @@ impl Default for GitConfig {
         Self {
             ssh_key_path: None,
             fetch_timeout_secs: 300,
+            rename_detection: true,
+            https_username: None,
+            https_token: None,
+            https_tokens: HashMap::new(),
+            max_blame_bytes: 1024 * 1024,
+            max_raw_bytes: 10 * 1024 * 1024,
+            intraline_diff: false,
+            max_diff_lines: 20_000,
+            repo_cache_size: 32,
         }
     }
 }
 
 /// 索引器配置
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct IndexerConfig {
     pub enabled: bool,
     pub interval_secs: u64,
-    pub max_commits_per_branch: usize,
+    /// 定时索引跳过距上次同步不足该秒数的仓库（0 表示每个周期都同步；webhook 与手动触发不受限制）
+    pub min_resync_interval_secs: u64,
+    /// 分支首次索引（尚无已存储的提交）时最多取回的提交数（0 表示不限制）
+    pub initial_max_commits: usize,
+    /// 之后每个周期每个分支最多取回的新提交数
+    #[serde(alias = "max_commits_per_branch")]
+    pub incremental_max_commits: usize,
+    /// 同时索引的仓库数，阻塞 git 操作的并发上限为其 2 倍（索引与页面请求共用）
     pub worker_threads: usize,
+    /// 是否索引合并提交（默认跳过，topological 模式始终索引）
+    pub index_merges: bool,
+    /// 提交遍历方式
+    pub walk: WalkMode,
+    /// 索引时计算每个提交的文件变更统计（合并提交除外）
+    pub compute_stats: bool,
+    /// 索引时按仓库默认分支上的 `.mailmap` 规范化作者
+    pub mailmap: bool,
+    /// 额外的 mailmap 文件（所有仓库共用，条目优先于仓库内的 `.mailmap`）
+    pub mailmap_file: Option<PathBuf>,
+    /// 对象库（objects 目录）超过该字节数的仓库跳过索引（0 表示不限制）
+    pub max_repo_bytes: u64,
+    /// 不受 `max_repo_bytes` 限制的仓库（目录名或完整路径）
+    pub allow_large_repos: Vec<String>,
+    /// 路径不存在的仓库标记为 missing 后保留的秒数，超过后删除其索引（0 表示从不删除）
+    pub missing_grace_secs: u64,
 }
 
 impl Default for IndexerConfig {
@@ -76,17 +201,32 @@ impl Default for IndexerConfig {
         Self {
             enabled: true,
             interval_secs: 300,
-            max_commits_per_branch: 2000,
+            min_resync_interval_secs: 0,
+            initial_max_commits: 100_000,
+            incremental_max_commits: 2000,
             worker_threads: 4,
+            index_merges: false,
+            walk: WalkMode::Linear,
+            compute_stats: false,
+            mailmap: false,
+            mailmap_file: None,
+            max_repo_bytes: 0,
+            allow_large_repos: Vec::new(),
+            missing_grace_secs: 7 * 86400,
         }
     }
 }
 
 /// 缓存配置
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub struct CacheConfig {
     pub max_capacity: u64,
     pub ttl_secs: u64,
+    /// 提交详情的磁盘缓存目录，未设置时只使用内存缓存
+    pub disk_path: Option<PathBuf>,
+    /// 磁盘缓存总大小上限（字节），超出时按最近访问时间淘汰
+    pub disk_max_bytes: u64,
 }
 
 impl Default for CacheConfig {
@@ -94,46 +234,250 @@ impl Default for CacheConfig {
         Self {
             max_capacity: 10000,
             ttl_secs: 3600,
+            disk_path: None,
+            disk_max_bytes: 1024 * 1024 * 1024,
+        }
+    }
+}
+
+/// Webhook 配置
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct WebhookConfig {
+    /// 共享密钥，设置后请求需携带 `X-Webhook-Secret` 头
+    pub secret: Option<Secret>,
+}
+
+/// 管理接口配置
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AdminConfig {
+    /// 管理令牌，设置后管理接口需携带 `X-Admin-Token` 头
+    pub token: Option<Secret>,
+}
+
+/// 访问认证配置
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// 启用后 `/api/*` 与写操作需携带 API key（`Authorization: Bearer <key>` 或 Basic 认证的密码）
+    pub enabled: bool,
+    /// API key 的 SHA-256（十六进制），配置文件中不保存明文
+    pub api_keys: Vec<String>,
+    /// 只读页面也要求认证（默认只保护 API 与写操作）
+    pub protect_ui: bool,
+}
+
+/// 页面外观配置，用于替换站点名称与样式
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct UiConfig {
+    /// 站点名称，显示在浏览器标题与首页标题栏
+    pub site_title: String,
+    /// 标题栏 logo 图片地址
+    pub logo_url: Option<String>,
+    /// 额外样式表地址，在内置样式之后加载，可覆盖默认样式
+    pub custom_css_url: Option<String>,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            site_title: "GitX".to_string(),
+            logo_url: None,
+            custom_css_url: None,
+        }
+    }
+}
+
+/// 日志配置
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// 日志级别（EnvFilter 语法，如 `info,gitx=debug`），设置 `RUST_LOG` 时以环境变量为准
+    pub level: String,
+    pub format: LogFormat,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            format: LogFormat::Pretty,
+        }
+    }
+}
+
+/// 限流配置（按客户端 IP + 仓库计数）
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// cherry-pick、push 等写操作每分钟允许的请求数，0 表示不限制
+    pub mutating_per_minute: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            mutating_per_minute: 30,
         }
     }
 }
 
+/// 索引时的提交遍历方式
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WalkMode {
+    /// 按时间遍历，遇到上次索引的提交即停止，可跳过合并提交（较快）
+    #[default]
+    Linear,
+    /// 拓扑 + 时间排序，索引所有可达提交（包括合并提交及其全部父提交）
+    Topological,
+}
+
+/// 日志输出格式
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Compact,
+    /// 每行一个 JSON 对象，便于日志采集系统检索
+    Json,
+}
+
+/// 敏感配置值，Debug 输出时隐藏内容
+#[derive(Clone, Deserialize, Serialize, PartialEq)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"***\"")
+    }
+}
+
+/// 命令行 `-p` 指定路径时的递归扫描深度
+pub const DEFAULT_SCAN_DEPTH: usize = 3;
+
 /// 项目配置
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ProjectConfig {
     pub name: String,
     pub base_path: PathBuf,
     pub scan_paths: Vec<String>,
+    /// 在 scan_path 下递归查找仓库的最大深度，0 表示只检查 scan_path 本身
+    #[serde(default)]
+    pub max_depth: usize,
+    /// 同步与索引使用的远程名称
+    #[serde(default = "default_remote")]
+    pub remote: String,
+    /// 扫描时排除的仓库（glob，不区分大小写，匹配目录名或完整路径）
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// 只索引匹配的分支（glob，匹配 `origin/main` 形式的名称），为空时索引全部分支
+    #[serde(default)]
+    pub branch_include: Vec<String>,
+    /// 不索引匹配的分支，优先于 `branch_include`
+    #[serde(default)]
+    pub branch_exclude: Vec<String>,
+    /// 索引优先级，数值大的项目在每个周期中先扫描、先索引
+    #[serde(default)]
+    pub priority: i32,
+    /// 提交信息中 issue 引用的链接模板
+    #[serde(default)]
+    pub linkify: LinkifyConfig,
+}
+
+/// 未配置 `remote` 时使用的远程名称
+pub const DEFAULT_REMOTE: &str = "origin";
+
+fn default_remote() -> String {
+    DEFAULT_REMOTE.to_string()
+}
+
+/// 提交信息自动链接配置
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LinkifyConfig {
+    /// `#123` 的链接模板，`{number}` 替换为编号，如 `https://github.com/org/repo/issues/{number}`
+    pub issue_url: Option<String>,
+    /// `org/repo#123` 的链接模板，`{repo}` 替换为 `org/repo`，如 `https://github.com/{repo}/issues/{number}`
+    pub repo_issue_url: Option<String>,
 }
 
 
 impl Config {
-    /// 从文件加载配置
-    pub fn from_file(path: &str) -> Result<Self> {
+    /// 读取配置文件，`.json` 扩展名按 JSON 解析，其余按 TOML 解析
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
         let content = std::fs::read_to_string(path)?;
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+            return serde_json::from_str(&content)
+                .map_err(|e| crate::shared::error::GitxError::Config(e.to_string()));
+        }
         let config: Config = toml::from_str(&content)
             .map_err(|e| crate::shared::error::GitxError::Config(e.to_string()))?;
         Ok(config)
     }
 
+    /// 查找配置文件
+    ///
+    /// 指定了 `--config` 时只使用该文件（不存在则报错），否则依次查找
+    /// `./config.toml`、`$XDG_CONFIG_HOME/gitx/config.toml`（未设置时为 `~/.config`）、`/etc/gitx/config.toml`
+    pub fn locate_file(explicit: Option<&Path>) -> Result<Option<PathBuf>> {
+        if let Some(path) = explicit {
+            if !path.is_file() {
+                return Err(crate::shared::error::GitxError::Config(format!(
+                    "config file {} does not exist",
+                    path.display()
+                )));
+            }
+            return Ok(Some(path.to_path_buf()));
+        }
+
+        let xdg_config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+        let mut candidates = vec![PathBuf::from("config.toml")];
+        if let Some(dir) = xdg_config_home {
+            candidates.push(dir.join("gitx").join("config.toml"));
+        }
+        candidates.push(PathBuf::from("/etc/gitx/config.toml"));
+
+        Ok(candidates.into_iter().find(|p| p.is_file()))
+    }
+
     /// 从命令行参数和文件加载配置
     pub fn from_args_and_file(
+        config_file: Option<&Path>,
         db_path: PathBuf,
         bind_address: Option<SocketAddr>,
+        static_dir: Option<PathBuf>,
         git_base_path: Option<PathBuf>,
     ) -> Result<Self> {
         // 尝试加载配置文件
-        let config_path = std::path::Path::new("config.toml");
-        let mut config = if config_path.exists() {
-            match Self::from_file("config.toml") {
+        let mut config = if let Some(config_path) = Self::locate_file(config_file)? {
+            tracing::info!("Loading configuration from {}", config_path.display());
+            match Self::from_file(&config_path) {
                 Ok(cfg) => cfg,
                 Err(e) => {
-                    tracing::error!("Found config.toml but failed to load it: {}", e);
+                    tracing::error!("Found {} but failed to load it: {}", config_path.display(), e);
                     return Err(e);
                 }
             }
         } else {
-            tracing::info!("config.toml not found, using default configuration");
+            tracing::info!("No config file found, using default configuration");
             // 使用默认配置
             Config {
                 server: ServerConfig::default(),
@@ -141,6 +485,12 @@ impl Config {
                 git: GitConfig::default(),
                 indexer: IndexerConfig::default(),
                 cache: CacheConfig::default(),
+                webhook: WebhookConfig::default(),
+                logging: LoggingConfig::default(),
+                rate_limit: RateLimitConfig::default(),
+                admin: AdminConfig::default(),
+                auth: AuthConfig::default(),
+                ui: UiConfig::default(),
                 projects: vec![],
             }
         };
@@ -149,6 +499,9 @@ impl Config {
         if let Some(addr) = bind_address {
             config.server.bind_address = addr;
         }
+        if let Some(dir) = static_dir {
+            config.server.static_dir = dir;
+        }
         config.database.sqlite_path = db_path;
 
         // 如果命令行提供了git路径，优先使用命令行参数
@@ -163,6 +516,13 @@ impl Config {
                 name: project_name,
                 base_path,
                 scan_paths: vec![".".to_string()],  // 扫描整个目录
+                max_depth: DEFAULT_SCAN_DEPTH,
+                remote: default_remote(),
+                exclude: Vec::new(),
+                branch_include: Vec::new(),
+                branch_exclude: Vec::new(),
+                priority: 0,
+                linkify: LinkifyConfig::default(),
             }];
         } else if config.projects.is_empty() {
             // 如果没有命令行参数且配置文件也没有项目，则无法发现仓库
@@ -172,11 +532,273 @@ impl Config {
         Ok(config)
     }
 
-    /// 保存配置到文件
-    pub fn save_to_file(&self, path: &str) -> Result<()> {
-        let content = toml::to_string_pretty(self)
-            .map_err(|e| crate::shared::error::GitxError::Config(e.to_string()))?;
-        std::fs::write(path, content)?;
+    /// 校验配置，启动与热加载时调用
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |msg: String| Err(crate::shared::error::GitxError::Config(msg));
+
+        if self.indexer.interval_secs == 0 {
+            return invalid("indexer.interval_secs must be greater than 0".to_string());
+        }
+        if self.indexer.incremental_max_commits == 0 {
+            return invalid("indexer.incremental_max_commits must be greater than 0".to_string());
+        }
+        if self.auth.enabled && self.auth.api_keys.is_empty() {
+            return invalid("auth.enabled requires at least one entry in auth.api_keys".to_string());
+        }
+        for key in &self.auth.api_keys {
+            if key.len() != 64 || !key.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return invalid("auth.api_keys must be SHA-256 hex digests (64 hex characters)".to_string());
+            }
+        }
+        if self.ui.site_title.trim().is_empty() {
+            return invalid("ui.site_title must not be empty".to_string());
+        }
+        if self.server.cors_origins.is_empty() {
+            return invalid("server.cors_origins must not be empty".to_string());
+        }
+        for project in &self.projects {
+            if project.name.trim().is_empty() {
+                return invalid(format!("project at {} has an empty name", project.base_path.display()));
+            }
+            if project.scan_paths.is_empty() {
+                return invalid(format!("project {} has no scan_paths", project.name));
+            }
+            if project.remote.trim().is_empty() || project.remote.contains('/') {
+                return invalid(format!("project {} has an invalid remote name: {:?}", project.name, project.remote));
+            }
+            let patterns = [("exclude", &project.exclude), ("branch_include", &project.branch_include), ("branch_exclude", &project.branch_exclude)];
+            for (field, list) in patterns {
+                for pattern in list {
+                    if let Err(e) = glob::Pattern::new(pattern) {
+                        return invalid(format!("project {} has an invalid {} pattern {:?}: {}", project.name, field, pattern, e));
+                    }
+                }
+            }
+        }
         Ok(())
     }
+
+    /// 仓库路径所属的项目（按 base_path 前缀匹配）
+    pub fn project_for_path(&self, path: &Path) -> Option<&ProjectConfig> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.projects.iter().find(|project| {
+            project
+                .base_path
+                .canonicalize()
+                .is_ok_and(|base| path.starts_with(base))
+        })
+    }
+
+    /// 仓库同步使用的远程名称，不属于任何项目时为 `origin`
+    pub fn remote_for_path(&self, path: &Path) -> &str {
+        self.project_for_path(path)
+            .map(|project| project.remote.as_str())
+            .unwrap_or(DEFAULT_REMOTE)
+    }
+
+    /// 用于展示的副本：SSH 私钥路径、token、共享密钥与 API key 替换为 `***`
+    pub fn redacted(&self) -> Config {
+        const REDACTED: &str = "***";
+        let mut config = self.clone();
+        let git = &mut config.git;
+        if git.ssh_key_path.is_some() {
+            git.ssh_key_path = Some(PathBuf::from(REDACTED));
+        }
+        if git.https_token.is_some() {
+            git.https_token = Some(Secret::new(REDACTED.to_string()));
+        }
+        for token in git.https_tokens.values_mut() {
+            *token = Secret::new(REDACTED.to_string());
+        }
+        if config.webhook.secret.is_some() {
+            config.webhook.secret = Some(Secret::new(REDACTED.to_string()));
+        }
+        if config.admin.token.is_some() {
+            config.admin.token = Some(Secret::new(REDACTED.to_string()));
+        }
+        for key in config.auth.api_keys.iter_mut() {
+            *key = REDACTED.to_string();
+        }
+        config
+    }
+}
+
+/// 启动时的命令行覆盖项，热加载时重新套用
+#[derive(Debug, Clone)]
+pub struct ConfigOverrides {
+    /// `--config` 指定的配置文件
+    pub config_file: Option<PathBuf>,
+    pub db_path: PathBuf,
+    pub bind_address: Option<SocketAddr>,
+    pub static_dir: Option<PathBuf>,
+    pub git_base_path: Option<PathBuf>,
+}
+
+/// 可热加载的配置句柄
+///
+/// 可热加载：indexer（worker_threads 除外）、projects、webhook、rate_limit、admin、auth、ui。
+/// 需要重启：server、database、git、cache、logging、indexer.worker_threads，
+/// 热加载时保留旧值。
+#[derive(Clone)]
+pub struct ConfigHandle {
+    current: Arc<watch::Sender<Arc<Config>>>,
+    overrides: Arc<ConfigOverrides>,
+}
+
+impl ConfigHandle {
+    pub fn new(config: Config, overrides: ConfigOverrides) -> Self {
+        Self {
+            current: Arc::new(watch::Sender::new(Arc::new(config))),
+            overrides: Arc::new(overrides),
+        }
+    }
+
+    /// 当前配置快照
+    pub fn load(&self) -> Arc<Config> {
+        self.current.borrow().clone()
+    }
+
+    /// 订阅配置变更
+    pub fn subscribe(&self) -> watch::Receiver<Arc<Config>> {
+        self.current.subscribe()
+    }
+
+    /// 重新读取配置文件，校验通过后原子替换；失败时保留旧配置
+    pub fn reload(&self) -> Result<Arc<Config>> {
+        let mut config = Config::from_args_and_file(
+            self.overrides.config_file.as_deref(),
+            self.overrides.db_path.clone(),
+            self.overrides.bind_address,
+            self.overrides.static_dir.clone(),
+            self.overrides.git_base_path.clone(),
+        )?;
+        config.validate()?;
+
+        let current = self.load();
+
+        // 运行期无法生效的部分沿用旧值
+        warn_if_changed("server", &current.server, &config.server);
+        warn_if_changed("database", &current.database, &config.database);
+        warn_if_changed("git", &current.git, &config.git);
+        warn_if_changed("cache", &current.cache, &config.cache);
+        warn_if_changed("logging", &current.logging, &config.logging);
+        warn_if_changed("indexer.worker_threads", &current.indexer.worker_threads, &config.indexer.worker_threads);
+        config.server = current.server.clone();
+        config.database = current.database.clone();
+        config.git = current.git.clone();
+        config.cache = current.cache.clone();
+        config.logging = current.logging.clone();
+        config.indexer.worker_threads = current.indexer.worker_threads;
+
+        let config = Arc::new(config);
+        self.current.send_replace(config.clone());
+        tracing::info!("Configuration reloaded: {} projects", config.projects.len());
+        Ok(config)
+    }
+}
+
+fn warn_if_changed<T: PartialEq>(section: &str, old: &T, new: &T) {
+    if old != new {
+        tracing::warn!("{} changed in the config file but requires a restart; keeping the running value", section);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> Config {
+        Config {
+            server: ServerConfig::default(),
+            database: DatabaseConfig::default(),
+            git: GitConfig::default(),
+            indexer: IndexerConfig::default(),
+            cache: CacheConfig::default(),
+            webhook: WebhookConfig::default(),
+            logging: LoggingConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            admin: AdminConfig::default(),
+            auth: AuthConfig::default(),
+            ui: UiConfig::default(),
+            projects: vec![ProjectConfig {
+                name: "demo".to_string(),
+                base_path: PathBuf::from("/tmp"),
+                scan_paths: vec![".".to_string()],
+                max_depth: 0,
+                remote: default_remote(),
+                exclude: Vec::new(),
+                branch_include: Vec::new(),
+                branch_exclude: Vec::new(),
+                priority: 0,
+                linkify: LinkifyConfig::default(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(test_config().validate().is_ok());
+
+        let mut config = test_config();
+        config.indexer.interval_secs = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = test_config();
+        config.projects[0].scan_paths.clear();
+        assert!(config.validate().is_err());
+
+        let mut config = test_config();
+        config.ui.site_title = " ".to_string();
+        assert!(config.validate().is_err());
+
+        let mut config = test_config();
+        config.indexer.incremental_max_commits = 0;
+        assert!(config.validate().is_err());
+
+        // 旧配置名 max_commits_per_branch 作为 incremental_max_commits 的别名
+        let indexer: IndexerConfig = toml::from_str("max_commits_per_branch = 7").unwrap();
+        assert_eq!(indexer.incremental_max_commits, 7);
+    }
+
+    #[test]
+    fn test_redacted() {
+        let mut config = test_config();
+        config.git.ssh_key_path = Some(PathBuf::from("/home/git/.ssh/id_ed25519"));
+        config.git.https_token = Some(Secret::new("ghp_secret".to_string()));
+        config.git.https_tokens.insert("repo".to_string(), Secret::new("ghp_repo".to_string()));
+        config.webhook.secret = Some(Secret::new("hook-secret".to_string()));
+        config.admin.token = Some(Secret::new("admin-token".to_string()));
+        config.auth.api_keys = vec!["a".repeat(64)];
+
+        let json = serde_json::to_string(&config.redacted()).unwrap();
+        for secret in ["id_ed25519", "ghp_secret", "ghp_repo", "hook-secret", "admin-token", &"a".repeat(64)] {
+            assert!(!json.contains(secret), "{} leaked", secret);
+        }
+        // 启动日志以 Debug 格式输出配置
+        assert!(!format!("{:?}", config).contains("hook-secret"));
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["git"]["https_tokens"]["repo"], "***");
+        assert_eq!(value["auth"]["api_keys"], serde_json::json!(["***"]));
+        // 未配置的项保持为空，非敏感项不变
+        assert_eq!(test_config().redacted().git.ssh_key_path, None);
+        assert_eq!(value["projects"][0]["name"], "demo");
+    }
+
+    #[test]
+    fn test_locate_explicit_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gitx.toml");
+        assert!(Config::locate_file(Some(&path)).is_err());
+
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(Config::locate_file(Some(&path)).unwrap(), Some(path));
+    }
+
+    #[test]
+    fn test_json_config_parse_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gitx.json");
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(matches!(Config::from_file(&path), Err(crate::shared::error::GitxError::Config(_))));
+    }
 }
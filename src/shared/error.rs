@@ -1,5 +1,6 @@
-use axum::http::StatusCode;
+use axum::http::{header, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
+use serde::Serialize;
 
 /// 统一的错误类型
 #[derive(Debug, thiserror::Error)]
@@ -20,10 +21,18 @@ pub enum GitxError {
     #[error("Serialization error: {0}")]
     Serialization(#[from] bincode::Error),
 
+    /// 服务端的 JSON 解析/序列化错误（已存储的数据、导出）；请求体由 axum 的 `Json` 提取器校验，不经过此变体
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
     /// 仓库未找到
     #[error("Repository not found: {0}")]
     RepositoryNotFound(String),
 
+    /// 仓库已登记，但磁盘上的路径不存在（被移动或删除）
+    #[error("Repository is missing on disk: {0}")]
+    RepositoryMissing(String),
+
     /// 提交未找到
     #[error("Commit not found: {0}")]
     CommitNotFound(String),
@@ -32,6 +41,10 @@ pub enum GitxError {
     #[error("Reference not found: {0}")]
     ReferenceNotFound(String),
 
+    /// 文件未找到
+    #[error("File not found: {0}")]
+    FileNotFound(String),
+
     /// 无效的路径
     #[error("Invalid path: {0}")]
     InvalidPath(String),
@@ -40,18 +53,34 @@ pub enum GitxError {
     #[error("Invalid OID: {0}")]
     InvalidOid(String),
 
+    /// OID 前缀匹配到多个提交
+    #[error("Ambiguous OID prefix: {0}")]
+    AmbiguousOid(String),
+
     /// 无效的引用
     #[error("Invalid reference")]
     InvalidRef,
 
+    /// 无效的请求
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// 资源冲突
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    /// 未授权
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// 请求过于频繁，附带建议的重试等待秒数
+    #[error("Too many requests, retry after {0}s")]
+    RateLimited(u64),
+
     /// 配置错误
     #[error("Configuration error: {0}")]
     Config(String),
 
-    /// 解析错误
-    #[error("Parse error: {0}")]
-    Parse(String),
-
     /// 内部错误
     #[error("Internal error: {0}")]
     Internal(String),
@@ -65,16 +94,66 @@ pub enum GitxError {
     Template(#[from] askama::Error),
 }
 
-/// 用于 Axum 的错误响应实现
+impl GitxError {
+    /// 稳定的机器可读错误码（API 客户端据此判断错误类型）
+    pub fn code(&self) -> &'static str {
+        match self {
+            GitxError::Git(_) => "git_error",
+            GitxError::Sqlx(e) if is_query_timeout(e) => "query_timeout",
+            GitxError::Sqlx(_) => "database_error",
+            GitxError::Io(_) => "io_error",
+            GitxError::Serialization(_) => "serialization_error",
+            GitxError::RepositoryNotFound(_) => "repository_not_found",
+            GitxError::RepositoryMissing(_) => "repository_missing",
+            GitxError::CommitNotFound(_) => "commit_not_found",
+            GitxError::ReferenceNotFound(_) => "reference_not_found",
+            GitxError::FileNotFound(_) => "file_not_found",
+            GitxError::InvalidPath(_) => "invalid_path",
+            GitxError::InvalidOid(_) => "invalid_oid",
+            GitxError::AmbiguousOid(_) => "ambiguous_oid",
+            GitxError::InvalidRef => "invalid_ref",
+            GitxError::InvalidRequest(_) => "invalid_request",
+            GitxError::Conflict(_) => "conflict",
+            GitxError::Unauthorized(_) => "unauthorized",
+            GitxError::RateLimited(_) => "rate_limited",
+            GitxError::Config(_) => "config_error",
+            GitxError::Internal(_) | GitxError::Other(_) => "internal_error",
+            GitxError::Template(_) => "template_error",
+            GitxError::Json(_) => "json_error",
+        }
+    }
+}
+
+/// 查询因超过 `database.statement_timeout_ms` 被中断（`SQLITE_INTERRUPT`）
+fn is_query_timeout(e: &sqlx::Error) -> bool {
+    matches!(e, sqlx::Error::Database(db) if db.code().as_deref() == Some("9"))
+}
+
+/// 错误响应体，附加在响应扩展中，由中间件决定是否以 JSON 输出
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorBody {
+    pub error: String,
+    pub code: &'static str,
+}
+
+/// 用于 Axum 的错误响应实现（纯文本，API 路由由 `json_errors` 中间件转为 JSON）
 impl IntoResponse for GitxError {
     fn into_response(self) -> Response {
         let (status, message) = match &self {
             GitxError::RepositoryNotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
+            GitxError::RepositoryMissing(_) => (StatusCode::GONE, self.to_string()),
             GitxError::CommitNotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
             GitxError::ReferenceNotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
+            GitxError::FileNotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
             GitxError::InvalidPath(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             GitxError::InvalidOid(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            GitxError::AmbiguousOid(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            GitxError::InvalidRequest(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            GitxError::Conflict(_) => (StatusCode::CONFLICT, self.to_string()),
+            GitxError::Unauthorized(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
+            GitxError::RateLimited(_) => (StatusCode::TOO_MANY_REQUESTS, self.to_string()),
             GitxError::Config(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+            GitxError::Sqlx(e) if is_query_timeout(e) => (StatusCode::SERVICE_UNAVAILABLE, "Database query timed out".to_string()),
             GitxError::Sqlx(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string()),
             GitxError::Git(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Git operation failed".to_string()),
             _ => (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string()),
@@ -82,7 +161,16 @@ impl IntoResponse for GitxError {
 
         tracing::error!("Request error: {}", self);
 
-        (status, message).into_response()
+        let body = ErrorBody {
+            error: message.clone(),
+            code: self.code(),
+        };
+        let mut response = (status, message).into_response();
+        if let GitxError::RateLimited(secs) = self {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response.extensions_mut().insert(body);
+        response
     }
 }
 
@@ -0,0 +1,20 @@
+use tracing_subscriber::EnvFilter;
+use crate::shared::config::{LogFormat, LoggingConfig};
+
+/// 按配置初始化全局日志，`RUST_LOG` 优先于配置中的级别
+pub fn init(config: &LoggingConfig) {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(&config.level))
+        .unwrap_or_else(|e| {
+            eprintln!("Invalid logging.level {:?}: {}, falling back to info", config.level, e);
+            EnvFilter::new("info")
+        });
+
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    match config.format {
+        LogFormat::Pretty => subscriber.pretty().init(),
+        LogFormat::Compact => subscriber.compact().init(),
+        // 事件字段平铺到顶层，当前 span（如 index 的 repo）单独输出在 span 字段中
+        LogFormat::Json => subscriber.json().flatten_event(true).with_span_list(false).init(),
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 运行指标注册表（调度器与 HTTP 中间件共同更新，/metrics 读取）
+#[derive(Default)]
+pub struct Metrics {
+    index_cycles_total: AtomicU64,
+    last_cycle_duration_ms: AtomicU64,
+    last_repos_discovered: AtomicU64,
+    repos_synced_total: AtomicU64,
+    repos_failed_total: AtomicU64,
+    /// (method, route, status) -> 请求数
+    http_requests: Mutex<HashMap<(String, String, u16), u64>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次索引周期的结果
+    pub fn record_index_cycle(&self, discovered: usize, synced: usize, failed: usize, duration: Duration) {
+        self.index_cycles_total.fetch_add(1, Ordering::Relaxed);
+        self.last_cycle_duration_ms.store(duration.as_millis() as u64, Ordering::Relaxed);
+        self.last_repos_discovered.store(discovered as u64, Ordering::Relaxed);
+        self.repos_synced_total.fetch_add(synced as u64, Ordering::Relaxed);
+        self.repos_failed_total.fetch_add(failed as u64, Ordering::Relaxed);
+    }
+
+    /// 记录一次 HTTP 请求
+    pub fn record_http_request(&self, method: &str, route: &str, status: u16) {
+        let mut requests = self.http_requests.lock().unwrap_or_else(|e| e.into_inner());
+        *requests
+            .entry((method.to_string(), route.to_string(), status))
+            .or_insert(0) += 1;
+    }
+
+    /// 以 Prometheus 文本格式输出（仓库级数据由调用方传入）
+    pub fn render(&self, repositories_total: usize, repo_commits: &[(String, i64)]) -> String {
+        let mut out = String::new();
+
+        write_metric(&mut out, "gitx_repositories_total", "gauge", "Number of registered repositories");
+        let _ = writeln!(out, "gitx_repositories_total {}", repositories_total);
+
+        write_metric(&mut out, "gitx_repository_commits", "gauge", "Indexed commit rows per repository");
+        for (name, count) in repo_commits {
+            let _ = writeln!(out, "gitx_repository_commits{{repository=\"{}\"}} {}", escape_label(name), count);
+        }
+
+        write_metric(&mut out, "gitx_index_cycles_total", "counter", "Completed index cycles");
+        let _ = writeln!(out, "gitx_index_cycles_total {}", self.index_cycles_total.load(Ordering::Relaxed));
+
+        write_metric(&mut out, "gitx_index_cycle_duration_seconds", "gauge", "Duration of the last index cycle");
+        let _ = writeln!(
+            out,
+            "gitx_index_cycle_duration_seconds {:.3}",
+            self.last_cycle_duration_ms.load(Ordering::Relaxed) as f64 / 1000.0
+        );
+
+        write_metric(&mut out, "gitx_index_repos_discovered", "gauge", "Repositories discovered in the last index cycle");
+        let _ = writeln!(out, "gitx_index_repos_discovered {}", self.last_repos_discovered.load(Ordering::Relaxed));
+
+        write_metric(&mut out, "gitx_index_repos_synced_total", "counter", "Repositories successfully indexed");
+        let _ = writeln!(out, "gitx_index_repos_synced_total {}", self.repos_synced_total.load(Ordering::Relaxed));
+
+        write_metric(&mut out, "gitx_index_repos_failed_total", "counter", "Repositories that failed to index");
+        let _ = writeln!(out, "gitx_index_repos_failed_total {}", self.repos_failed_total.load(Ordering::Relaxed));
+
+        write_metric(&mut out, "gitx_http_requests_total", "counter", "HTTP requests by route and status");
+        let requests = self.http_requests.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries: Vec<_> = requests.iter().collect();
+        entries.sort();
+        for ((method, route, status), count) in entries {
+            let _ = writeln!(
+                out,
+                "gitx_http_requests_total{{method=\"{}\",route=\"{}\",status=\"{}\"}} {}",
+                escape_label(method),
+                escape_label(route),
+                status,
+                count
+            );
+        }
+
+        out
+    }
+}
+
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// 转义 Prometheus 标签值
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -1,3 +1,6 @@
 pub mod error;
 pub mod config;
 pub mod result;
+pub mod metrics;
+pub mod logging;
+pub mod validation;
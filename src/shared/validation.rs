@@ -0,0 +1,131 @@
+use std::path::{Component, Path, PathBuf};
+use crate::shared::config::Config;
+use crate::shared::error::GitxError;
+use crate::shared::result::Result;
+
+/// 校验仓库名：只能是单个路径段，不允许分隔符与 `..`
+pub fn validate_repo_name(name: &str) -> Result<()> {
+    let invalid = name.is_empty()
+        || name.contains('/')
+        || name.contains('\\')
+        || name.contains('\0')
+        || name.contains("..");
+
+    if invalid {
+        return Err(GitxError::InvalidPath(format!("invalid repository name: {}", name)));
+    }
+    Ok(())
+}
+
+/// 配置中允许访问的根目录（项目 base_path 及其扫描路径）
+pub fn allowed_base_paths(config: &Config) -> Vec<PathBuf> {
+    config
+        .projects
+        .iter()
+        .flat_map(|project| {
+            std::iter::once(project.base_path.clone())
+                .chain(project.scan_paths.iter().map(|scan| project.base_path.join(scan)))
+        })
+        .collect()
+}
+
+/// 解析仓库路径并确认其位于允许的根目录之下，返回规范化后的路径
+pub fn resolve_repo_path(path: &str, allowed_bases: &[PathBuf]) -> Result<PathBuf> {
+    let raw = Path::new(path);
+    if raw.components().any(|c| c == Component::ParentDir) {
+        return Err(GitxError::InvalidPath(format!("repository path escapes base: {}", path)));
+    }
+
+    // 规范化会解析符号链接，防止通过链接跳出根目录
+    let resolved = raw
+        .canonicalize()
+        .map_err(|_| GitxError::InvalidPath(format!("repository path does not exist: {}", path)))?;
+
+    let allowed = allowed_bases
+        .iter()
+        .filter_map(|base| base.canonicalize().ok())
+        .any(|base| resolved.starts_with(&base));
+
+    if !allowed {
+        return Err(GitxError::InvalidPath(format!("repository path outside allowed base: {}", path)));
+    }
+    Ok(resolved)
+}
+
+/// 两个路径是否指向磁盘上的同一目录：规范化后相同（符号链接），或设备号与 inode 相同（bind mount）
+pub async fn same_location(a: &Path, b: &Path) -> bool {
+    if let (Ok(ca), Ok(cb)) = (tokio::fs::canonicalize(a).await, tokio::fs::canonicalize(b).await) {
+        if ca == cb {
+            return true;
+        }
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if let (Ok(ma), Ok(mb)) = (tokio::fs::metadata(a).await, tokio::fs::metadata(b).await) {
+            return ma.dev() == mb.dev() && ma.ino() == mb.ino();
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_repo_name() {
+        assert!(validate_repo_name("gitx").is_ok());
+        assert!(validate_repo_name("my-repo.git").is_ok());
+
+        for name in ["", "..", "a/b", "a\\b", "../etc", "a..b"] {
+            assert!(matches!(validate_repo_name(name), Err(GitxError::InvalidPath(_))), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_resolve_repo_path_stays_within_base() {
+        let base = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let repo = base.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        let bases = vec![base.path().to_path_buf()];
+
+        let resolved = resolve_repo_path(repo.to_str().unwrap(), &bases).unwrap();
+        assert_eq!(resolved, repo.canonicalize().unwrap());
+
+        let escaped = format!("{}/../{}", repo.display(), outside.path().display());
+        assert!(resolve_repo_path(&escaped, &bases).is_err());
+        assert!(resolve_repo_path(outside.path().to_str().unwrap(), &bases).is_err());
+        assert!(resolve_repo_path(base.path().join("missing").to_str().unwrap(), &bases).is_err());
+
+        // 指向根目录外的符号链接同样拒绝
+        #[cfg(unix)]
+        {
+            let link = base.path().join("link");
+            std::os::unix::fs::symlink(outside.path(), &link).unwrap();
+            assert!(resolve_repo_path(link.to_str().unwrap(), &bases).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_same_location() {
+        let base = tempfile::tempdir().unwrap();
+        let a = base.path().join("a");
+        let b = base.path().join("b");
+        std::fs::create_dir(&a).unwrap();
+        std::fs::create_dir(&b).unwrap();
+
+        assert!(same_location(&a, &a).await);
+        assert!(same_location(&a, &base.path().join("b/../a")).await);
+        assert!(!same_location(&a, &b).await);
+        assert!(!same_location(&a, &base.path().join("missing")).await);
+
+        #[cfg(unix)]
+        {
+            let link = base.path().join("link");
+            std::os::unix::fs::symlink(&a, &link).unwrap();
+            assert!(same_location(&link, &a).await);
+        }
+    }
+}